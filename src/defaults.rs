@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuoteTokenData {
     pub address: String,
     pub name: String,
@@ -7,7 +7,25 @@ pub struct QuoteTokenData {
     pub decimals: u8,
     pub logo: String,
 }
-#[derive(Debug, Serialize, Deserialize)]
+// pump.fun mints use 6 decimals; used when a mint's decimals are unknown
+pub const DEFAULT_TOKEN_DECIMALS: u8 = 6;
+
+// Holders counted in token-info's top_n_holders_percent unless `top_n` is given
+pub const DEFAULT_TOP_HOLDERS: u32 = 10;
+
+// Row cap for the holders CSV export; the JSON endpoint stops at 1000
+pub const HOLDERS_EXPORT_LIMIT: u64 = 10_000;
+
+// Owners that can never move tokens again; LP held here counts as burned. The System Program
+// isn't one: indexers use it as a placeholder owner, so it says nothing about a burn.
+pub const LP_BURN_ADDRESSES: [&str; 1] = ["1nc1nerator11111111111111111111111111111111"];
+
+// How far a pool's quote reserve may drift from its initial reserve and still count as locked
+pub const LP_RESERVE_TOLERANCE: f64 = 0.1;
+
+// Compile-time definition of a built-in quote token. Responses always carry the
+// registry's QuoteTokenData instead, so this is deliberately not serializable.
+#[derive(Debug)]
 pub struct QuoteToken {
     pub address: &'static str,
     pub name: &'static str,
@@ -1,41 +1,91 @@
 use crate::{
+    metrics::METRICS,
+    middleware::{
+        auth::{ApiKeyAuth, require_api_key},
+        body_limit::body_limit_layer,
+        cors::cors_layer,
+        metrics::track_http,
+        rate_limit::{RateLimiter, rate_limit, throttle_auth_failures},
+        request_id::request_id,
+    },
     models::{
         pool::{DBPool, NewPool},
         swap::DBSwap,
     },
     routes::{
         // get_trades::get_trades,
+        authority::get_token_authority,
+        creator::{get_creator_migrations, get_creator_pools},
+        curve_history::get_curve_history,
+        dev_activity::get_dev_activity,
+        exists::get_pool_exists,
+        factory_stats::get_factory_stats,
+        first_buyers::get_first_buyers,
+        funding::get_funding,
         get_candlestick::get_candlestick,
         get_holders::get_holders,
-        get_pair_info::get_pair_info,
-        get_token_info::get_token_info,
+        get_pair_info::{get_pair_info, get_pair_info_batch},
+        get_swap::get_swap,
+        get_token_info::{get_token_info, get_token_info_batch},
         get_top_traders::{self, get_top_traders},
         get_trader_details::{self, get_trader_details},
         get_trades::get_trades,
+        holder_distribution::get_holder_distribution,
+        holders_csv::get_holders_csv,
+        holders_history::get_holders_history,
+        internal::{internal_addr_from_env, internal_routes, public_internal_routes},
         last_transaction::get_last_transaction,
+        liquidity_locked::get_liquidity_locked,
+        market_stats::get_market_stats,
+        pnl::get_wallet_pnl,
         pool_report::get_pool_report,
-        pulse::pulse,
+        pulse::{get_pool_pulse, pulse, pulse_count},
+        recent_pools::get_recent_pools,
         search::search_pools, // search::search_pools,
+        sse::sse_pulse,
+        trending::get_trending_pools,
+        webhooks::{create_webhook, delete_webhook},
+        whale_trades::get_whale_trades,
+    },
+    services::{
+        clickhouse::ClickhouseService,
+        pulse_feed::PulseFeed,
+        redis::{
+            NewPairDedup, PUBSUB_CHANNELS, connect_pubsub, connect_with_backoff, report_malformed,
+        },
+        webhooks::WebhookRegistry,
+    },
+    state::AppState,
+    websocket::{
+        emit_or_shed, idle_timeout_from_env, max_buffer_size_from_env,
+        new_pool_event::on_new_pool_event,
+        on_connect, pool_swaps_room, reap_idle_sockets, relay_pulse_feed, require_socket_api_key,
+        swap_stream::{PendingSnapshots, SWAP_STREAM_NS, on_swap_stream_connect, relay_swap},
+        wallet_trades_room,
     },
-    services::{clickhouse::ClickhouseService, redis::subscribe_and_process},
-    websocket::{new_pool_event::on_new_pool_event, on_connect},
 };
 use axum::{
     Router,
-    routing::{get, post},
+    routing::{delete, get, post},
 };
 use futures_util::StreamExt;
+use std::{net::SocketAddr, sync::Arc};
+
+use socketioxide::{SocketIo, handler::ConnectHandler};
 
-use socketioxide::SocketIo;
+use tokio::{signal, sync::watch};
 
 use tower::ServiceBuilder;
-use tower_http::cors::CorsLayer;
-use tracing::info;
+use tower_http::compression::CompressionLayer;
+use tracing::{error, info, warn};
 use tracing_subscriber::FmtSubscriber;
 mod defaults;
+mod metrics;
+mod middleware;
 mod models;
 mod routes;
 mod services;
+mod state;
 mod types;
 mod utils;
 mod websocket;
@@ -53,87 +103,311 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // let redis = RedisService::init().await;
     tracing::subscriber::set_global_default(FmtSubscriber::default())?;
 
-    let (layer, io) = SocketIo::new_layer();
+    let pending_snapshots = PendingSnapshots::default();
+    let api_key_auth = Arc::new(ApiKeyAuth::from_env());
+    let (layer, io) = SocketIo::builder()
+        .max_buffer_size(max_buffer_size_from_env())
+        .with_state(clickhouse.clone())
+        .with_state(pending_snapshots.clone())
+        .with_state(api_key_auth.clone())
+        .build_layer();
     let io_clone = io.clone();
     let clickhouse_clone = clickhouse.clone();
-    tokio::spawn(async move {
+    // New pools are computed once here; socket.io, SSE and webhooks each subscribe to the
+    // feed in the shared state
+    let state = AppState {
+        clickhouse,
+        pulse_feed: PulseFeed::from_env(),
+        webhooks: WebhookRegistry::default(),
+    };
+    let pulse_feed_clone = state.pulse_feed.clone();
+    tokio::spawn(relay_pulse_feed(io.clone(), state.pulse_feed.subscribe()));
+    tokio::spawn(state.webhooks.clone().deliver(state.pulse_feed.subscribe()));
+    let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+    let pubsub_task = tokio::spawn(async move {
         let redis_url =
             std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://localhost:6379/".to_string());
-        let client = redis::Client::open(redis_url).unwrap();
-        let mut pubsub = client.get_async_pubsub().await.unwrap();
+        'connection: loop {
+            let Some(mut pubsub) = connect_with_backoff(
+                || connect_pubsub(&redis_url, &PUBSUB_CHANNELS),
+                &mut shutdown_rx,
+            )
+            .await
+            else {
+                info!("Stopping redis pubsub loop");
+                break;
+            };
+            let Some(mut new_pair_dedup) =
+                connect_with_backoff(|| NewPairDedup::connect(&redis_url), &mut shutdown_rx).await
+            else {
+                info!("Stopping redis pubsub loop");
+                break;
+            };
+            let mut stream = pubsub.on_message();
 
-        pubsub.subscribe("swap_created").await.unwrap();
-        pubsub.subscribe("pool_created").await.unwrap();
-
-        let mut stream = pubsub.on_message();
-
-        while let Some(msg) = stream.next().await {
-            let channel: String = msg.get_channel_name().to_string();
-            let payload: String = msg.get_payload().unwrap();
+            loop {
+                let msg = tokio::select! {
+                    msg = stream.next() => msg,
+                    _ = shutdown_rx.changed() => {
+                        info!("Stopping redis pubsub loop");
+                        break 'connection;
+                    }
+                };
+                let Some(msg) = msg else {
+                    warn!("Redis pubsub connection closed, reconnecting");
+                    continue 'connection;
+                };
+                let channel: String = msg.get_channel_name().to_string();
+                let payload: String = match msg.get_payload() {
+                    Ok(payload) => payload,
+                    Err(e) => {
+                        report_malformed(&channel, "", e);
+                        continue;
+                    }
+                };
 
-            match channel.as_str() {
-                "swap_created" => {
-                    if let Ok(data) = serde_json::from_str::<DBSwap>(&payload) {
-                        let _ = io_clone
-                            .emit(format!("s:{}", data.pool_address), &data)
-                            .await;
+                match channel.as_str() {
+                    "swap_created" => {
+                        match serde_json::from_str::<DBSwap>(&payload) {
+                            Ok(data) => {
+                                METRICS.ws_event("swap_created", 1);
+                                // Only sockets that subscribed to this pool's swaps get it
+                                let room = pool_swaps_room(&data.pool_address);
+                                emit_or_shed(io_clone.to(room.clone()).sockets(), &room, &data);
+                                relay_swap(&io_clone, &pending_snapshots, &data);
+                                // ... and so do sockets following the trader's wallet
+                                let room = wallet_trades_room(&data.creator);
+                                emit_or_shed(io_clone.to(room.clone()).sockets(), &room, &data);
+                            }
+                            Err(e) => report_malformed(&channel, &payload, e),
+                        }
                     }
-                }
-                "pool_created" => {
-                    if let Ok(data) = serde_json::from_str::<NewPool>(&payload) {
-                        // println!("data: {:?}", data);
+                    "pool_created" => {
+                        let data = match serde_json::from_str::<NewPool>(&payload) {
+                            Ok(data) => data,
+                            Err(e) => {
+                                report_malformed(&channel, &payload, e);
+                                continue;
+                            }
+                        };
                         match on_new_pool_event(data, &clickhouse_clone).await {
-                            Ok(pulse_data) => {
-                                let _ = io_clone.emit("new-pair", &pulse_data).await;
+                            Ok(Some(pulse_data)) => {
+                                // Fails open: a Redis error shouldn't hide a new pool
+                                let first = new_pair_dedup
+                                    .claim(&pulse_data.pair_address)
+                                    .await
+                                    .unwrap_or_else(|e| {
+                                        warn!("new-pair dedup check failed: {}", e);
+                                        true
+                                    });
+                                if first {
+                                    pulse_feed_clone.publish(pulse_data);
+                                }
                             }
+                            Ok(None) => {}
                             Err(error) => {
-                                println!("Error: {:?}", error.to_string());
+                                error!("Failed to build pulse data for new pool: {}", error);
                             }
                         }
                     }
+                    _ => {}
                 }
-                _ => {}
             }
         }
     });
 
     // Connection to the socket start
-    io.ns("/", on_connect);
+    // socket.io is layered outside the HTTP routes, so it checks API keys on its own
+    io.ns("/", on_connect.with(require_socket_api_key));
+    io.ns(
+        SWAP_STREAM_NS,
+        on_swap_stream_connect.with(require_socket_api_key),
+    );
+    if let Some(idle_timeout) = idle_timeout_from_env() {
+        tokio::spawn(reap_idle_sockets(io.clone(), idle_timeout));
+    }
     // Connection to the socket end
-    println!("Starting server");
-    let app = Router::new()
+    info!("Starting server");
+    let rate_limiter = RateLimiter::from_env().map(Arc::new);
+    let internal_addr = internal_addr_from_env();
+    if let Some(internal_addr) = internal_addr {
+        let internal_app = internal_routes().with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind(internal_addr).await.unwrap();
+        let mut internal_shutdown = shutdown_tx.subscribe();
+        info!("Internal routes are served on {}", internal_addr);
+        tokio::spawn(async move {
+            axum::serve(listener, internal_app)
+                .with_graceful_shutdown(async move {
+                    let _ = internal_shutdown.changed().await;
+                })
+                .await
+                .unwrap();
+        });
+    }
+    let mut app = Router::new()
         .route("/", get(root))
+        .merge(public_internal_routes(internal_addr))
         .route("/pools", get(search_pools))
+        .route("/pools/trending", get(get_trending_pools))
+        .route("/recent-pools", get(get_recent_pools))
+        .route("/market-stats", get(get_market_stats))
+        .route("/factory-stats", get(get_factory_stats))
+        .route(
+            "/pools/{pool_address}/liquidity-locked",
+            get(get_liquidity_locked),
+        )
+        .route("/pools/{pool_address}/dev-activity", get(get_dev_activity))
+        .route("/exists/{pool_address}", get(get_pool_exists))
+        .route("/authority/{mint}", get(get_token_authority))
+        .route("/pools/{pool_address}/holders.csv", get(get_holders_csv))
+        .route("/funding/{wallet_address}", get(get_funding))
+        .route("/creator/{wallet}/pools", get(get_creator_pools))
+        .route("/creator/{wallet}/migrations", get(get_creator_migrations))
         .route("/candlestick", get(get_candlestick))
+        .route("/curve-history/{pool_address}", get(get_curve_history))
+        .route("/pair-info/batch", post(get_pair_info_batch))
         .route("/pair-info/{pool_address}", get(get_pair_info))
         .route("/top-traders/{pool_address}", get(get_top_traders))
+        .route("/first-buyers/{pool_address}", get(get_first_buyers))
         .route("/holders/{token_address}", get(get_holders))
+        .route(
+            "/holders/{token_address}/distribution",
+            get(get_holder_distribution),
+        )
+        .route("/holders-history/{mint}", get(get_holders_history))
         .route("/trades", get(get_trades))
+        .route("/whale-trades", get(get_whale_trades))
+        .route("/swap/{hash}", get(get_swap))
         .route(
             "/get-last-transaction/{pool_address}",
             get(get_last_transaction),
         )
         .route("/pool-report", get(get_pool_report))
         .route("/pulse", post(pulse))
+        .route("/pulse/count", post(pulse_count))
+        .route("/pulse/{pool_address}", get(get_pool_pulse))
+        .route("/sse/pulse", get(sse_pulse))
+        .route("/token-info/batch", post(get_token_info_batch))
         .route("/token-info/{pool_address}", get(get_token_info))
         .route("/trader-details", get(get_trader_details))
-        .with_state(clickhouse)
+        .route("/webhooks", post(create_webhook))
+        .route("/webhooks/{id}", delete(delete_webhook))
+        .route("/pnl/{user_address}", get(get_wallet_pnl));
+    // Added before auth so it runs after it and can key on the authenticated ApiKey
+    if let Some(rate_limiter) = &rate_limiter {
+        app = app.route_layer(axum::middleware::from_fn_with_state(
+            rate_limiter.clone(),
+            rate_limit,
+        ));
+    }
+    app = app.route_layer(axum::middleware::from_fn_with_state(
+        api_key_auth,
+        require_api_key,
+    ));
+    // Added after auth so it runs first and sees the 401s of key guessing
+    if let Some(rate_limiter) = rate_limiter {
+        app = app.route_layer(axum::middleware::from_fn_with_state(
+            rate_limiter,
+            throttle_auth_failures,
+        ));
+    }
+    let app = app
+        .route_layer(axum::middleware::from_fn(track_http))
+        .route_layer(axum::middleware::from_fn(request_id))
+        .with_state(state)
+        .layer(body_limit_layer())
+        // Only wraps the HTTP routes; socket.io is layered outside it and stays uncompressed
+        .layer(CompressionLayer::new())
         .layer(
             // Cors layer
             ServiceBuilder::new()
-                .layer(CorsLayer::permissive())
+                .layer(cors_layer())
                 // Socket layer
                 .layer(layer),
         );
     info!("Server is running on ports 3001");
     // run our app with hyper, listening globally on port 3000
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3001").await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal(os_signal(), shutdown_tx))
+    .await
+    .unwrap();
+    let _ = pubsub_task.await;
+    info!("Server stopped");
     Ok(())
 }
 
+// Resolves on Ctrl-C or SIGTERM
+async fn os_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+// Waits for `trigger` (os_signal outside tests), then tells background loops to stop
+async fn shutdown_signal(trigger: impl Future<Output = ()>, shutdown_tx: watch::Sender<bool>) {
+    trigger.await;
+    info!("Shutdown signal received, draining in-flight requests");
+    let _ = shutdown_tx.send(true);
+}
+
 // db.listen_to_swap_events(|swap_event| {
 //     println!("Swap event: {:?}", swap_event);
 // })
 // .await
 // .unwrap();
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::sync::oneshot;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn shutdown_stops_the_server_and_background_loops() {
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let (trigger_tx, trigger_rx) = oneshot::channel::<()>();
+        let trigger = async move {
+            let _ = trigger_rx.await;
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server = tokio::spawn(async move {
+            axum::serve(listener, Router::new())
+                .with_graceful_shutdown(shutdown_signal(trigger, shutdown_tx))
+                .await
+        });
+
+        // Stands in for SIGTERM without signalling the test process
+        trigger_tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("server kept running after the shutdown signal")
+            .unwrap()
+            .unwrap();
+        // Background loops watch this channel
+        assert!(*shutdown_rx.borrow());
+    }
+}
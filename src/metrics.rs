@@ -0,0 +1,239 @@
+// Minimal Prometheus registry: counters, gauges and fixed-bucket histograms keyed by a
+// rendered label set, exposed in text exposition format on GET /metrics.
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        LazyLock, Mutex,
+        atomic::{AtomicI64, Ordering},
+    },
+    time::Duration,
+};
+
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Debug, Default, Clone)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        for (i, bound) in LATENCY_BUCKETS.iter().enumerate() {
+            if value <= *bound {
+                self.buckets[i] += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    http_requests: Mutex<BTreeMap<String, u64>>,
+    http_latency: Mutex<BTreeMap<String, Histogram>>,
+    clickhouse_queries: Mutex<BTreeMap<String, u64>>,
+    clickhouse_latency: Mutex<BTreeMap<String, Histogram>>,
+    socket_connections: AtomicI64,
+    slow_consumers: AtomicI64,
+    ws_events: Mutex<BTreeMap<String, u64>>,
+    pubsub_malformed: Mutex<BTreeMap<String, u64>>,
+}
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::default);
+
+fn labels(pairs: &[(&str, &str)]) -> String {
+    pairs
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, v.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+impl Metrics {
+    pub fn observe_http(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        let status = status.to_string();
+        let key = labels(&[("method", method), ("route", route), ("status", &status)]);
+        *self.http_requests.lock().unwrap().entry(key).or_default() += 1;
+        let key = labels(&[("method", method), ("route", route)]);
+        self.http_latency
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_default()
+            .observe(elapsed.as_secs_f64());
+    }
+
+    // outcome is "ok", "error" or "timeout"
+    pub fn observe_clickhouse(&self, outcome: &str, elapsed: Duration) {
+        let key = labels(&[("outcome", outcome)]);
+        *self
+            .clickhouse_queries
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default() += 1;
+        self.clickhouse_latency
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_default()
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub fn socket_connected(&self) {
+        self.socket_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn socket_disconnected(&self) {
+        self.socket_connections.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    // A socket whose send buffer filled up and was disconnected
+    pub fn slow_consumer_disconnected(&self) {
+        self.slow_consumers.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Counts events pushed to socket.io clients, e.g. "swap_created" or "new-pair"
+    pub fn ws_event(&self, event: &str, pools: u64) {
+        let key = labels(&[("event", event)]);
+        *self.ws_events.lock().unwrap().entry(key).or_default() += pools;
+    }
+
+    // A pubsub message on `channel` that couldn't be decoded
+    pub fn pubsub_malformed(&self, channel: &str) {
+        let key = labels(&[("channel", channel)]);
+        *self
+            .pubsub_malformed
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_default() += 1;
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        render_counter(
+            &mut out,
+            "http_requests_total",
+            "HTTP requests by route and status",
+            &self.http_requests.lock().unwrap(),
+        );
+        render_histogram(
+            &mut out,
+            "http_request_duration_seconds",
+            "HTTP handler latency",
+            &self.http_latency.lock().unwrap(),
+        );
+        render_counter(
+            &mut out,
+            "clickhouse_queries_total",
+            "ClickHouse queries by outcome",
+            &self.clickhouse_queries.lock().unwrap(),
+        );
+        render_histogram(
+            &mut out,
+            "clickhouse_query_duration_seconds",
+            "ClickHouse query duration",
+            &self.clickhouse_latency.lock().unwrap(),
+        );
+        let _ = writeln!(
+            out,
+            "# HELP socketio_connections Connected socket.io clients"
+        );
+        let _ = writeln!(out, "# TYPE socketio_connections gauge");
+        let _ = writeln!(
+            out,
+            "socketio_connections {}",
+            self.socket_connections.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(
+            out,
+            "# HELP socketio_slow_consumers_total Sockets disconnected for a full send buffer"
+        );
+        let _ = writeln!(out, "# TYPE socketio_slow_consumers_total counter");
+        let _ = writeln!(
+            out,
+            "socketio_slow_consumers_total {}",
+            self.slow_consumers.load(Ordering::Relaxed)
+        );
+        render_counter(
+            &mut out,
+            "socketio_pools_emitted_total",
+            "Pools pushed to socket.io clients by event",
+            &self.ws_events.lock().unwrap(),
+        );
+        render_counter(
+            &mut out,
+            "pubsub_malformed_payloads_total",
+            "Redis pubsub payloads dropped because they failed to decode",
+            &self.pubsub_malformed.lock().unwrap(),
+        );
+        out
+    }
+}
+
+fn render_counter(out: &mut String, name: &str, help: &str, values: &BTreeMap<String, u64>) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} counter", name);
+    for (labels, value) in values {
+        let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+    }
+}
+
+fn render_histogram(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    values: &BTreeMap<String, Histogram>,
+) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} histogram", name);
+    for (labels, histogram) in values {
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets.iter()) {
+            let _ = writeln!(
+                out,
+                "{}_bucket{{{},le=\"{}\"}} {}",
+                name, labels, bound, count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{{{},le=\"+Inf\"}} {}",
+            name, labels, histogram.count
+        );
+        let _ = writeln!(out, "{}_sum{{{}}} {}", name, labels, histogram.sum);
+        let _ = writeln!(out, "{}_count{{{}}} {}", name, labels, histogram.count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_counters_histograms_and_gauges() {
+        let metrics = Metrics::default();
+        metrics.observe_http("GET", "/pair/{pool}", 200, Duration::from_millis(30));
+        metrics.observe_http("GET", "/pair/{pool}", 200, Duration::from_millis(30));
+        metrics.socket_connected();
+        metrics.ws_event("new\"pair", 3);
+
+        let out = metrics.render();
+        assert!(out.contains("# TYPE http_requests_total counter\n"));
+        assert!(out.contains(
+            "http_requests_total{method=\"GET\",route=\"/pair/{pool}\",status=\"200\"} 2\n"
+        ));
+        let latency = "http_request_duration_seconds_bucket{method=\"GET\",route=\"/pair/{pool}\"";
+        assert!(out.contains(&format!("{},le=\"0.025\"}} 0\n", latency)));
+        assert!(out.contains(&format!("{},le=\"0.05\"}} 2\n", latency)));
+        assert!(out.contains(&format!("{},le=\"+Inf\"}} 2\n", latency)));
+        assert!(out.contains("socketio_connections 1\n"));
+        assert!(out.contains("socketio_pools_emitted_total{event=\"new\\\"pair\"} 3\n"));
+    }
+}
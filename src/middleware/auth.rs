@@ -0,0 +1,170 @@
+use std::{collections::HashSet, sync::Arc};
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, header},
+    middleware::Next,
+    response::Response,
+};
+use tracing::warn;
+
+use crate::routes::error::ApiError;
+
+const DEFAULT_PUBLIC_PATHS: &str = "/,/health,/health/deep";
+
+// Key the request was authenticated with, for layers that run after auth
+#[derive(Debug, Clone)]
+pub struct ApiKey(pub String);
+
+#[derive(Debug, Default)]
+pub struct ApiKeyAuth {
+    keys: HashSet<String>,
+    public_paths: HashSet<String>,
+}
+
+impl ApiKeyAuth {
+    // API_KEYS and PUBLIC_PATHS are comma-separated. With no keys configured every
+    // request is let through, which keeps local development working.
+    pub fn from_env() -> Self {
+        let keys: HashSet<String> = parse_list(&std::env::var("API_KEYS").unwrap_or_default());
+        if keys.is_empty() {
+            warn!("API_KEYS is not set, API key authentication is disabled");
+        }
+        let public_paths = public_paths_from_env();
+
+        Self { keys, public_paths }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    pub fn is_public(&self, path: &str) -> bool {
+        self.public_paths.contains(path)
+    }
+
+    pub fn is_valid(&self, key: &str) -> bool {
+        self.keys.contains(key)
+    }
+
+    #[cfg(test)]
+    pub fn with_keys(keys: &str) -> Self {
+        Self {
+            keys: parse_list(keys),
+            public_paths: HashSet::new(),
+        }
+    }
+}
+
+// Paths that skip API keys and rate limits, e.g. for load balancer health checks
+pub fn public_paths_from_env() -> HashSet<String> {
+    parse_list(&std::env::var("PUBLIC_PATHS").unwrap_or_else(|_| DEFAULT_PUBLIC_PATHS.to_string()))
+}
+
+pub fn parse_list(value: &str) -> HashSet<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// `Authorization: Bearer <key>` wins over `x-api-key: <key>`
+pub fn header_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or_else(|| headers.get("x-api-key").and_then(|v| v.to_str().ok()))
+        .map(str::trim)
+}
+
+pub async fn require_api_key(
+    State(auth): State<Arc<ApiKeyAuth>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    if !auth.is_enabled() || auth.is_public(request.uri().path()) {
+        return Ok(next.run(request).await);
+    }
+
+    let key = match header_key(request.headers()) {
+        Some(key) if auth.is_valid(key) => key.to_string(),
+        Some(_) => return Err(ApiError::unauthorized("invalid API key")),
+        None => return Err(ApiError::unauthorized("missing API key")),
+    };
+
+    request.extensions_mut().insert(ApiKey(key));
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, body::Body, http::StatusCode, routing::get};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn app() -> Router {
+        let auth = Arc::new(ApiKeyAuth {
+            keys: parse_list("key-1,key-2"),
+            public_paths: parse_list(DEFAULT_PUBLIC_PATHS),
+        });
+        Router::new()
+            .route("/health", get(|| async { "ok" }))
+            .route(
+                "/pools",
+                get(|request: Request| async move {
+                    request.extensions().get::<ApiKey>().unwrap().0.clone()
+                }),
+            )
+            .route_layer(axum::middleware::from_fn_with_state(auth, require_api_key))
+    }
+
+    async fn call(path: &str, header: Option<(&str, &str)>) -> (StatusCode, String) {
+        let mut request = Request::get(path);
+        if let Some((name, value)) = header {
+            request = request.header(name, value);
+        }
+        let response = app()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn valid_keys_pass_and_are_recorded() {
+        assert_eq!(
+            call("/pools", Some(("authorization", "Bearer key-1"))).await,
+            (StatusCode::OK, "key-1".to_string())
+        );
+        assert_eq!(
+            call("/pools", Some(("x-api-key", "key-2"))).await,
+            (StatusCode::OK, "key-2".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_or_unknown_keys_are_unauthorized() {
+        for header in [None, Some(("x-api-key", "nope"))] {
+            let (status, body) = call("/pools", header).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+            assert_eq!(body["error"]["code"], "unauthorized");
+        }
+    }
+
+    #[tokio::test]
+    async fn public_paths_skip_the_check() {
+        assert_eq!(
+            call("/health", None).await,
+            (StatusCode::OK, "ok".to_string())
+        );
+    }
+}
@@ -0,0 +1,46 @@
+use tower_http::limit::RequestBodyLimitLayer;
+
+const DEFAULT_MAX_BODY_BYTES: usize = 64 * 1024;
+
+// MAX_BODY_BYTES caps every request body (default 64 KiB). Larger bodies get a 413
+// before any JSON is parsed; the biggest legitimate ones are batch lookups.
+pub fn body_limit_layer() -> RequestBodyLimitLayer {
+    let limit = std::env::var("MAX_BODY_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|limit| *limit > 0)
+        .unwrap_or(DEFAULT_MAX_BODY_BYTES);
+    RequestBodyLimitLayer::new(limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::{Body, Bytes},
+        http::{Request, StatusCode},
+        routing::post,
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn post_bytes(len: usize) -> StatusCode {
+        let app = Router::new()
+            .route("/pulse", post(|_: Bytes| async { "ok" }))
+            .layer(body_limit_layer());
+        let request = Request::post("/pulse")
+            .body(Body::from(vec![b' '; len]))
+            .unwrap();
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn oversized_bodies_are_413() {
+        assert_eq!(post_bytes(1024).await, StatusCode::OK);
+        assert_eq!(
+            post_bytes(DEFAULT_MAX_BODY_BYTES + 1).await,
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use axum::http::{HeaderName, HeaderValue, Method, header};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::{info, warn};
+
+// ALLOWED_ORIGINS is a comma-separated list of exact origins
+// (e.g. `https://app.example.com`). Empty or `*` keeps the old permissive behaviour.
+pub fn cors_layer() -> CorsLayer {
+    cors_layer_for(&std::env::var("ALLOWED_ORIGINS").unwrap_or_default())
+}
+
+fn cors_layer_for(configured: &str) -> CorsLayer {
+    let configured = configured.trim();
+    if configured.is_empty() || configured == "*" {
+        warn!("ALLOWED_ORIGINS is not set, CORS allows any origin");
+        return CorsLayer::permissive();
+    }
+
+    let origins: Vec<HeaderValue> = configured
+        .split(',')
+        .map(str::trim)
+        .filter(|origin| !origin.is_empty())
+        .filter_map(|origin| match HeaderValue::from_str(origin) {
+            Ok(value) => Some(value),
+            Err(_) => {
+                warn!("Ignoring invalid origin in ALLOWED_ORIGINS: {}", origin);
+                None
+            }
+        })
+        .collect();
+    info!("CORS restricted to {} origins", origins.len());
+
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            HeaderName::from_static("x-api-key"),
+        ])
+        .expose_headers([header::RETRY_AFTER])
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::Body,
+        http::{Request, StatusCode},
+        routing::get,
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn allowed_origin(configured: &str, origin: &str) -> Option<HeaderValue> {
+        let app = Router::new()
+            .route("/pools", get(|| async { "ok" }))
+            .layer(cors_layer_for(configured));
+        let request = Request::get("/pools")
+            .header(header::ORIGIN, origin)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .cloned()
+    }
+
+    #[tokio::test]
+    async fn only_listed_origins_are_allowed() {
+        let configured = "https://app.example.com, https://staging.example.com";
+        assert_eq!(
+            allowed_origin(configured, "https://app.example.com").await,
+            Some(HeaderValue::from_static("https://app.example.com"))
+        );
+        assert_eq!(
+            allowed_origin(configured, "https://evil.example.com").await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn wildcard_allows_any_origin() {
+        for configured in ["", "*"] {
+            assert!(
+                allowed_origin(configured, "https://evil.example.com")
+                    .await
+                    .is_some()
+            );
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub mod auth;
+pub mod body_limit;
+pub mod cors;
+pub mod metrics;
+pub mod rate_limit;
+pub mod request_id;
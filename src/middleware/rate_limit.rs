@@ -0,0 +1,308 @@
+use std::{
+    collections::{HashMap, HashSet},
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::info;
+
+use crate::{
+    middleware::auth::{ApiKey, parse_list, public_paths_from_env},
+    routes::error::ApiError,
+};
+
+// Past this many tracked clients, idle buckets are dropped on the next request
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+// Token bucket per API key, or per client IP for unauthenticated requests
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<String, Bucket>>,
+    // Paths that are never limited, the same ones auth lets through
+    public_paths: HashSet<String>,
+    // Peers whose X-Forwarded-For is believed, e.g. the load balancer
+    trusted_proxies: HashSet<IpAddr>,
+}
+
+impl RateLimiter {
+    pub fn new(per_second: f64, burst: f64) -> Self {
+        Self {
+            per_second,
+            burst: burst.max(1.0),
+            buckets: Mutex::new(HashMap::new()),
+            public_paths: HashSet::new(),
+            trusted_proxies: HashSet::new(),
+        }
+    }
+
+    // Off unless RATE_LIMIT_RPS is set above 0. RATE_LIMIT_BURST defaults to 2x RPS, and
+    // TRUSTED_PROXIES lists the comma-separated proxy IPs whose X-Forwarded-For is used.
+    pub fn from_env() -> Option<Self> {
+        let per_second = std::env::var("RATE_LIMIT_RPS")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0);
+        if per_second <= 0.0 {
+            info!("RATE_LIMIT_RPS is not set, rate limiting is disabled");
+            return None;
+        }
+        let burst = std::env::var("RATE_LIMIT_BURST")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(per_second * 2.0);
+        let trusted_proxies = parse_list(&std::env::var("TRUSTED_PROXIES").unwrap_or_default())
+            .iter()
+            .map(|ip| {
+                ip.parse()
+                    .expect("TRUSTED_PROXIES must be comma-separated IPs")
+            })
+            .collect();
+
+        Some(Self {
+            public_paths: public_paths_from_env(),
+            trusted_proxies,
+            ..Self::new(per_second, burst)
+        })
+    }
+
+    // Whether `client` could take a token right now, without taking it
+    fn has_token(&self, client: &str) -> bool {
+        let buckets = self.buckets.lock().unwrap();
+        buckets.get(client).is_none_or(|bucket| {
+            let elapsed = bucket.updated_at.elapsed().as_secs_f64();
+            bucket.tokens + elapsed * self.per_second >= 1.0
+        })
+    }
+
+    // Takes one token for `client`, or returns how long until one is available
+    pub fn check(&self, client: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if buckets.len() > MAX_TRACKED_CLIENTS {
+            let idle = Duration::from_secs_f64(self.burst / self.per_second);
+            buckets.retain(|_, bucket| now.duration_since(bucket.updated_at) < idle);
+        }
+
+        let bucket = buckets.entry(client.to_string()).or_insert(Bucket {
+            tokens: self.burst,
+            updated_at: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.burst);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / self.per_second,
+            ))
+        }
+    }
+}
+
+impl RateLimiter {
+    // The peer address, or for a trusted proxy the last X-Forwarded-For hop that isn't
+    // itself a trusted proxy. Hops further left are client-supplied and can be forged.
+    fn client_ip(&self, request: &Request) -> Option<IpAddr> {
+        let peer = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())?;
+        if !self.trusted_proxies.contains(&peer) {
+            return Some(peer);
+        }
+        let forwarded = request
+            .headers()
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|hop| hop.trim().parse::<IpAddr>().ok())
+            .collect::<Vec<_>>();
+        Some(
+            forwarded
+                .into_iter()
+                .rev()
+                .find(|hop| !self.trusted_proxies.contains(hop))
+                .unwrap_or(peer),
+        )
+    }
+
+    fn ip_key(&self, request: &Request) -> String {
+        match self.client_ip(request) {
+            Some(ip) => format!("ip:{}", ip),
+            None => "ip:unknown".to_string(),
+        }
+    }
+
+    fn client_key(&self, request: &Request) -> String {
+        if let Some(ApiKey(key)) = request.extensions().get::<ApiKey>() {
+            return format!("key:{}", key);
+        }
+        self.ip_key(request)
+    }
+}
+
+fn limited(wait: Duration) -> Response {
+    let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+    let mut response = ApiError::too_many_requests("rate limit exceeded").into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    response
+}
+
+// Runs after auth, so authenticated requests are limited per key
+pub async fn rate_limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if limiter.public_paths.contains(request.uri().path()) {
+        return next.run(request).await;
+    }
+    match limiter.check(&limiter.client_key(&request)) {
+        Ok(()) => next.run(request).await,
+        Err(wait) => limited(wait),
+    }
+}
+
+// Runs before auth: every 401 takes a token from the client IP's bucket, and an empty
+// bucket turns further attempts away before the key is even checked
+pub async fn throttle_auth_failures(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let key = format!("auth-{}", limiter.ip_key(&request));
+    if !limiter.has_token(&key) {
+        return limited(Duration::from_secs_f64(1.0 / limiter.per_second));
+    }
+    let response = next.run(request).await;
+    if response.status() == StatusCode::UNAUTHORIZED {
+        let _ = limiter.check(&key);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, body::Body, http::StatusCode, routing::get};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn app(limiter: Arc<RateLimiter>) -> Router {
+        Router::new()
+            .route("/pools", get(|| async { "ok" }))
+            .route_layer(axum::middleware::from_fn_with_state(limiter, rate_limit))
+    }
+
+    async fn call(limiter: &Arc<RateLimiter>, key: &str) -> Response {
+        let mut request = Request::get("/pools").body(Body::empty()).unwrap();
+        request.extensions_mut().insert(ApiKey(key.to_string()));
+        app(limiter.clone()).oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn request_past_the_burst_is_rejected() {
+        let limiter = Arc::new(RateLimiter::new(0.5, 3.0));
+        for _ in 0..3 {
+            assert_eq!(call(&limiter, "key-1").await.status(), StatusCode::OK);
+        }
+
+        let response = call(&limiter, "key-1").await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "2");
+
+        // Other keys have their own bucket
+        assert_eq!(call(&limiter, "key-2").await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn public_paths_are_not_limited() {
+        let limiter = Arc::new(RateLimiter {
+            public_paths: parse_list("/health"),
+            ..RateLimiter::new(0.5, 1.0)
+        });
+        let app = Router::new()
+            .route("/health", get(|| async { "ok" }))
+            .route_layer(axum::middleware::from_fn_with_state(limiter, rate_limit));
+        for _ in 0..3 {
+            let request = Request::get("/health").body(Body::empty()).unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+    }
+
+    fn from(peer: &str, forwarded: Option<&str>) -> Request {
+        let mut request = Request::get("/pools").body(Body::empty()).unwrap();
+        let peer: SocketAddr = format!("{peer}:4000").parse().unwrap();
+        request.extensions_mut().insert(ConnectInfo(peer));
+        if let Some(forwarded) = forwarded {
+            let value = HeaderValue::from_str(forwarded).unwrap();
+            request.headers_mut().insert("x-forwarded-for", value);
+        }
+        request
+    }
+
+    #[test]
+    fn forwarded_for_is_only_believed_from_trusted_proxies() {
+        let limiter = RateLimiter {
+            trusted_proxies: ["10.0.0.2".parse().unwrap()].into(),
+            ..RateLimiter::new(1.0, 1.0)
+        };
+        // The client can prepend anything; the hop the proxy appended is the one used
+        let behind_proxy = from("10.0.0.2", Some("6.6.6.6, 203.0.113.7"));
+        assert_eq!(limiter.client_key(&behind_proxy), "ip:203.0.113.7");
+        let direct = from("198.51.100.1", Some("203.0.113.7"));
+        assert_eq!(limiter.client_key(&direct), "ip:198.51.100.1");
+    }
+
+    #[tokio::test]
+    async fn failed_auth_is_throttled_per_ip() {
+        let limiter = Arc::new(RateLimiter::new(0.5, 2.0));
+        let app = Router::new()
+            .route("/pools", get(|| async { StatusCode::UNAUTHORIZED }))
+            .route_layer(axum::middleware::from_fn_with_state(
+                limiter,
+                throttle_auth_failures,
+            ));
+        let status = |peer: &'static str| {
+            let app = app.clone();
+            async move { app.oneshot(from(peer, None)).await.unwrap().status() }
+        };
+        assert_eq!(status("198.51.100.1").await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status("198.51.100.1").await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status("198.51.100.1").await, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(status("198.51.100.2").await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn buckets_refill_over_time() {
+        let limiter = RateLimiter::new(1000.0, 1.0);
+        assert!(limiter.check("ip:127.0.0.1").is_ok());
+        assert!(limiter.check("ip:127.0.0.1").is_err());
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(limiter.check("ip:127.0.0.1").is_ok());
+    }
+}
@@ -0,0 +1,129 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::{Instrument, info_span};
+
+const X_REQUEST_ID: &str = "x-request-id";
+
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+// Longest caller-supplied id that is echoed back instead of replaced
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+// Runs the request inside a `request` span carrying its id, so every log line and DB
+// query span below it can be correlated. A well-formed `x-request-id` from the caller
+// is kept; otherwise one is generated. The id is echoed back in the response header.
+pub async fn request_id(request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(X_REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| {
+            !id.is_empty()
+                && id.len() <= MAX_REQUEST_ID_LEN
+                && id
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        })
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{:016x}", NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed)));
+
+    let span = info_span!(
+        "request",
+        request_id = %id,
+        method = %request.method(),
+        path = %request.uri().path(),
+    );
+
+    let mut response = next.run(request).instrument(span).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response
+            .headers_mut()
+            .insert(HeaderName::from_static(X_REQUEST_ID), value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fmt,
+        sync::{Arc, Mutex},
+    };
+
+    use axum::{Router, body::Body, routing::get};
+    use tower::ServiceExt;
+    use tracing::{
+        Subscriber,
+        field::{Field, Visit},
+        span,
+    };
+    use tracing_subscriber::{
+        layer::{Context, SubscriberExt},
+        registry::Registry,
+    };
+
+    use super::*;
+
+    // Collects the request_id of every `request` span opened
+    #[derive(Clone, Default)]
+    struct RequestSpans(Arc<Mutex<Vec<String>>>);
+
+    struct RequestIdField(Option<String>);
+
+    impl Visit for RequestIdField {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "request_id" {
+                self.0 = Some(format!("{value:?}"));
+            }
+        }
+    }
+
+    impl<S: Subscriber> tracing_subscriber::Layer<S> for RequestSpans {
+        fn on_new_span(&self, attrs: &span::Attributes<'_>, _: &span::Id, _: Context<'_, S>) {
+            if attrs.metadata().name() == "request" {
+                let mut field = RequestIdField(None);
+                attrs.record(&mut field);
+                self.0.lock().unwrap().extend(field.0);
+            }
+        }
+    }
+
+    async fn call(header: Option<&str>) -> String {
+        let app = Router::new()
+            .route("/pools", get(|| async { "ok" }))
+            .route_layer(axum::middleware::from_fn(request_id));
+        let mut request = Request::get("/pools");
+        if let Some(id) = header {
+            request = request.header(X_REQUEST_ID, id);
+        }
+        let response = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        response.headers()[X_REQUEST_ID]
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn each_request_gets_a_span_with_its_id() {
+        let spans = RequestSpans::default();
+        let _guard = tracing::subscriber::set_default(Registry::default().with(spans.clone()));
+
+        let kept = call(Some("client-id_1")).await;
+        let generated = call(None).await;
+        let replaced = call(Some("not an id")).await;
+
+        assert_eq!(kept, "client-id_1");
+        assert_eq!(generated.len(), 16);
+        assert_ne!(replaced, "not an id");
+        assert_eq!(*spans.0.lock().unwrap(), [kept, generated, replaced]);
+    }
+}
@@ -1,26 +1,103 @@
+use chrono::{DateTime, Utc};
 use clickhouse::Row;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     models::{pool::DBPool, token::DBToken},
-    utils::Decimal18,
+    utils::{Decimal18, decimal18_as_string, sub_dec18},
 };
 
-#[derive(Debug, Serialize, Deserialize, Row)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TopTrader {
     creator: String,
     is_sniper: bool,
     base_bought: f64,
     base_sold: f64,
+    // base_bought - base_sold, summed exactly; None if it overflows Decimal18
+    #[serde(with = "decimal18_as_string::option")]
+    net_base: Option<Decimal18>,
+    quote_bought: f64,
+    quote_sold: f64,
+    // None when the base token's decimals aren't indexed yet
+    holding_base_token: Option<f64>,
+}
+
+// TopTrader as read from ClickHouse, with exact base sums for net_base
+#[derive(Debug, Deserialize, Row)]
+pub struct TopTraderRow {
+    creator: String,
+    is_sniper: bool,
+    base_bought: f64,
+    base_sold: f64,
+    base_bought_exact: Decimal18,
+    base_sold_exact: Decimal18,
     quote_bought: f64,
     quote_sold: f64,
-    holding_base_token: f64,
+    holding_base_token: Option<f64>,
+}
+
+impl From<TopTraderRow> for TopTrader {
+    fn from(row: TopTraderRow) -> Self {
+        Self {
+            creator: row.creator,
+            is_sniper: row.is_sniper,
+            base_bought: row.base_bought,
+            base_sold: row.base_sold,
+            net_base: sub_dec18(row.base_bought_exact, row.base_sold_exact),
+            quote_bought: row.quote_bought,
+            quote_sold: row.quote_sold,
+            holding_base_token: row.holding_base_token,
+        }
+    }
+}
+
+// TopTrader for one maker, with PnL in the quote token and the value of what it still holds
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TraderDetails {
+    #[serde(flatten)]
+    trader: TopTrader,
+    // Latest swap price of the pool, quote per base token
+    current_price_sol: f64,
+    // quote_sold - quote_bought
+    realized_pnl_sol: f64,
+    // holding_base_token * current_price_sol
+    holding_value_sol: Option<f64>,
+}
+
+impl TraderDetails {
+    pub fn new(trader: TopTrader, current_price_sol: f64) -> Self {
+        Self {
+            current_price_sol,
+            realized_pnl_sol: trader.quote_sold - trader.quote_bought,
+            holding_value_sol: trader
+                .holding_base_token
+                .map(|holding| holding * current_price_sol),
+            trader,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Row)]
+pub struct FirstBuyer {
+    creator: String,
+    slot: i64,
+    base_amount: f64,
+    // None when the base token's decimals aren't indexed yet
+    remaining: Option<f64>,
+    is_sniper: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PairInfo {
     pub pool: DBPool,
     pub base_token: DBToken,
+    // No tokens row yet; base_token holds defaults (empty name/symbol, decimals 0)
+    pub metadata_pending: bool,
+    // Time of the pool's first swap; `None` until it has traded
+    #[serde(with = "clickhouse::serde::chrono::datetime::option")]
+    pub open_trading: Option<DateTime<Utc>>,
+    // Move from the first to the last swap price of the last 24h; `None` without swaps
+    pub price_change_24h_percent: Option<f64>,
 }
 #[derive(Debug, Serialize, Deserialize, Row)]
 pub struct HolderResponse {
@@ -30,4 +107,82 @@ pub struct HolderResponse {
     pub decimals: u8,
     pub amount: f64,
     pub delegated_amount: i64,
+    // Bought in the slot after a pool for this mint was created
+    pub is_sniper: bool,
+    // Bought in the same slot a pool for this mint was created
+    pub is_bundler: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn row(bought: Decimal18, sold: Decimal18) -> TopTraderRow {
+        TopTraderRow {
+            creator: "trader".to_string(),
+            is_sniper: false,
+            base_bought: 0.0,
+            base_sold: 0.0,
+            base_bought_exact: bought,
+            base_sold_exact: sold,
+            quote_bought: 0.0,
+            quote_sold: 0.0,
+            holding_base_token: Some(0.0),
+        }
+    }
+
+    #[test]
+    fn net_base_is_exact() {
+        let bought = Decimal18::from_str("1000.000000000000000003").unwrap();
+        let sold = Decimal18::from_str("999.000000000000000001").unwrap();
+        let trader = TopTrader::from(row(bought, sold));
+        assert_eq!(
+            trader.net_base,
+            Some(Decimal18::from_str("1.000000000000000002").unwrap())
+        );
+
+        let json = serde_json::to_value(&trader).unwrap();
+        assert_eq!(json["net_base"], "1.000000000000000002");
+    }
+
+    #[test]
+    fn trader_details_value_the_holding_at_the_current_price() {
+        let one = Decimal18::from_str("1").unwrap();
+        let trader = TopTrader::from(TopTraderRow {
+            quote_bought: 2.0,
+            quote_sold: 3.5,
+            holding_base_token: Some(1_000.0),
+            ..row(one, one)
+        });
+        let details = TraderDetails::new(trader, 0.001);
+        assert_eq!(details.realized_pnl_sol, 1.5);
+        assert_eq!(details.holding_value_sol, Some(1.0));
+
+        let json = serde_json::to_value(&details).unwrap();
+        assert_eq!(json["realized_pnl_sol"], 1.5);
+        assert_eq!(json["holding_base_token"], 1_000.0);
+        assert_eq!(json["creator"], "trader");
+    }
+
+    #[test]
+    fn holdings_with_unknown_decimals_have_no_value() {
+        let one = Decimal18::from_str("1").unwrap();
+        let trader = TopTrader::from(TopTraderRow {
+            holding_base_token: None,
+            ..row(one, one)
+        });
+        let json = serde_json::to_value(TraderDetails::new(trader, 0.001)).unwrap();
+        assert!(json["holding_base_token"].is_null());
+        assert!(json["holding_value_sol"].is_null());
+    }
+
+    #[test]
+    fn net_base_overflow_is_null() {
+        let min = Decimal18::from_bits(i128::MIN);
+        let trader = TopTrader::from(row(min, Decimal18::from_str("1").unwrap()));
+        assert_eq!(trader.net_base, None);
+        assert!(serde_json::to_value(&trader).unwrap()["net_base"].is_null());
+    }
 }
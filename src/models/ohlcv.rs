@@ -1,17 +1,23 @@
 use clickhouse::Row;
 use serde::{Deserialize, Serialize};
 
-use crate::utils::Decimal18;
+use crate::utils::{Decimal18, decimal18_as_string};
 
 #[derive(Debug, Serialize, Deserialize, Row)]
 pub struct OHLCV {
     pub pool_address: String,
     pub timestamp: u64,
+    #[serde(with = "decimal18_as_string")]
     pub open: Decimal18,
+    #[serde(with = "decimal18_as_string")]
     pub high: Decimal18,
+    #[serde(with = "decimal18_as_string")]
     pub low: Decimal18,
+    #[serde(with = "decimal18_as_string")]
     pub close: Decimal18,
+    #[serde(with = "decimal18_as_string")]
     pub volume_base: Decimal18,
+    #[serde(with = "decimal18_as_string")]
     pub volume_quote: Decimal18,
     pub trades: u64,
 }
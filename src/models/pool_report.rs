@@ -19,4 +19,20 @@ pub struct PoolReport {
     pub unique_traders: u64,
     pub unique_buyers: u64,
     pub unique_sellers: u64,
+    // First and last swap price in the bucket
+    pub open_price: f64,
+    pub close_price: f64,
+}
+
+// A report bucket with the quote token its volumes are denominated in
+#[derive(Debug, Serialize)]
+pub struct PoolReportEntry {
+    #[serde(flatten)]
+    pub report: PoolReport,
+    pub quote_mint: String,
+    pub quote_decimals: u8,
+    // `None` when the bucket opened at a zero price
+    pub price_change_percent: Option<f64>,
+    // buy_volume / (buy_volume + sell_volume); `None` for a bucket with no volume
+    pub buy_pressure: Option<f64>,
 }
@@ -10,6 +10,8 @@ use serde::{Deserialize, Serialize};
 use solana_signature::Signature;
 use spl_token::solana_program::pubkey::Pubkey;
 
+use crate::utils::quote_decimals_factor;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 
 pub enum SwapType {
@@ -62,6 +64,7 @@ pub struct Swap {
 }
 
 #[derive(Debug, Clone, Row, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Default))]
 pub struct DBSwap {
     pub creator: String,
     pub pool_address: String,
@@ -83,6 +86,91 @@ pub struct DBSwap {
                            // pub updated_at: DateTime<Utc>, // Remove serde annotation
 }
 
+// DBSwap without the pool reserves, for /trades clients that only need amounts and times
+#[derive(Debug, Clone, Serialize)]
+pub struct LeanSwap {
+    pub creator: String,
+    pub pool_address: String,
+    pub hash: String,
+    pub base_amount: f64,
+    pub quote_amount: f64,
+    pub price_sol: f64,
+    pub swap_type: String,
+    pub slot: i64,
+    #[serde(with = "clickhouse::serde::chrono::datetime")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<DBSwap> for LeanSwap {
+    fn from(swap: DBSwap) -> Self {
+        Self {
+            creator: swap.creator,
+            pool_address: swap.pool_address,
+            hash: swap.hash,
+            base_amount: swap.base_amount,
+            quote_amount: swap.quote_amount,
+            price_sol: swap.price_sol,
+            swap_type: swap.swap_type,
+            slot: swap.slot,
+            created_at: swap.created_at,
+        }
+    }
+}
+
+// Decimals of the tokens on a pool's base and quote side, in the orientation its swaps are
+// indexed in. `None` when that token's decimals aren't known. A `reversed` pool holds its
+// quote token on the base side.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SwapDecimals {
+    pub base: Option<u8>,
+    pub quote: Option<u8>,
+    pub reversed: bool,
+}
+
+// A /trades row plus its amounts in whole tokens of each side, so clients don't need the
+// decimals. `None` on a side whose decimals aren't known.
+#[derive(Debug, Clone, Serialize)]
+pub struct UiSwap<T> {
+    #[serde(flatten)]
+    pub swap: T,
+    pub base_amount_ui: Option<f64>,
+    pub quote_amount_ui: Option<f64>,
+}
+
+impl SwapDecimals {
+    pub fn normalize(&self, swap: DBSwap) -> UiSwap<DBSwap> {
+        // Base token amounts are indexed in whole tokens. Quote token amounts are on SOL's
+        // 9-decimal scale like prices and reserves, so they are rescaled first. Either way,
+        // the float noise past the token's last decimal place is dropped.
+        let ui = |amount: f64, decimals: Option<u8>, is_quote_token: bool| {
+            decimals.map(|decimals| {
+                let amount = if is_quote_token {
+                    amount * quote_decimals_factor(decimals)
+                } else {
+                    amount
+                };
+                let scale = 10f64.powi(decimals as i32);
+                (amount * scale).round() / scale
+            })
+        };
+        UiSwap {
+            base_amount_ui: ui(swap.base_amount, self.base, self.reversed),
+            quote_amount_ui: ui(swap.quote_amount, self.quote, !self.reversed),
+            swap,
+        }
+    }
+}
+
+impl UiSwap<DBSwap> {
+    pub fn lean(self) -> UiSwap<LeanSwap> {
+        UiSwap {
+            swap: LeanSwap::from(self.swap),
+            base_amount_ui: self.base_amount_ui,
+            quote_amount_ui: self.quote_amount_ui,
+        }
+    }
+}
+
 impl From<Swap> for DBSwap {
     fn from(swap: Swap) -> Self {
         let now = Utc::now();
@@ -23,6 +23,7 @@ pub struct Token {
     pub twitter: Option<String>,
     pub telegram: Option<String>,
     pub website: Option<String>,
+    pub discord: Option<String>,
     pub program_id: Pubkey,
 }
 
@@ -42,6 +43,7 @@ pub struct DBToken {
     pub twitter: Option<String>,
     pub telegram: Option<String>,
     pub website: Option<String>,
+    pub discord: Option<String>,
     pub program_id: String,
 }
 
@@ -75,6 +77,7 @@ impl TryFrom<DBToken> for Token {
             twitter: db_token.twitter,
             telegram: db_token.telegram,
             website: db_token.website,
+            discord: db_token.discord,
             program_id: Pubkey::from_str(&db_token.program_id)
                 .map_err(|_| "parse program id".to_string())?,
         })
@@ -0,0 +1,22 @@
+use axum::{Json, extract::State};
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    routes::{error::ApiError, extractors::PoolAddress},
+    services::clickhouse::ClickhouseService,
+};
+
+// Current mint and freeze authority of a token, and whether each has been renounced
+pub async fn get_token_authority(
+    PoolAddress(mint): PoolAddress,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    match db.get_token_authority(mint).await {
+        Ok(authority) => Ok(Json(json!(authority))),
+        Err(e) => {
+            error!("Error getting token authority: {}", e);
+            Err(e.into())
+        }
+    }
+}
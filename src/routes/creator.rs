@@ -0,0 +1,60 @@
+use axum::{Json, extract::State};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    routes::{
+        error::ApiError,
+        extractors::{ApiQuery, PoolAddress},
+    },
+    services::clickhouse::ClickhouseService,
+};
+
+const MAX_CREATOR_POOLS: u64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct CreatorPoolsParams {
+    #[serde(default = "default_limit")]
+    limit: u64,
+}
+
+fn default_limit() -> u64 {
+    100
+}
+
+// Pools a wallet has created, newest first; an unknown wallet gets an empty array
+pub async fn get_creator_pools(
+    PoolAddress(wallet): PoolAddress,
+    ApiQuery(params): ApiQuery<CreatorPoolsParams>,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if params.limit == 0 || params.limit > MAX_CREATOR_POOLS {
+        return Err(ApiError::bad_request(format!(
+            "limit must be between 1 and {}",
+            MAX_CREATOR_POOLS
+        )));
+    }
+
+    match db.get_creator_pools(wallet, params.limit).await {
+        Ok(pools) => Ok(Json(json!(pools))),
+        Err(e) => {
+            error!("Error getting creator pools: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+// PumpFun -> PumpSwap migrations of a wallet's tokens
+pub async fn get_creator_migrations(
+    PoolAddress(wallet): PoolAddress,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    match db.get_creator_migrations(wallet).await {
+        Ok(migrations) => Ok(Json(json!(migrations))),
+        Err(e) => {
+            error!("Error getting creator migrations: {}", e);
+            Err(e.into())
+        }
+    }
+}
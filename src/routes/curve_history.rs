@@ -0,0 +1,24 @@
+use axum::{Json, extract::State};
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    routes::{error::ApiError, extractors::PoolAddress},
+    services::clickhouse::ClickhouseService,
+};
+
+// Caps the response for pools with a very long update history
+const MAX_CURVE_POINTS: u64 = 5_000;
+
+pub async fn get_curve_history(
+    PoolAddress(pool_address): PoolAddress,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    match db.get_curve_history(pool_address, MAX_CURVE_POINTS).await {
+        Ok(points) => Ok(Json(json!(points))),
+        Err(e) => {
+            error!("Error getting curve history: {}", e);
+            Err(e.into())
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use axum::{Json, extract::State};
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    routes::{error::ApiError, extractors::PoolAddress},
+    services::clickhouse::ClickhouseService,
+};
+
+pub async fn get_dev_activity(
+    PoolAddress(pool_address): PoolAddress,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    match db.get_dev_activity(pool_address).await {
+        Ok(activity) => Ok(Json(json!(activity))),
+        Err(e) => {
+            error!("Error getting dev activity: {}", e);
+            Err(e.into())
+        }
+    }
+}
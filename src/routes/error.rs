@@ -0,0 +1,127 @@
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+
+use crate::services::error::ServiceError;
+
+// Error returned by every route: `{"error": {"code": "...", "message": "..."}}`
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "bad_request", message)
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "unauthorized", message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "not_found", message)
+    }
+
+    pub fn too_many_requests(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::TOO_MANY_REQUESTS, "rate_limited", message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "internal_error", message)
+    }
+}
+
+impl From<ServiceError> for ApiError {
+    fn from(e: ServiceError) -> Self {
+        match e {
+            ServiceError::Timeout => Self::new(e.status_code(), "timeout", "query timed out"),
+            ServiceError::Db(_) => Self::internal("database error"),
+            ServiceError::NotFound => Self::not_found("not found"),
+        }
+    }
+}
+
+// Body field a validator rejected, e.g. `filters.age` past the lookback limit. Sent as
+// 422 with the usual error envelope plus `field` and `reason`.
+#[derive(Debug)]
+pub struct FilterError {
+    pub field: String,
+    pub reason: String,
+}
+
+impl IntoResponse for FilterError {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "error": {
+                "code": "invalid_filter",
+                "message": format!("{}: {}", self.field, self.reason),
+                "field": self.field,
+                "reason": self.reason,
+            }
+        });
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response()
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "error": {
+                "code": self.code,
+                "message": self.message,
+            }
+        });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    async fn body(error: ApiError) -> (StatusCode, Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn bad_request_shape() {
+        let (status, body) = body(ApiError::bad_request("invalid pool address")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            json!({ "error": { "code": "bad_request", "message": "invalid pool address" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn database_errors_are_500_without_details() {
+        let error = ServiceError::Db(clickhouse::error::Error::Custom("secret".into()));
+        let (status, body) = body(error.into()).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            body,
+            json!({ "error": { "code": "internal_error", "message": "database error" } })
+        );
+    }
+}
@@ -0,0 +1,22 @@
+use axum::{Json, extract::State};
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    routes::{error::ApiError, extractors::PoolAddress},
+    services::clickhouse::ClickhouseService,
+};
+
+// Whether a pool is indexed and has traded; an unknown pool is `exists: false`, not 404
+pub async fn get_pool_exists(
+    PoolAddress(pool_address): PoolAddress,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    match db.get_pool_exists(pool_address).await {
+        Ok(exists) => Ok(Json(json!(exists))),
+        Err(e) => {
+            error!("Error checking pool existence: {}", e);
+            Err(e.into())
+        }
+    }
+}
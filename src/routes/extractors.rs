@@ -0,0 +1,231 @@
+use std::str::FromStr;
+
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{FromRequest, FromRequestParts, Path, Query, Request},
+    http::{StatusCode, request::Parts},
+    response::{IntoResponse, Response},
+};
+use serde::de::DeserializeOwned;
+use spl_token::solana_program::pubkey::Pubkey;
+use tracing::warn;
+
+use crate::routes::error::{ApiError, FilterError};
+
+// Base58 pubkey taken from the route's single path parameter, validated once
+#[derive(Debug)]
+pub struct PoolAddress(pub String);
+
+impl<S> FromRequestParts<S> for PoolAddress
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(address) = Path::<String>::from_request_parts(parts, state)
+            .await
+            .map_err(|e| ApiError::bad_request(e.body_text()))?;
+
+        let pubkey = Pubkey::from_str(&address).map_err(|_| {
+            warn!("failed to parse address from path {}", address);
+            ApiError::bad_request(format!("invalid address: {}", address))
+        })?;
+
+        Ok(PoolAddress(pubkey.to_string()))
+    }
+}
+
+// ApiError for an axum extractor rejection, keeping its status but replacing axum's plain
+// text body with the usual `{"error": {...}}` envelope
+fn rejected(status: StatusCode, message: String) -> ApiError {
+    let code = match status {
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported_media_type",
+        StatusCode::UNPROCESSABLE_ENTITY => "invalid_body",
+        status if status.is_server_error() => "internal_error",
+        _ => "bad_request",
+    };
+    ApiError::new(status, code, message)
+}
+
+// `Query`, with rejections (e.g. `interval=foo`) as ApiErrors
+#[derive(Debug)]
+pub struct ApiQuery<T>(pub T);
+
+impl<S, T> FromRequestParts<S> for ApiQuery<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(value) = Query::<T>::from_request_parts(parts, state)
+            .await
+            .map_err(|e| rejected(e.status(), e.body_text()))?;
+        Ok(ApiQuery(value))
+    }
+}
+
+// `Path`, with rejections as ApiErrors
+#[derive(Debug)]
+pub struct ApiPath<T>(pub T);
+
+impl<S, T> FromRequestParts<S> for ApiPath<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Send,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Path(value) = Path::<T>::from_request_parts(parts, state)
+            .await
+            .map_err(|e| rejected(e.status(), e.body_text()))?;
+        Ok(ApiPath(value))
+    }
+}
+
+// `Json` body, with rejections as ApiErrors. Bodies of filters use `FilterJson` instead,
+// which also names the offending field.
+#[derive(Debug)]
+pub struct ApiJson<T>(pub T);
+
+impl<S, T> FromRequest<S> for ApiJson<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(|e| rejected(e.status(), e.body_text()))?;
+        Ok(ApiJson(value))
+    }
+}
+
+// JSON body like `Json`, except a value the target type rejects (a filter out of range,
+// a missing field, an unknown table) is a 422 naming the field. Malformed JSON is a 400.
+#[derive(Debug)]
+pub struct FilterJson<T>(pub T);
+
+impl<S, T> FromRequest<S> for FilterJson<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| rejected(e.status(), e.body_text()).into_response())?;
+        let deserializer = &mut serde_json::Deserializer::from_slice(&bytes);
+        match serde_path_to_error::deserialize(deserializer) {
+            Ok(value) => Ok(FilterJson(value)),
+            Err(e) if e.inner().is_data() => {
+                let message = e.inner().to_string();
+                // serde_json appends the position, which means nothing to the caller
+                let reason = match message.rsplit_once(" at line ") {
+                    Some((reason, _)) => reason.to_string(),
+                    None => message.clone(),
+                };
+                let field = e.path().to_string();
+                Err(FilterError { field, reason }.into_response())
+            }
+            Err(e) => Err(ApiError::bad_request(e.inner().to_string()).into_response()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, body::Body, http::Request, http::StatusCode, routing::get};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn get_path(address: &str) -> (StatusCode, String) {
+        let app = Router::new().route(
+            "/pools/{pool_address}",
+            get(|PoolAddress(address): PoolAddress| async move { address }),
+        );
+        let request = Request::get(format!("/pools/{address}"))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn accepts_a_base58_pubkey() {
+        let mint = "So11111111111111111111111111111111111111112";
+        assert_eq!(get_path(mint).await, (StatusCode::OK, mint.to_string()));
+    }
+
+    #[tokio::test]
+    async fn query_rejections_use_the_error_envelope() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Params {
+            #[allow(dead_code)]
+            limit: u32,
+        }
+        let app = Router::new().route("/list", get(|ApiQuery(_): ApiQuery<Params>| async { "ok" }));
+        let request = Request::get("/list?limit=foo").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["error"]["code"], "bad_request");
+        assert!(body["error"]["message"].as_str().unwrap().contains("limit"));
+    }
+
+    #[tokio::test]
+    async fn json_rejections_keep_their_status() {
+        let app = Router::new().route(
+            "/batch",
+            axum::routing::post(|ApiJson(_): ApiJson<Vec<String>>| async { "ok" }),
+        );
+        for (content_type, body, status) in [
+            ("application/json", "{not json", StatusCode::BAD_REQUEST),
+            ("application/json", "[1]", StatusCode::UNPROCESSABLE_ENTITY),
+            ("text/plain", "[]", StatusCode::UNSUPPORTED_MEDIA_TYPE),
+        ] {
+            let request = Request::post("/batch")
+                .header("content-type", content_type)
+                .body(Body::from(body))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), status, "{body}");
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+            assert!(body["error"]["code"].is_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_segments_with_json() {
+        for segment in [
+            "not-a-pubkey",
+            "abc",
+            "0OIl0OIl0OIl0OIl0OIl0OIl0OIl0OIl0OIl0OIl0OIl",
+        ] {
+            let (status, body) = get_path(segment).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{segment}");
+            let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+            assert_eq!(body["error"]["code"], "bad_request");
+        }
+    }
+}
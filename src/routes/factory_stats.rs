@@ -0,0 +1,41 @@
+use axum::{Json, extract::State};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    routes::{error::ApiError, extractors::ApiQuery},
+    services::clickhouse::ClickhouseService,
+};
+
+const MAX_FACTORY_STATS_HOURS: u32 = 168;
+
+#[derive(Debug, Deserialize)]
+pub struct FactoryStatsParams {
+    #[serde(default = "default_hours")]
+    hours: u32,
+}
+
+fn default_hours() -> u32 {
+    24
+}
+
+pub async fn get_factory_stats(
+    ApiQuery(params): ApiQuery<FactoryStatsParams>,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if params.hours == 0 || params.hours > MAX_FACTORY_STATS_HOURS {
+        return Err(ApiError::bad_request(format!(
+            "hours must be between 1 and {}",
+            MAX_FACTORY_STATS_HOURS
+        )));
+    }
+
+    match db.get_factory_stats(params.hours).await {
+        Ok(stats) => Ok(Json(json!(stats))),
+        Err(e) => {
+            error!("Error getting factory stats: {}", e);
+            Err(e.into())
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use axum::{Json, extract::State};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    routes::{
+        error::ApiError,
+        extractors::{ApiQuery, PoolAddress},
+    },
+    services::clickhouse::ClickhouseService,
+};
+
+const MAX_FIRST_BUYERS: u64 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct FirstBuyersParams {
+    #[serde(default = "default_limit")]
+    limit: u64,
+}
+
+fn default_limit() -> u64 {
+    20
+}
+
+pub async fn get_first_buyers(
+    PoolAddress(pool_address): PoolAddress,
+    ApiQuery(params): ApiQuery<FirstBuyersParams>,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if params.limit == 0 || params.limit > MAX_FIRST_BUYERS {
+        return Err(ApiError::bad_request(format!(
+            "limit must be between 1 and {}",
+            MAX_FIRST_BUYERS
+        )));
+    }
+
+    match db.get_first_buyers(pool_address, params.limit).await {
+        Ok(buyers) => Ok(Json(json!(buyers))),
+        Err(e) => {
+            error!("Error getting first buyers: {}", e);
+            Err(e.into())
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use axum::{Json, extract::State};
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    routes::{error::ApiError, extractors::PoolAddress},
+    services::clickhouse::ClickhouseService,
+};
+
+const DEFAULT_FUNDING_LIMIT: u64 = 100;
+
+fn funding_limit_from_env() -> u64 {
+    std::env::var("FUNDING_TRANSFERS_LIMIT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_FUNDING_LIMIT)
+}
+
+// Incoming SOL transfers for a wallet, earliest first
+pub async fn get_funding(
+    PoolAddress(wallet): PoolAddress,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    match db
+        .get_incoming_transfers(wallet, funding_limit_from_env())
+        .await
+    {
+        Ok(transfers) => Ok(Json(json!(transfers))),
+        Err(e) => {
+            error!("Error getting incoming transfers: {}", e);
+            Err(e.into())
+        }
+    }
+}
@@ -1,25 +1,41 @@
 use std::str::FromStr;
 
-use axum::{
-    Json,
-    extract::{Path, Query, State},
-    http::StatusCode,
-};
+use axum::{Json, extract::State};
 use chrono::{DateTime, Duration, NaiveDateTime, Utc};
-use serde_json::json;
+use serde_json::{Value, json};
 use spl_token::solana_program::pubkey::Pubkey;
 use tracing::{error, warn};
 
-use crate::{services::clickhouse::ClickhouseService, types::candlestick::CandlestickQuery};
+use crate::{
+    routes::{error::ApiError, extractors::ApiQuery},
+    services::clickhouse::ClickhouseService,
+    types::candlestick::CandlestickQuery,
+};
+
+// Also bounds how many candles `fill_gaps` can synthesize
+const MAX_CANDLES: i32 = 5000;
 
 pub async fn get_candlestick(
-    Query(query): Query<CandlestickQuery>,
+    ApiQuery(query): ApiQuery<CandlestickQuery>,
     State(db): State<ClickhouseService>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     let pool_address = Pubkey::from_str(&query.pool_address).map_err(|e| {
         warn!(?e, "Failed to parse pool in candlestick");
-        StatusCode::INTERNAL_SERVER_ERROR
+        ApiError::bad_request("invalid pool_address")
     })?;
+    if query.limit < 1 || query.limit > MAX_CANDLES {
+        return Err(ApiError::bad_request(format!(
+            "limit must be between 1 and {}",
+            MAX_CANDLES
+        )));
+    }
+    if !db.supports_candle_interval(&query.interval).await {
+        return Err(ApiError::bad_request(format!(
+            "unsupported interval {}; supported: {}",
+            query.interval.to_string(),
+            db.supported_candle_intervals().await.join(", ")
+        )));
+    }
     // Convert start_time and end_time from unix timestamp (i64) to DateTime<Utc>
     // Try to parse start_time and end_time, default to last 7 days if not passed or invalid
     let now = Utc::now();
@@ -32,22 +48,185 @@ pub async fn get_candlestick(
         Some(ts) => ts,
         None => now.timestamp(),
     };
-    let interval = query.interval.to_string();
+    let interval_secs = query.interval.seconds();
     let limit = query.limit;
     let candles = db
         .get_candlestick(
             pool_address.to_string(),
-            interval,
+            &query.interval,
             start_time,
             end_time,
             limit,
         )
-        .await;
-    match candles {
-        Ok(candles) => Ok(Json(json!(candles))),
-        Err(e) => {
+        .await
+        .map_err(|e| {
             error!("Error getting candlestick: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            ApiError::from(e)
+        })?;
+    // No candles is a 200 for a known pool that didn't trade in range, and a 404 for a pool
+    // that isn't indexed at all. Only empty results pay for the extra lookup.
+    if candles.is_empty() && !db.get_pool_exists(pool_address.to_string()).await?.exists {
+        return Err(ApiError::not_found(format!(
+            "pool not found: {}",
+            pool_address
+        )));
+    }
+    if query.fill_gaps {
+        let end_time = end_time.min(now.timestamp());
+        let filled = fill_candle_gaps(candles, interval_secs, end_time, limit as usize);
+        return Ok(Json(json!(filled)));
+    }
+    Ok(Json(json!(candles)))
+}
+
+// Candle timestamps come back from JSONEachRow either as numbers, quoted numbers or
+// "YYYY-MM-DD HH:MM:SS"; filler candles are written back in the same shape
+fn candle_timestamp(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.parse::<i64>().ok().or_else(|| {
+            NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .map(|dt| dt.and_utc().timestamp())
+        }),
+        _ => None,
+    }
+}
+
+fn timestamp_like(template: &Value, ts: i64) -> Value {
+    match template {
+        Value::String(s) if s.parse::<i64>().is_ok() => Value::String(ts.to_string()),
+        Value::String(_) => DateTime::<Utc>::from_timestamp(ts, 0)
+            .map(|dt| Value::String(dt.format("%Y-%m-%d %H:%M:%S").to_string()))
+            .unwrap_or(Value::Null),
+        _ => json!(ts),
+    }
+}
+
+fn zero_like(template: &Value) -> Value {
+    match template {
+        Value::String(_) => Value::String("0".to_string()),
+        _ => json!(0),
+    }
+}
+
+// Inserts a flat candle (open = high = low = close = previous close, zero volume) for
+// every missing bucket after the first real candle, up to `end_time`. Buckets before
+// the first candle are left out since there is no previous close to carry.
+fn fill_candle_gaps(
+    candles: Vec<Value>,
+    interval_secs: i64,
+    end_time: i64,
+    limit: usize,
+) -> Vec<Value> {
+    let mut filled: Vec<Value> = Vec::with_capacity(candles.len());
+    let mut last: Option<(i64, Value)> = None;
+
+    let push_fillers = |filled: &mut Vec<Value>, prev_ts: i64, prev: &Value, until: i64| {
+        let mut ts = prev_ts + interval_secs;
+        while ts < until && filled.len() < limit {
+            let mut candle = prev.clone();
+            candle["timestamp"] = timestamp_like(&prev["timestamp"], ts);
+            let close = prev["close"].clone();
+            for field in ["open", "high", "low"] {
+                candle[field] = close.clone();
+            }
+            for field in ["volume_base", "volume_quote", "trades"] {
+                candle[field] = zero_like(&prev[field]);
+            }
+            filled.push(candle);
+            ts += interval_secs;
         }
+    };
+
+    for candle in candles {
+        let Some(ts) = candle_timestamp(&candle["timestamp"]) else {
+            filled.push(candle);
+            continue;
+        };
+        if let Some((prev_ts, prev)) = &last {
+            push_fillers(&mut filled, *prev_ts, prev, ts);
+        }
+        if filled.len() >= limit {
+            return filled;
+        }
+        filled.push(candle.clone());
+        last = Some((ts, candle));
+    }
+
+    if let Some((prev_ts, prev)) = &last {
+        push_fillers(&mut filled, *prev_ts, prev, end_time + 1);
+    }
+
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(timestamp: &str, open: f64, close: f64, trades: u64) -> Value {
+        json!({
+            "timestamp": timestamp,
+            "open": open,
+            "high": open.max(close),
+            "low": open.min(close),
+            "close": close,
+            "volume_base": "100",
+            "volume_quote": 2.5,
+            "trades": trades,
+        })
+    }
+
+    #[test]
+    fn fills_missing_buckets_with_previous_close() {
+        let candles = vec![
+            candle("2024-01-01 00:00:00", 1.0, 2.0, 3),
+            candle("2024-01-01 00:03:00", 2.5, 3.0, 1),
+        ];
+        let end_time = candle_timestamp(&json!("2024-01-01 00:04:00")).unwrap();
+        let filled = fill_candle_gaps(candles, 60, end_time, 100);
+
+        let timestamps: Vec<&str> = filled
+            .iter()
+            .map(|c| c["timestamp"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            timestamps,
+            [
+                "2024-01-01 00:00:00",
+                "2024-01-01 00:01:00",
+                "2024-01-01 00:02:00",
+                "2024-01-01 00:03:00",
+                "2024-01-01 00:04:00",
+            ]
+        );
+
+        let gap = &filled[1];
+        for field in ["open", "high", "low", "close"] {
+            assert_eq!(gap[field], json!(2.0));
+        }
+        // Zeroes keep the type the column came back as
+        assert_eq!(gap["volume_base"], json!("0"));
+        assert_eq!(gap["volume_quote"], json!(0));
+        assert_eq!(gap["trades"], json!(0));
+        assert_eq!(filled[4]["close"], json!(3.0));
+    }
+
+    #[test]
+    fn stops_at_limit() {
+        let candles = vec![
+            candle("2024-01-01 00:00:00", 1.0, 2.0, 3),
+            candle("2024-01-01 00:10:00", 2.5, 3.0, 1),
+        ];
+        let end_time = candle_timestamp(&json!("2024-01-01 00:10:00")).unwrap();
+        let filled = fill_candle_gaps(candles, 60, end_time, 4);
+        assert_eq!(filled.len(), 4);
+        assert_eq!(filled[3]["timestamp"], json!("2024-01-01 00:03:00"));
+    }
+
+    #[test]
+    fn no_candles_stay_empty() {
+        assert!(fill_candle_gaps(Vec::new(), 60, 1_700_000_000, 10).is_empty());
     }
 }
@@ -1,31 +1,47 @@
-use std::str::FromStr;
+use axum::{Json, extract::State};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::error;
 
-use axum::{
-    Json,
-    extract::{Path, State},
-    http::StatusCode,
+use crate::{
+    routes::{
+        error::ApiError,
+        extractors::{ApiQuery, PoolAddress},
+    },
+    services::clickhouse::ClickhouseService,
 };
-use serde_json::json;
-use spl_token::solana_program::pubkey::Pubkey;
-use tracing::{error, warn};
 
-use crate::services::clickhouse::ClickhouseService;
+const MAX_HOLDERS: u64 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct HoldersParams {
+    #[serde(default = "default_limit")]
+    limit: u64,
+}
+
+fn default_limit() -> u64 {
+    50
+}
 
 pub async fn get_holders(
-    Path(address): Path<String>,
+    PoolAddress(mint): PoolAddress,
+    ApiQuery(params): ApiQuery<HoldersParams>,
     State(db): State<ClickhouseService>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let mint = Pubkey::from_str(&address).map_err(|e| {
-        warn!(?e, "Failed to parse pool in candlestick");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    let holders = db.get_holders(mint.to_string()).await;
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if params.limit == 0 || params.limit > MAX_HOLDERS {
+        return Err(ApiError::bad_request(format!(
+            "limit must be between 1 and {}",
+            MAX_HOLDERS
+        )));
+    }
+
+    let holders = db.get_holders(mint, params.limit).await;
 
     match holders {
         Ok(holders) => Ok(Json(json!(holders))),
         Err(e) => {
             error!("Error getting holders: {:?}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.into())
         }
     }
 }
@@ -1,54 +1,91 @@
 use std::{str::FromStr, thread::park};
 
-use axum::{
-    Json,
-    extract::{Path, State},
-    http::StatusCode,
-};
+use axum::{Json, extract::State};
+use serde::Deserialize;
 use serde_json::json;
 use spl_token::solana_program::pubkey::Pubkey;
-use tracing::{debug, info, warn};
+use tracing::{debug, error, info, warn};
 
 use crate::{
-    defaults::{SOL_TOKEN, USDC_TOKEN},
-    models::{pool::DBPool, token::DBToken},
-    services::clickhouse::ClickhouseService,
+    models::{extra::PairInfo, pool::DBPool, token::DBToken},
+    routes::{
+        error::ApiError,
+        extractors::{ApiJson, PoolAddress},
+    },
+    services::{clickhouse::ClickhouseService, quote_tokens::QuoteTokenRegistry},
 };
 
+const MAX_BATCH_PAIRS: usize = 50;
+
+#[derive(Debug, Deserialize)]
+pub struct PairInfoBatchRequest {
+    pool_addresses: Vec<String>,
+}
+
+fn pair_info_json(
+    pair_info: PairInfo,
+    quote_tokens: &QuoteTokenRegistry,
+) -> Result<serde_json::Value, ApiError> {
+    let pool: DBPool = DBPool::try_from(pair_info.pool).map_err(|e| {
+        warn!(?e, "failed to convert pool");
+        ApiError::internal("failed to convert pool")
+    })?;
+
+    let base_token = DBToken::try_from(pair_info.base_token)
+        .map_err(|_| ApiError::internal("failed to convert base token"))?;
+
+    let quote_token = quote_tokens.resolve(&pool.token_quote_address);
+
+    Ok(json!({
+        "pool": pool,
+        "base_token":base_token,
+        "quote_token":quote_token,
+        "metadata_pending": pair_info.metadata_pending,
+        "open_trading": pair_info.open_trading.map(|t| t.timestamp()),
+        "price_change_24h_percent": pair_info.price_change_24h_percent,
+    }))
+}
+
 pub async fn get_pair_info(
-    Path(address): Path<String>,
+    PoolAddress(pool_address): PoolAddress,
     State(db): State<ClickhouseService>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let pool_address = Pubkey::from_str(&address).map_err(|_| {
-        warn!("failed to parse pool address from token_info {}", address);
-        StatusCode::BAD_REQUEST
-    })?;
-    let pair_info = db.get_pair_info(pool_address.to_string()).await;
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let pair_info = db.get_pair_info(pool_address).await;
 
     match pair_info {
-        Ok(pair_info) => {
-            let pool: DBPool = DBPool::try_from(pair_info.pool).map_err(|e| {
-                warn!(?e, "failed to convert pool");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
-
-            let base_token = DBToken::try_from(pair_info.base_token)
-                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-            let quote_token = {
-                if pool.token_quote_address == SOL_TOKEN.address {
-                    SOL_TOKEN
-                } else {
-                    USDC_TOKEN
-                }
-            };
-
-            Ok(Json(json!({
-                "pool": pool,
-                "base_token":base_token,
-                "quote_token":quote_token
-            })))
-        }
-        Err(e) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Ok(pair_info) => Ok(Json(pair_info_json(pair_info, &db.quote_tokens)?)),
+        Err(e) => Err(e.into()),
     }
 }
+
+// Returns `{ "<pool_address>": <pair info> }`; addresses with no pool are left out
+pub async fn get_pair_info_batch(
+    State(db): State<ClickhouseService>,
+    ApiJson(request): ApiJson<PairInfoBatchRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if request.pool_addresses.len() > MAX_BATCH_PAIRS {
+        return Err(ApiError::bad_request(format!(
+            "at most {} pool_addresses per request",
+            MAX_BATCH_PAIRS
+        )));
+    }
+
+    let mut pool_addresses = Vec::with_capacity(request.pool_addresses.len());
+    for address in &request.pool_addresses {
+        let pool = Pubkey::from_str(address)
+            .map_err(|_| ApiError::bad_request(format!("invalid address: {}", address)))?;
+        pool_addresses.push(pool.to_string());
+    }
+
+    let pairs = db.get_pair_info_batch(&pool_addresses).await.map_err(|e| {
+        error!("Error getting pair info batch: {}", e);
+        ApiError::from(e)
+    })?;
+
+    let mut result = serde_json::Map::with_capacity(pairs.len());
+    for (address, pair_info) in pairs {
+        result.insert(address, pair_info_json(pair_info, &db.quote_tokens)?);
+    }
+
+    Ok(Json(serde_json::Value::Object(result)))
+}
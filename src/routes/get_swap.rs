@@ -0,0 +1,30 @@
+use std::str::FromStr;
+
+use axum::{Json, extract::State};
+use serde_json::json;
+use solana_signature::Signature;
+use tracing::{error, warn};
+
+use crate::{
+    routes::{error::ApiError, extractors::ApiPath},
+    services::clickhouse::ClickhouseService,
+};
+
+pub async fn get_swap(
+    ApiPath(hash): ApiPath<String>,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let signature = Signature::from_str(&hash).map_err(|_| {
+        warn!("failed to parse signature {}", hash);
+        ApiError::bad_request(format!("invalid signature: {}", hash))
+    })?;
+
+    match db.get_swap_by_hash(signature.to_string()).await {
+        Ok(Some(swap)) => Ok(Json(json!(swap))),
+        Ok(None) => Err(ApiError::not_found("swap not found")),
+        Err(e) => {
+            error!("Error getting swap: {}", e);
+            Err(e.into())
+        }
+    }
+}
@@ -1,28 +1,83 @@
 use std::str::FromStr;
 
-use axum::{
-    Json,
-    extract::{Path, State},
-    http::StatusCode,
-};
+use axum::{Json, extract::State};
+use serde::Deserialize;
 use serde_json::json;
 use spl_token::solana_program::pubkey::Pubkey;
-use tracing::{debug, warn};
+use tracing::error;
+
+use crate::{
+    defaults::DEFAULT_TOP_HOLDERS,
+    routes::{
+        error::ApiError,
+        extractors::{ApiJson, ApiQuery, PoolAddress},
+    },
+    services::clickhouse::ClickhouseService,
+};
+
+const MAX_BATCH_TOKEN_INFO: usize = 50;
+
+// Holder counts `top_n` may take
+const TOP_N_CHOICES: [u32; 4] = [5, 10, 20, 50];
 
-use crate::services::clickhouse::ClickhouseService;
+#[derive(Debug, Deserialize)]
+pub struct TokenInfoParams {
+    #[serde(default = "default_top_n")]
+    top_n: u32,
+}
+
+fn default_top_n() -> u32 {
+    DEFAULT_TOP_HOLDERS
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenInfoBatchRequest {
+    pool_addresses: Vec<String>,
+}
 
 pub async fn get_token_info(
     db: State<ClickhouseService>,
-    Path(address): Path<String>,
-) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
-    let pool_address = Pubkey::from_str(&address).map_err(|_| {
-        warn!("failed to parse pool address from token_info {}", address);
-        StatusCode::BAD_REQUEST
-    })?;
-
-    let token_info = db.get_token_info(pool_address.to_string()).await;
+    PoolAddress(pool_address): PoolAddress,
+    ApiQuery(params): ApiQuery<TokenInfoParams>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if !TOP_N_CHOICES.contains(&params.top_n) {
+        return Err(ApiError::bad_request(format!(
+            "top_n must be one of {:?}",
+            TOP_N_CHOICES
+        )));
+    }
+
+    let token_info = db.get_token_info(pool_address, params.top_n).await;
     match token_info {
         Ok(token_info) => Ok(Json(json!(token_info))),
-        Err(_e) => Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// Returns `{ "<pool_address>": <token info> }`; addresses with no pool are left out
+pub async fn get_token_info_batch(
+    State(db): State<ClickhouseService>,
+    ApiJson(request): ApiJson<TokenInfoBatchRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if request.pool_addresses.len() > MAX_BATCH_TOKEN_INFO {
+        return Err(ApiError::bad_request(format!(
+            "at most {} pool_addresses per request",
+            MAX_BATCH_TOKEN_INFO
+        )));
+    }
+
+    let mut pool_addresses = Vec::with_capacity(request.pool_addresses.len());
+    for address in &request.pool_addresses {
+        let pool = Pubkey::from_str(address)
+            .map_err(|_| ApiError::bad_request(format!("invalid address: {}", address)))?;
+        pool_addresses.push(pool.to_string());
+    }
+
+    match db.get_token_info_batch(&pool_addresses).await {
+        Ok(token_info) => Ok(Json(json!(token_info))),
+        Err(e) => {
+            error!("Error getting token info batch: {}", e);
+            Err(e.into())
+        }
     }
 }
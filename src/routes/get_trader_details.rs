@@ -1,37 +1,41 @@
 use std::str::FromStr;
 
-use axum::{
-    Json,
-    extract::{Query, State},
-    http::StatusCode,
-};
+use axum::{Json, extract::State};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use spl_token::solana_program::pubkey::Pubkey;
 use tracing::{error, warn};
 
-use crate::services::clickhouse::ClickhouseService;
+use crate::{
+    routes::{error::ApiError, extractors::ApiQuery},
+    services::clickhouse::ClickhouseService,
+};
 
+// Both are optional here so a missing one gets a 400 naming it, not a bare
+// deserialization error
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TraderParams {
     #[serde(rename = "makerAddress")]
-    creator: String,
+    creator: Option<String>,
     #[serde(rename = "poolAddress")]
-    pool_address: String,
+    pool_address: Option<String>,
+}
+
+// The `name` query param as a pubkey, or a 400 naming it
+fn required_pubkey(value: Option<&str>, name: &str) -> Result<Pubkey, ApiError> {
+    let value = value.ok_or_else(|| ApiError::bad_request(format!("missing {}", name)))?;
+    Pubkey::from_str(value).map_err(|e| {
+        warn!(?e, "failed to parse {} in get_trader_details", name);
+        ApiError::bad_request(format!("invalid {}: {}", name, value))
+    })
 }
 
 pub async fn get_trader_details(
-    Query(query): Query<TraderParams>,
+    ApiQuery(query): ApiQuery<TraderParams>,
     State(db): State<ClickhouseService>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let creator = Pubkey::from_str(&query.creator).map_err(|e| {
-        warn!(?e, "failed to encode creator in get_trader_details");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    let pool_address = Pubkey::from_str(&query.pool_address).map_err(|e| {
-        warn!(?e, "failed to encode pool_address in get_trader_details");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let creator = required_pubkey(query.creator.as_deref(), "makerAddress")?;
+    let pool_address = required_pubkey(query.pool_address.as_deref(), "poolAddress")?;
     match db
         .get_trader_details(creator.to_string(), pool_address.to_string())
         .await
@@ -39,7 +43,7 @@ pub async fn get_trader_details(
         Ok(data) => Ok(Json(json!(data))),
         Err(e) => {
             error!("Error getting get trader details: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(e.into())
         }
     }
 }
@@ -1,17 +1,17 @@
 use std::str::FromStr;
 
-use axum::{
-    Json,
-    extract::{Path, Query, State},
-    http::StatusCode,
-};
+use axum::{Json, extract::State};
 use chrono::{DateTime, Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use spl_token::solana_program::pubkey::Pubkey;
 use tracing::{error, warn};
 
-use crate::services::clickhouse::ClickhouseService;
+use crate::{
+    models::swap::{DBSwap, LeanSwap, UiSwap},
+    routes::{error::ApiError, extractors::ApiQuery},
+    services::clickhouse::ClickhouseService,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 
@@ -19,21 +19,26 @@ pub struct GetTradesParams {
     pool_address: String,
     start_date: Option<String>,
     end_date: Option<String>,
+    // false drops base_reserve / quote_reserve from each trade
+    include_reserves: Option<bool>,
 }
 
-fn parse_ymd_to_utc(date: &str) -> Result<DateTime<Utc>, StatusCode> {
-    let d = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| StatusCode::BAD_REQUEST)?;
-    let ndt = d.and_hms_opt(0, 0, 0).ok_or(StatusCode::BAD_REQUEST)?;
+fn parse_ymd_to_utc(date: &str) -> Result<DateTime<Utc>, ApiError> {
+    let d = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| ApiError::bad_request("dates must be YYYY-MM-DD"))?;
+    let ndt = d
+        .and_hms_opt(0, 0, 0)
+        .ok_or_else(|| ApiError::bad_request("dates must be YYYY-MM-DD"))?;
     Ok(DateTime::<Utc>::from_naive_utc_and_offset(ndt, Utc))
 }
 
 pub async fn get_trades(
     db: State<ClickhouseService>,
-    Query(params): Query<GetTradesParams>,
-) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
+    ApiQuery(params): ApiQuery<GetTradesParams>,
+) -> Result<Json<serde_json::Value>, ApiError> {
     let pool_address = Pubkey::from_str(&params.pool_address).map_err(|e| {
         warn!(?e, "failed to encode pool_address in get_trader_details");
-        StatusCode::INTERNAL_SERVER_ERROR
+        ApiError::bad_request("invalid pool_address")
     })?;
     let mut start_date = match params.start_date.as_deref() {
         Some(s) => Some(parse_ymd_to_utc(s)?),
@@ -52,13 +57,28 @@ pub async fn get_trades(
     }
     let trades = db
         .get_pool_swaps(pool_address.to_string(), start_date, end_date)
-        .await;
-
-    match trades {
-        Ok(trades) => Ok(Json(json!(trades))),
-        Err(e) => {
+        .await
+        .map_err(|e| {
             error!("Error getting trades: {}", e);
-            Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR)
-        }
+            ApiError::from(e)
+        })?;
+    if trades.is_empty() {
+        return Ok(Json(json!(trades)));
+    }
+
+    // One lookup for the whole page; every trade is in the same pool
+    let decimals = db
+        .get_swap_decimals(&pool_address.to_string())
+        .await
+        .map_err(|e| {
+            error!("Error getting swap decimals: {}", e);
+            ApiError::from(e)
+        })?;
+    let trades = trades.into_iter().map(|trade| decimals.normalize(trade));
+    if params.include_reserves == Some(false) {
+        let trades: Vec<UiSwap<LeanSwap>> = trades.map(UiSwap::lean).collect();
+        return Ok(Json(json!(trades)));
     }
+    let trades: Vec<UiSwap<DBSwap>> = trades.collect();
+    Ok(Json(json!(trades)))
 }
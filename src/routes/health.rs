@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use axum::{Json, extract::State, http::StatusCode};
+use serde_json::json;
+use tracing::error;
+
+use crate::services::clickhouse::ClickhouseService;
+
+pub async fn health(State(db): State<ClickhouseService>) -> (StatusCode, Json<serde_json::Value>) {
+    match db.ping().await {
+        Ok(()) => (StatusCode::OK, Json(json!({ "status": "ok" }))),
+        Err(e) => {
+            error!("Health check failed: {}", e);
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({ "status": "degraded", "error": e.to_string() })),
+            )
+        }
+    }
+}
+
+// Readiness check: only 200 when the pulse query path works end to end
+pub async fn health_deep(
+    State(db): State<ClickhouseService>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let deadline_ms = std::env::var("HEALTH_DEEP_TIMEOUT_MS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(2_000);
+
+    match db.deep_ping(Duration::from_millis(deadline_ms)).await {
+        Ok(()) => (StatusCode::OK, Json(json!({ "status": "ok" }))),
+        Err(e) => {
+            error!("Deep health check failed: {}", e);
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({ "status": "degraded", "error": e.to_string() })),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clickhouse::{
+        Client,
+        test::{Mock, handlers},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn ok_when_clickhouse_answers() {
+        let mock = Mock::new();
+        mock.add(handlers::provide(vec![1u8]));
+        let db = ClickhouseService::with_client(Client::default().with_url(mock.url()));
+
+        let (status, Json(body)) = health(State(db)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({ "status": "ok" }));
+    }
+
+    #[tokio::test]
+    async fn degraded_with_a_bad_clickhouse_url() {
+        let db = ClickhouseService::with_client(Client::default().with_url("http://127.0.0.1:1"));
+
+        let (status, Json(body)) = health(State(db)).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "degraded");
+    }
+
+    #[tokio::test]
+    async fn deep_ok_when_the_pulse_path_answers() {
+        let mock = Mock::new();
+        mock.add(handlers::provide(vec![1u64]));
+        let db = ClickhouseService::with_client(Client::default().with_url(mock.url()));
+
+        let (status, Json(body)) = health_deep(State(db)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({ "status": "ok" }));
+    }
+
+    #[tokio::test]
+    async fn deep_degraded_when_the_pulse_path_fails() {
+        let mock = Mock::new();
+        // e.g. UNKNOWN_TABLE for a missing view
+        mock.add(handlers::exception(60));
+        let db = ClickhouseService::with_client(Client::default().with_url(mock.url()));
+
+        let (status, Json(body)) = health_deep(State(db)).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "degraded");
+    }
+}
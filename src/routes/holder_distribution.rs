@@ -0,0 +1,21 @@
+use axum::{Json, extract::State};
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    routes::{error::ApiError, extractors::PoolAddress},
+    services::clickhouse::ClickhouseService,
+};
+
+pub async fn get_holder_distribution(
+    PoolAddress(mint): PoolAddress,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    match db.get_holder_distribution(mint).await {
+        Ok(distribution) => Ok(Json(json!(distribution))),
+        Err(e) => {
+            error!("Error getting holder distribution: {}", e);
+            Err(e.into())
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::convert::Infallible;
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use futures_util::stream;
+use tracing::error;
+
+use crate::{
+    routes::{error::ApiError, extractors::PoolAddress},
+    services::clickhouse::ClickhouseService,
+};
+
+// CSV export of a pool's base-token holders, largest first, pool vaults excluded
+pub async fn get_holders_csv(
+    PoolAddress(pool_address): PoolAddress,
+    State(db): State<ClickhouseService>,
+) -> Result<Response, ApiError> {
+    let holders = db
+        .get_holders_export(pool_address.clone())
+        .await
+        .map_err(|e| {
+            error!("Error exporting holders: {:?}", e);
+            ApiError::from(e)
+        })?;
+
+    // Owners are base58, so no field needs quoting
+    let header_line = std::iter::once("owner,amount,percent_of_supply\n".to_string());
+    let rows = holders.into_iter().map(|holder| {
+        format!(
+            "{},{},{}\n",
+            holder.owner, holder.amount, holder.percent_of_supply
+        )
+    });
+    let body = Body::from_stream(stream::iter(
+        header_line.chain(rows).map(Ok::<_, Infallible>),
+    ));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}-holders.csv\"", pool_address),
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use clickhouse::{
+        Client, Row,
+        test::{Mock, handlers},
+    };
+    use serde::Serialize;
+
+    use super::*;
+
+    // Column layout of the holders export query
+    #[derive(Serialize, Row)]
+    struct HolderExportRow {
+        owner: String,
+        amount_raw: f64,
+        token_supply: f64,
+        decimals: u8,
+        has_decimals: bool,
+    }
+
+    #[tokio::test]
+    async fn csv_has_a_header_and_decimal_adjusted_rows() {
+        let mock = Mock::new();
+        mock.add(handlers::provide(vec![HolderExportRow {
+            owner: "Holder1111111111111111111111111111111111111".to_string(),
+            amount_raw: 25_000_000.0 * 1e6,
+            token_supply: 1_000_000_000.0 * 1e6,
+            decimals: 6,
+            has_decimals: true,
+        }]));
+        let db = ClickhouseService::with_client(Client::default().with_url(mock.url()));
+
+        let pool = PoolAddress("So11111111111111111111111111111111111111112".to_string());
+        let response = get_holders_csv(pool, State(db)).await.unwrap();
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/csv; charset=utf-8"
+        );
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(bytes.to_vec()).unwrap(),
+            "owner,amount,percent_of_supply\n\
+             Holder1111111111111111111111111111111111111,25000000,2.5\n"
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use axum::{Json, extract::State};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    routes::{
+        error::ApiError,
+        extractors::{ApiQuery, PoolAddress},
+    },
+    services::clickhouse::ClickhouseService,
+    types::candlestick::Interval,
+};
+
+const MAX_HOLDERS_HISTORY_POINTS: u64 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct HoldersHistoryParams {
+    #[serde(default = "default_interval")]
+    interval: Interval,
+    #[serde(default = "default_limit")]
+    limit: u64,
+}
+
+fn default_interval() -> Interval {
+    Interval::OneHour
+}
+
+fn default_limit() -> u64 {
+    168
+}
+
+pub async fn get_holders_history(
+    PoolAddress(mint): PoolAddress,
+    ApiQuery(params): ApiQuery<HoldersHistoryParams>,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if params.limit == 0 || params.limit > MAX_HOLDERS_HISTORY_POINTS {
+        return Err(ApiError::bad_request(format!(
+            "limit must be between 1 and {}",
+            MAX_HOLDERS_HISTORY_POINTS
+        )));
+    }
+
+    match db
+        .get_holders_history(mint, params.interval.seconds(), params.limit)
+        .await
+    {
+        Ok(points) => Ok(Json(json!(points))),
+        Err(e) => {
+            error!("Error getting holders history: {}", e);
+            Err(e.into())
+        }
+    }
+}
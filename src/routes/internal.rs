@@ -0,0 +1,83 @@
+use std::net::SocketAddr;
+
+use axum::{Router, routing::get};
+
+use crate::{
+    routes::{
+        health::{health, health_deep},
+        metrics::metrics,
+    },
+    state::AppState,
+};
+
+// Health checks and Prometheus metrics, for load balancers and scrapers rather than clients
+pub fn internal_routes() -> Router<AppState> {
+    Router::new()
+        .route("/health", get(health))
+        .route("/health/deep", get(health_deep))
+        .route("/metrics", get(metrics))
+}
+
+// INTERNAL_ADDR (e.g. `127.0.0.1:9100`) serves the internal routes on their own listener,
+// without CORS or API keys, and drops them from the public port
+pub fn internal_addr_from_env() -> Option<SocketAddr> {
+    let addr = std::env::var("INTERNAL_ADDR")
+        .ok()
+        .filter(|addr| !addr.is_empty())?;
+    // Ignoring a typo here would quietly put /metrics back on the public port
+    Some(addr.parse().expect("INTERNAL_ADDR must be an ip:port"))
+}
+
+// What the public router mounts: nothing when an internal listener takes these routes
+pub fn public_internal_routes(internal_addr: Option<SocketAddr>) -> Router<AppState> {
+    match internal_addr {
+        Some(_) => Router::new(),
+        None => internal_routes(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use clickhouse::{Client, test::Mock};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::services::{
+        clickhouse::ClickhouseService, pulse_feed::PulseFeed, webhooks::WebhookRegistry,
+    };
+
+    async fn status(router: Router<AppState>, uri: &str) -> StatusCode {
+        let mock = Mock::new();
+        let state = AppState {
+            clickhouse: ClickhouseService::with_client(Client::default().with_url(mock.url())),
+            pulse_feed: PulseFeed::from_env(),
+            webhooks: WebhookRegistry::default(),
+        };
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+        router
+            .with_state(state)
+            .oneshot(request)
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn metrics_move_to_the_internal_listener_when_configured() {
+        let internal_addr = "127.0.0.1:9100".parse().ok();
+        assert_eq!(status(internal_routes(), "/metrics").await, StatusCode::OK);
+        assert_eq!(
+            status(public_internal_routes(internal_addr), "/metrics").await,
+            StatusCode::NOT_FOUND
+        );
+        // Without INTERNAL_ADDR the public port keeps serving them
+        assert_eq!(
+            status(public_internal_routes(None), "/metrics").await,
+            StatusCode::OK
+        );
+    }
+}
@@ -1,19 +1,15 @@
-use std::str::FromStr;
-
-use axum::{
-    Json,
-    extract::{Path, State},
-    http::StatusCode,
-};
+use axum::{Json, extract::State};
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use spl_token::solana_program::pubkey::Pubkey;
-use tracing::warn;
 
-use crate::{models::swap::SwapType, services::clickhouse::ClickhouseService};
+use crate::{
+    models::swap::SwapType,
+    routes::{error::ApiError, extractors::PoolAddress},
+    services::clickhouse::ClickhouseService,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 struct LastTransactionResponse {
@@ -33,24 +29,16 @@ struct LastTransactionResponse {
 
 pub async fn get_last_transaction(
     db: State<ClickhouseService>,
-    Path(address): Path<String>,
-) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
-    let pool_address = Pubkey::from_str(&address).map_err(|_| {
-        warn!(
-            "failed to parse pool address from last transasction {}",
-            address
-        );
-        StatusCode::BAD_REQUEST
-    })?;
-
-    let last_transaction = db.get_last_transaction(pool_address.to_string()).await;
+    PoolAddress(pool_address): PoolAddress,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let last_transaction = db.get_last_transaction(pool_address).await;
     match last_transaction {
         Ok(Some(swap)) => Ok(Json(json!(swap))),
         Ok(None) => {
-            return Err(axum::http::StatusCode::NOT_FOUND);
+            return Err(ApiError::not_found("no transactions for pool"));
         }
-        Err(_) => {
-            return Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+        Err(e) => {
+            return Err(e.into());
         }
     }
     // return Err(axum::http::StatusCode::NOT_FOUND);
@@ -0,0 +1,35 @@
+use axum::{Json, extract::State};
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    routes::{error::ApiError, extractors::PoolAddress},
+    services::clickhouse::ClickhouseService,
+};
+
+/// Heuristic liquidity-lock status for a pool.
+///
+/// - `burned`: the incinerator address holds the pool's LP mint
+///   (read from `pools.metadata.lp_mint`).
+/// - `locked`: no `REMOVE` event, and the latest quote reserve is within
+///   10% of the initial reserve.
+/// - `unlocked`: at least one `REMOVE` event pulled liquidity out.
+/// - `unknown`: anything else, including pools with no swaps indexed yet
+///   and pools whose reserves moved without a `REMOVE`.
+///
+/// This only looks at indexed swaps/accounts. It does not know about
+/// third-party lockers (time-locks, vesting programs), so LP sitting in a
+/// locker contract is never reported as `burned`.
+pub async fn get_liquidity_locked(
+    PoolAddress(pool_address): PoolAddress,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    match db.get_liquidity_lock_status(pool_address).await {
+        Ok(Some(status)) => Ok(Json(json!(status))),
+        Ok(None) => Err(ApiError::not_found("pool not found")),
+        Err(e) => {
+            error!("Error getting liquidity lock status: {}", e);
+            Err(e.into())
+        }
+    }
+}
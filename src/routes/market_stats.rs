@@ -0,0 +1,17 @@
+use axum::{Json, extract::State};
+use serde_json::json;
+use tracing::error;
+
+use crate::{routes::error::ApiError, services::clickhouse::ClickhouseService};
+
+pub async fn get_market_stats(
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    match db.get_market_stats().await {
+        Ok(stats) => Ok(Json(json!(stats))),
+        Err(e) => {
+            error!("Error getting market stats: {}", e);
+            Err(e.into())
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use axum::{http::header, response::IntoResponse};
+
+use crate::metrics::METRICS;
+
+pub async fn metrics() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        METRICS.render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn serves_text_exposition_format() {
+        let response = metrics().await.into_response();
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/plain; version=0.0.4"
+        );
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("# TYPE http_requests_total counter\n"));
+        assert!(body.contains("# TYPE clickhouse_query_duration_seconds histogram\n"));
+        assert!(body.contains("# TYPE socketio_connections gauge\n"));
+    }
+}
@@ -1,12 +1,40 @@
+pub mod authority;
 pub mod candle;
+pub mod creator;
+pub mod curve_history;
+pub mod dev_activity;
+pub mod error;
+pub mod exists;
+pub mod extractors;
+pub mod factory_stats;
+pub mod first_buyers;
+pub mod funding;
 pub mod get_candlestick;
 pub mod get_holders;
 pub mod get_pair_info;
+pub mod get_swap;
 pub mod get_token_info;
 pub mod get_top_traders;
 pub mod get_trader_details;
 pub mod get_trades;
+pub mod health;
+pub mod holder_distribution;
+pub mod holders_csv;
+pub mod holders_history;
+pub mod internal;
 pub mod last_transaction;
+pub mod liquidity_locked;
+pub mod market_stats;
+pub mod metrics;
+pub mod pnl;
 pub mod pool_report;
 pub mod pulse;
+pub mod recent_pools;
 pub mod search;
+pub mod sse;
+pub mod trending;
+pub mod webhooks;
+pub mod whale_trades;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,40 @@
+use axum::{Json, extract::State};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    routes::{
+        error::ApiError,
+        extractors::{ApiQuery, PoolAddress},
+    },
+    services::clickhouse::ClickhouseService,
+};
+
+// Optional unix-seconds window over the wallet's swaps
+#[derive(Debug, Deserialize)]
+pub struct PnlParams {
+    from: Option<i64>,
+    to: Option<i64>,
+}
+
+pub async fn get_wallet_pnl(
+    PoolAddress(user_address): PoolAddress,
+    ApiQuery(params): ApiQuery<PnlParams>,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if matches!((params.from, params.to), (Some(from), Some(to)) if from > to) {
+        return Err(ApiError::bad_request("from must not be after to"));
+    }
+
+    match db
+        .get_wallet_pnl(user_address, params.from, params.to)
+        .await
+    {
+        Ok(pnl) => Ok(Json(json!(pnl))),
+        Err(e) => {
+            error!("Error getting wallet pnl: {}", e);
+            Err(e.into())
+        }
+    }
+}
@@ -1,17 +1,17 @@
 use std::str::FromStr;
 
-use axum::{
-    Json,
-    extract::{Query, State},
-    http::StatusCode,
-};
+use axum::{Json, extract::State};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use spl_token::solana_program::pubkey::Pubkey;
 use tracing::warn;
 
-use crate::services::clickhouse::ClickhouseService;
+use crate::{
+    routes::{error::ApiError, extractors::ApiQuery},
+    services::clickhouse::ClickhouseService,
+    utils::quote_decimals_factor,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum ReportType {
@@ -27,19 +27,32 @@ pub enum ReportType {
     OneDay,
 }
 
+// Denomination of buy_volume / sell_volume in the response
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VolumeUnit {
+    // As indexed: quote amounts on SOL's 9-decimal scale whatever the quote token
+    #[default]
+    Raw,
+    // Rescaled to whole units of the pool's quote token
+    Quote,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 
 pub struct PoolReportParams {
     pool_address: String,
     report_type: ReportType,
+    #[serde(default)]
+    unit: VolumeUnit,
 }
 pub async fn get_pool_report(
-    Query(params): Query<PoolReportParams>,
+    ApiQuery(params): ApiQuery<PoolReportParams>,
     State(db): State<ClickhouseService>,
-) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     let pool_address = Pubkey::from_str(&params.pool_address).map_err(|e| {
         warn!(?e, "failed to encode pool_address in get_trader_details");
-        StatusCode::INTERNAL_SERVER_ERROR
+        ApiError::bad_request("invalid pool_address")
     })?;
 
     let pool_report = db
@@ -47,8 +60,18 @@ pub async fn get_pool_report(
         .await;
 
     match pool_report {
-        Ok(Some(report)) => Ok(Json(json!(report))),
-        Ok(None) => return Err(axum::http::StatusCode::NOT_FOUND),
-        Err(_) => return Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+        Ok(mut report) => {
+            if let VolumeUnit::Quote = params.unit {
+                for entry in &mut report {
+                    let factor = quote_decimals_factor(entry.quote_decimals);
+                    entry.report.buy_volume *= factor;
+                    entry.report.sell_volume *= factor;
+                    entry.report.open_price *= factor;
+                    entry.report.close_price *= factor;
+                }
+            }
+            Ok(Json(json!(report)))
+        }
+        Err(e) => Err(e.into()),
     }
 }
@@ -1,22 +1,29 @@
-use axum::{Json, extract::State, http::StatusCode};
+use std::sync::Arc;
+
+use axum::{Json, extract::State};
 use chrono::{DateTime, Utc};
 use clickhouse::Row;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tracing::info;
+use tracing::{debug, error, info};
 
 use crate::{
-    services::clickhouse::ClickhouseService,
+    routes::{
+        error::ApiError,
+        extractors::{FilterJson, PoolAddress},
+    },
+    services::{clickhouse::ClickhouseService, quote_tokens::QuoteTokenRegistry},
     types::{
-        filter::{PulseFilter, PulseTable},
-        pulse::{DevWalletFunding, PulseDataResponse},
+        filter::{Filters, PulseFilter, PulseTable, max_lookback_hours},
+        pulse::{DevWalletFunding, Lifecycle, PulseDataResponse},
     },
-    utils::{calculate_market_cap, calculate_percentage},
+    utils::{PoolReserves, calculate_market_cap, calculate_percentage, quote_decimals_factor},
 };
 
 // ClickHouse result row structure
 #[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize, Row)]
+#[cfg_attr(test, derive(Default))]
 pub struct PulseRow {
     pub pool_address: String,
     pub creator: String,
@@ -27,6 +34,7 @@ pub struct PulseRow {
     pub created_at: DateTime<Utc>,
     pub initial_token_base_reserve: f64,
     pub initial_token_quote_reserve: f64,
+    pub reversed: bool,
     pub bonding_curve_percent: f32,
 
     // Token metadata
@@ -37,14 +45,20 @@ pub struct PulseRow {
     pub website: Option<String>,
     pub twitter: Option<String>,
     pub telegram: Option<String>,
+    pub discord: Option<String>,
     pub mint_address: String,
     pub token_supply: f64,
     pub scale_factor: f64,
+    // No usable tokens row yet (missing, or its decimals unknown); the metadata above
+    // are defaults
+    pub metadata_pending: bool,
 
     // Liquidity/price
     pub liquidity_sol: f64,
     pub liquidity_token: f64,
     pub current_price_sol: f64,
+    // The pool has a swap in the lookback window; the fields above are fallbacks if not
+    pub has_trades: bool,
 
     // Holders
     pub num_holders: u64,
@@ -72,19 +86,261 @@ pub struct PulseRow {
     pub funded_at: Option<DateTime<Utc>>,
 }
 
-pub async fn pulse(
-    State(db): State<ClickhouseService>,
-    Json(input): Json<PulseFilter>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    let filters = input.filters;
-    println!("{:?}", filters);
-    let table = input.table;
-    println!("{:?}", table);
+impl PulseRow {
+    // Prices and reserves are indexed in SOL units (9 decimals); pools quoted in another
+    // token are rescaled by that token's decimals before market cap is computed
+    pub fn into_pulse_data(self, quote_tokens: &QuoteTokenRegistry) -> PulseDataResponse {
+        let quote_decimals = quote_tokens.resolve(&self.token_quote_address).decimals;
+        let top10_decimal_adjusted = (self.top10_amount_raw as f64) / self.scale_factor;
+        let top10_holders_percent = calculate_percentage(top10_decimal_adjusted, self.token_supply);
+        let dev_decimal_adjusted = (self.dev_amount_raw as f64) / self.scale_factor;
+        let dev_holds_percent = calculate_percentage(dev_decimal_adjusted, self.token_supply);
+        let snipers_holds_percent =
+            calculate_percentage(self.snipers_amount_raw, self.token_supply);
+        let reserves = PoolReserves::oriented(
+            self.reversed,
+            self.liquidity_token,
+            self.liquidity_sol,
+            self.current_price_sol,
+        );
+        let market_cap_sol =
+            calculate_market_cap(reserves.price, self.token_supply, quote_decimals);
+        let quote_factor = quote_decimals_factor(quote_decimals);
+        let age_seconds = (Utc::now() - self.created_at).num_seconds().max(0);
+        let lifecycle = Lifecycle::classify(&self.factory, age_seconds);
+
+        PulseDataResponse {
+            pair_address: self.pool_address,
+            liquidity_sol: reserves.quote * quote_factor,
+            liquidity_token: reserves.base,
+            token_address: self.mint_address,
+            bonding_curve_percent: self.bonding_curve_percent,
+            token_name: self.name,
+            token_symbol: self.symbol,
+            token_decimals: self.decimals as u8,
+            creator: self.creator,
+            protocol: self.factory,
+            website: self.website,
+            twitter: self.twitter,
+            telegram: self.telegram,
+            discord: self.discord,
+            top10_holders_percent,
+            dev_holds_percent,
+            snipers_holds_percent,
+            volume_sol: self.volume_sol * quote_factor,
+            market_cap_sol,
+            has_trades: self.has_trades,
+            created_at: self.created_at,
+            age_seconds,
+            lifecycle,
+            migration_count: self.migration_count as i64,
+            num_txns: self.num_txns,
+            num_buys: self.num_buys,
+            num_sells: self.num_sells,
+            num_holders: self.num_holders as i64,
+            supply: self.token_supply,
+            token_image: self.image,
+            metadata_pending: self.metadata_pending,
+            dev_wallet_funding: if let Some(funding_wallet) = self.funding_wallet_address {
+                Some(DevWalletFunding {
+                    funding_wallet_address: funding_wallet,
+                    wallet_address: self.wallet_address.unwrap_or_default(),
+                    amount_sol: self.amount_sol.unwrap_or_default(),
+                    hash: self.transfer_hash.unwrap_or_default(),
+                    funded_at: self.funded_at.unwrap_or(Utc::now()),
+                })
+            } else {
+                None
+            },
+        }
+    }
+}
+
+// Token metadata for the pulse queries. Decimals come from `tokens`, else from the mint's
+// `token_initialize_events` row. A token whose decimals are known in neither is left out
+// rather than scaled by 1, which would inflate its holder percentages; its pools then
+// show as metadata_pending.
+pub const TOKEN_CTE: &str = r#"tok AS (
+  SELECT
+    t.mint_address,
+    t.name, t.symbol, t.image,
+    coalesce(t.decimals, ti.decimals) AS decimals,
+    t.website, t.twitter, t.telegram, t.discord,
+    t.supply AS token_supply,
+    pow(10, coalesce(t.decimals, ti.decimals)) AS scale_factor
+  FROM tokens t
+  LEFT JOIN token_initialize_events ti ON ti.mint_address = t.mint_address
+  WHERE isNotNull(t.decimals) OR notEmpty(ti.mint_address)
+)"#;
+
+// Holder count / top-10 CTEs for the pulse query over `source`.
+//
+// The exact variant joins `accounts` and runs COUNT(DISTINCT owner) per pool, which
+// gets expensive once the pulse window covers many pools. The approximate variant
+// reads the precomputed `pool_holder_counts` MV instead; it is only as fresh as the
+// MV's last merge, so counts can lag the live accounts table by a few seconds. Pools
+// the MV hasn't seen yet fall back to the exact count, computed for those pools only.
+fn holders_ctes(source: &str, approximate: bool, lookback_hours: u32) -> String {
+    if !approximate {
+        return exact_holders_ctes(source, "holders_base", "top10_holders", lookback_hours);
+    }
+
+    let exact = exact_holders_ctes(
+        "holders_pending",
+        "holders_exact",
+        "top10_exact",
+        lookback_hours,
+    );
+    format!(
+        r#"holders_mv AS (
+  SELECT hc.pool_address, hc.num_holders, hc.top10_amount_raw, 1 AS in_mv
+  FROM (SELECT * FROM pool_holder_counts FINAL) hc
+  WHERE hc.pool_address IN (SELECT pool_address FROM {source})
+),
+holders_pending AS (
+  SELECT * FROM {source}
+  WHERE pool_address NOT IN (SELECT pool_address FROM holders_mv)
+),
+{exact}holders_base AS (
+  SELECT r.pool_address,
+         if(mv.in_mv = 1, CAST(mv.num_holders AS UInt64),
+            CAST(coalesce(he.num_holders, 0) AS UInt64)) AS num_holders
+  FROM {source} r
+  LEFT JOIN holders_mv mv ON mv.pool_address = r.pool_address
+  LEFT JOIN holders_exact he ON he.pool_address = r.pool_address
+),
+top10_holders AS (
+  SELECT r.pool_address,
+         if(mv.in_mv = 1, CAST(mv.top10_amount_raw AS Int64),
+            CAST(coalesce(te.top10_amount_raw, 0) AS Int64)) AS top10_amount_raw
+  FROM {source} r
+  LEFT JOIN holders_mv mv ON mv.pool_address = r.pool_address
+  LEFT JOIN top10_exact te ON te.pool_address = r.pool_address
+),
+"#
+    )
+}
+
+// Holder count and top-10 amount per pool in `source`, read from `accounts`
+fn exact_holders_ctes(source: &str, holders: &str, top10: &str, lookback_hours: u32) -> String {
+    format!(
+        r#"{holders} AS (
+  SELECT
+    r.pool_address,
+    COUNT(DISTINCT a.owner) AS num_holders
+  FROM {source} r
+  JOIN accounts a
+    ON a.mint = r.token_base_address
+   AND a.owner <> r.pool_address
+   AND a.owner <> r.pool_base_address
+   AND a.owner <> r.pool_quote_address
+   AND a.updated_at >= now() - INTERVAL {lookback_hours} HOUR
+  GROUP BY r.pool_address
+),
+{top10} AS (
+  SELECT pool_address, SUM(amount) AS top10_amount_raw
+  FROM (
+    SELECT r.pool_address, a.amount,
+           ROW_NUMBER() OVER (PARTITION BY r.pool_address ORDER BY a.amount DESC) AS rn
+    FROM {source} r
+    JOIN accounts a
+      ON a.mint = r.token_base_address
+     AND a.owner <> r.pool_address
+     AND a.owner <> r.pool_base_address
+     AND a.owner <> r.pool_quote_address
+     AND a.updated_at >= now() - INTERVAL {lookback_hours} HOUR
+  ) x
+  WHERE rn <= 10
+  GROUP BY pool_address
+),
+"#
+    )
+}
+
+// `vol_24h` over the 5m report buckets of the last 24h. `pool_report_5m` is fed
+// continuously, so the most recent bucket is partial but still counted by default;
+// `settling_period` (seconds) drops buckets that started within that window for
+// callers that prefer settled numbers over fresh ones.
+fn vol_24h_cte(source: &str, settling_period: u32) -> String {
+    format!(
+        r#"vol_24h AS (
+  SELECT s.pool_address,
+         SUM(s.buy_volume + s.sell_volume) AS volume_sol,
+         CAST(SUM(s.buy_count) AS Int64) AS num_buys,
+         CAST(SUM(s.sell_count) AS Int64) AS num_sells,
+         CAST(SUM(s.buy_count + s.sell_count) AS Int64) AS num_txns
+  FROM pool_report_5m s
+  JOIN {source} r ON r.pool_address = s.pool_address
+  WHERE  s.bucket_start >= now() - INTERVAL 24 HOUR
+    AND s.bucket_start <= now() - INTERVAL {settling_period} SECOND
+  GROUP BY s.pool_address
+)
+"#
+    )
+}
+
+// Counts the social links a token has set; NULL and '' both count as missing
+fn min_socials_condition(min_socials: u8) -> String {
+    format!(
+        "(notEmpty(coalesce(t.twitter, '')) + notEmpty(coalesce(t.website, '')) \
+         + notEmpty(coalesce(t.telegram, '')) + notEmpty(coalesce(t.discord, ''))) >= {}",
+        min_socials
+    )
+}
+
+// A keyword as the inside of a quoted LIKE pattern that matches it literally: `%`, `_` and
+// `\` are LIKE-escaped, then `\` and `'` are escaped again for the string literal
+fn like_literal(keyword: &str) -> String {
+    let mut escaped = String::with_capacity(keyword.len());
+    for c in keyword.chars() {
+        match c {
+            '\\' => escaped.push_str(r"\\\\"),
+            '%' | '_' => {
+                escaped.push_str(r"\\");
+                escaped.push(c);
+            }
+            '\'' => escaped.push_str(r"\'"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn approximate_holders_from_env() -> bool {
+    std::env::var("PULSE_APPROXIMATE_HOLDERS")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+// Builds the pulse query for `table` without ORDER BY / LIMIT, so the same CTEs and
+// filters back both `/pulse` and `/pulse/count`. Returns the query and its ORDER BY.
+fn build_pulse_query(
+    table: &PulseTable,
+    filters: &Filters,
+    approximate_holders: bool,
+    settling_period: u32,
+    lookback_hours: u32,
+    quote_tokens: &QuoteTokenRegistry,
+) -> (String, &'static str) {
+    // Liquidity, volume and market cap in the units and orientation `into_pulse_data`
+    // reports, so the SQL filters and `Filters::matches` agree on which pools pass
+    let quote_factor = quote_tokens.decimals_factor_sql("r.token_quote_address");
+    let liquidity = format!(
+        "(if(r.reversed, \
+         coalesce(ls.latest_base_reserve, r.initial_token_base_reserve), \
+         coalesce(ls.latest_quote_reserve, r.initial_token_quote_reserve)) * {quote_factor})"
+    );
+    let volume = format!("(coalesce(v.volume_sol, 0) * {quote_factor})");
+    let price = "coalesce(ls.latest_price_sol, 0)";
+    let market_cap = format!(
+        "(if(r.reversed, if({price} = 0, 0, 1 / {price}), {price}) \
+         * {quote_factor} * t.token_supply)"
+    );
     match table {
         PulseTable::NewPairs => {
             let mut query = String::new();
 
-            query.push_str(
+            query.push_str(&format!(
                 r#"
 WITH all_pools AS (
   SELECT
@@ -97,9 +353,10 @@ WITH all_pools AS (
     p.factory,
     p.created_at,
     p.initial_token_base_reserve,
-    p.initial_token_quote_reserve
+    p.initial_token_quote_reserve,
+    p.reversed
   FROM pools p
- WHERE p.created_at >= now() - INTERVAL 24 HOUR
+ WHERE p.created_at >= now() - INTERVAL {lookback_hours} HOUR
 ),
 pool_curve AS (
   SELECT 
@@ -117,8 +374,8 @@ pools_with_curve AS (
 r AS (
   SELECT * FROM pools_with_curve
   WHERE 1=1
-"#,
-            );
+"#
+            ));
             if let Some(min_age) = filters.age.min {
                 query.push_str(&format!(
                     " AND created_at <= now() - INTERVAL {} MINUTE",
@@ -146,25 +403,18 @@ r AS (
                 query.push_str(")");
             }
 
-            query.push_str(
+            query.push_str(&format!(
                 r#"
           ),
 
-tok AS (
-  SELECT
-    t.mint_address,
-    t.name, t.symbol, t.image, t.decimals,
-    t.website, t.twitter, t.telegram,
-    t.supply AS token_supply,
-    pow(10, t.decimals) AS scale_factor
-  FROM tokens t
-),
+{TOKEN_CTE},
 latest_swap AS (
   SELECT
     r.pool_address,
     s.base_reserve AS latest_base_reserve,
     s.quote_reserve AS latest_quote_reserve,
-    s.price_sol AS latest_price_sol
+    s.price_sol AS latest_price_sol,
+    notEmpty(coalesce(s.pool_address, '')) AS has_trades
   FROM pools_with_curve r
   LEFT JOIN (
     SELECT
@@ -173,39 +423,19 @@ latest_swap AS (
       argMax(quote_reserve, created_at) AS quote_reserve,
       argMax(price_sol, created_at) AS price_sol
     FROM swaps
-    WHERE created_at >= now() - INTERVAL 24 HOUR
+    WHERE created_at >= now() - INTERVAL {lookback_hours} HOUR
     GROUP BY pool_address
   ) s ON s.pool_address = r.pool_address
 ),
-holders_base AS (
-  SELECT
-    r.pool_address,
-    COUNT(DISTINCT a.owner) AS num_holders
-  FROM pools_with_curve r
-  JOIN accounts a
-    ON a.mint = r.token_base_address
-   AND a.owner <> r.pool_address
-   AND a.owner <> r.pool_base_address
-   AND a.owner <> r.pool_quote_address
-   AND a.updated_at >= now() - INTERVAL 24 HOUR
-  GROUP BY r.pool_address
-),
-top10_holders AS (
-  SELECT pool_address, SUM(amount) AS top10_amount_raw
-  FROM (
-    SELECT r.pool_address, a.amount,
-           ROW_NUMBER() OVER (PARTITION BY r.pool_address ORDER BY a.amount DESC) AS rn
-    FROM pools_with_curve r
-    JOIN accounts a
-      ON a.mint = r.token_base_address
-     AND a.owner <> r.pool_address
-     AND a.owner <> r.pool_base_address
-     AND a.owner <> r.pool_quote_address
-     AND a.updated_at >= now() - INTERVAL 24 HOUR
-  ) x
-  WHERE rn <= 10
-  GROUP BY pool_address
-),
+"#
+            ));
+            query.push_str(&holders_ctes(
+                "pools_with_curve",
+                approximate_holders,
+                lookback_hours,
+            ));
+            query.push_str(&format!(
+                r#"
 dev_hold AS (
   SELECT
     r.pool_address,
@@ -216,7 +446,7 @@ dev_hold AS (
    AND a.owner = r.creator
    AND a.owner <> r.pool_address
 
-   AND a.updated_at >= now() - INTERVAL 24 HOUR
+   AND a.updated_at >= now() - INTERVAL {lookback_hours} HOUR
   GROUP BY r.pool_address
 ),
 snipers_holds AS (
@@ -228,7 +458,7 @@ snipers_holds AS (
     AND s.creator <> r.pool_address
     AND s.creator <> r.pool_base_address
     AND s.creator <> r.pool_quote_address
-    AND s.created_at >= now() - INTERVAL 24 HOUR
+    AND s.created_at >= now() - INTERVAL {lookback_hours} HOUR
   GROUP BY s.pool_address
 ),
 dev_wallet_funding AS (
@@ -248,7 +478,7 @@ dev_wallet_funding AS (
       argMin(hash, created_at) AS hash,
       min(created_at) AS earliest_transfer_at
     FROM transfer_sol
-    WHERE created_at >= now() - INTERVAL 24 HOUR
+    WHERE created_at >= now() - INTERVAL {lookback_hours} HOUR
     GROUP BY destination
   ) ts ON ts.destination = r.creator
 ),
@@ -256,21 +486,14 @@ migration AS (
   SELECT r.creator,
          countIf(p2.pre_factory = 'PumpFun' AND p2.factory = 'PumpSwap') AS migration_count
   FROM pools_with_curve r
-  LEFT JOIN pools p2 ON p2.creator = r.creator AND p2.created_at >= now() - INTERVAL 24 HOUR
+  LEFT JOIN pools p2 ON p2.creator = r.creator AND p2.created_at >= now() - INTERVAL {lookback_hours} HOUR
   GROUP BY r.creator
 ),
-vol_24h AS (
-  SELECT s.pool_address,
-         SUM(s.buy_volume + s.sell_volume) AS volume_sol,
-         CAST(SUM(s.buy_count) AS Int64) AS num_buys,
-         CAST(SUM(s.sell_count) AS Int64) AS num_sells,
-         CAST(SUM(s.buy_count + s.sell_count) AS Int64) AS num_txns
-  FROM pool_report_5m s
-  JOIN pools_with_curve r ON r.pool_address = s.pool_address
-  WHERE  s.bucket_start >= now() - INTERVAL 24 HOUR
-    AND s.bucket_start < now() - INTERVAL 5 MINUTE
-  GROUP BY s.pool_address
-)
+"#
+            ));
+            query.push_str(&vol_24h_cte("pools_with_curve", settling_period));
+            query.push_str(
+                r#"
 SELECT
   r.pool_address AS pool_address,
   r.creator AS creator,
@@ -280,9 +503,10 @@ SELECT
   r.created_at AS created_at,
   r.initial_token_base_reserve AS initial_token_base_reserve,
   r.initial_token_quote_reserve AS initial_token_quote_reserve,
+  r.reversed AS reversed,
   r.curve_percentage AS bonding_curve_percent,
 
-  -- token meta
+  -- token meta; defaults (decimals 0) until the tokens row is indexed
   t.name AS name,
   t.symbol AS symbol,
   t.image AS image,
@@ -290,14 +514,17 @@ SELECT
   t.website AS website,
   t.twitter AS twitter,
   t.telegram AS telegram,
-  t.mint_address AS mint_address,
+  t.discord AS discord,
+  r.token_base_address AS mint_address,
   t.token_supply AS token_supply,
-  t.scale_factor AS scale_factor,
+  if(empty(t.mint_address), 1, t.scale_factor) AS scale_factor,
+  empty(t.mint_address) AS metadata_pending,
 
   -- liquidity/price (fallback to initial if no swaps yet)
   coalesce(ls.latest_quote_reserve, r.initial_token_quote_reserve) AS liquidity_sol,
   coalesce(ls.latest_base_reserve,  r.initial_token_base_reserve)  AS liquidity_token,
   coalesce(ls.latest_price_sol, 0)                                 AS current_price_sol,
+  coalesce(ls.has_trades, 0)                                       AS has_trades,
 
   -- holders
   coalesce(h.num_holders, 0)                                       AS num_holders,
@@ -340,13 +567,13 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
             // Top 10 holders filter
             if let Some(min_top10) = filters.top10_holders.min {
                 where_conditions.push(format!(
-                        "((coalesce(th.top10_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0) >= {}",
+                        "coalesce(((coalesce(th.top10_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0), 0) >= {}",
                         min_top10
                     ));
             }
             if let Some(max_top10) = filters.top10_holders.max {
                 where_conditions.push(format!(
-                        "((coalesce(th.top10_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0) <= {}",
+                        "coalesce(((coalesce(th.top10_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0), 0) <= {}",
                         max_top10
                     ));
             }
@@ -354,25 +581,25 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
             // Dev holding filter
             if let Some(min_dev) = filters.dev_holding.min {
                 where_conditions.push(format!(
-                        "((coalesce(d.dev_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0) >= {}",
+                        "coalesce(((coalesce(d.dev_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0), 0) >= {}",
                         min_dev
                     ));
             }
             if let Some(max_dev) = filters.dev_holding.max {
                 where_conditions.push(format!(
-                        "((coalesce(d.dev_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0) <= {}",
+                        "coalesce(((coalesce(d.dev_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0), 0) <= {}",
                         max_dev
                     ));
             }
             if let Some(min_snipers) = filters.snipers_holding.min {
                 where_conditions.push(format!(
-                    "(coalesce(sh.snipers_amount_raw,0) * 100.0) / nullif(t.token_supply,0) >= {}",
+                    "coalesce((coalesce(sh.snipers_amount_raw,0) * 100.0) / nullif(t.token_supply,0), 0) >= {}",
                     min_snipers
                 ));
             }
             if let Some(max_snipers) = filters.snipers_holding.max {
                 where_conditions.push(format!(
-                    "(coalesce(sh.snipers_amount_raw,0) * 100.0) / nullif(t.token_supply,0) <= {}",
+                    "coalesce((coalesce(sh.snipers_amount_raw,0) * 100.0) / nullif(t.token_supply,0), 0) <= {}",
                     max_snipers
                 ));
             }
@@ -395,38 +622,26 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
 
             // Liquidity filter
             if let Some(min_liquidity) = filters.liquidity.min {
-                where_conditions.push(format!(
-                    "coalesce(ls.latest_quote_reserve, r.initial_token_quote_reserve) >= {}",
-                    min_liquidity
-                ));
+                where_conditions.push(format!("{liquidity} >= {}", min_liquidity));
             }
             if let Some(max_liquidity) = filters.liquidity.max {
-                where_conditions.push(format!(
-                    "coalesce(ls.latest_quote_reserve, r.initial_token_quote_reserve) <= {}",
-                    max_liquidity
-                ));
+                where_conditions.push(format!("{liquidity} <= {}", max_liquidity));
             }
 
             // Volume filter
             if let Some(min_volume) = filters.volume.min {
-                where_conditions.push(format!("coalesce(v.volume_sol, 0) >= {}", min_volume));
+                where_conditions.push(format!("{volume} >= {}", min_volume));
             }
             if let Some(max_volume) = filters.volume.max {
-                where_conditions.push(format!("coalesce(v.volume_sol, 0) <= {}", max_volume));
+                where_conditions.push(format!("{volume} <= {}", max_volume));
             }
 
             // Market cap filter
             if let Some(min_market_cap) = filters.market_cap.min {
-                where_conditions.push(format!(
-                    "(coalesce(ls.latest_price_sol, 0) * t.token_supply) >= {}",
-                    min_market_cap
-                ));
+                where_conditions.push(format!("{market_cap} >= {}", min_market_cap));
             }
             if let Some(max_market_cap) = filters.market_cap.max {
-                where_conditions.push(format!(
-                    "(coalesce(ls.latest_price_sol, 0) * t.token_supply) <= {}",
-                    max_market_cap
-                ));
+                where_conditions.push(format!("{market_cap} <= {}", max_market_cap));
             }
 
             // Transactions filter
@@ -477,8 +692,14 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
             if filters.telegram {
                 where_conditions.push("t.telegram IS NOT NULL AND t.telegram != ''".to_string());
             }
+            if filters.discord {
+                where_conditions.push("t.discord IS NOT NULL AND t.discord != ''".to_string());
+            }
             if filters.at_least_one_social {
-                where_conditions.push("(t.twitter IS NOT NULL AND t.twitter != '') OR (t.website IS NOT NULL AND t.website != '') OR (t.telegram IS NOT NULL AND t.telegram != '')".to_string());
+                where_conditions.push("((t.twitter IS NOT NULL AND t.twitter != '') OR (t.website IS NOT NULL AND t.website != '') OR (t.telegram IS NOT NULL AND t.telegram != '') OR (t.discord IS NOT NULL AND t.discord != ''))".to_string());
+            }
+            if let Some(min_socials) = filters.min_socials {
+                where_conditions.push(min_socials_condition(min_socials));
             }
 
             // Search keywords filter
@@ -486,6 +707,7 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
                 let search_conditions: Vec<String> = filters.search_keywords
                         .iter()
                         .map(|keyword| {
+                            let keyword = like_literal(keyword);
                             format!(
                                 "(LOWER(t.name) LIKE LOWER('%{}%') OR LOWER(t.symbol) LIKE LOWER('%{}%'))",
                                 keyword, keyword
@@ -500,6 +722,7 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
                 let exclude_conditions: Vec<String> = filters.exclude_keywords
                         .iter()
                         .map(|keyword| {
+                            let keyword = like_literal(keyword);
                             format!(
                                 "(LOWER(t.name) NOT LIKE LOWER('%{}%') AND LOWER(t.symbol) NOT LIKE LOWER('%{}%'))",
                                 keyword, keyword
@@ -510,87 +733,17 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
             }
 
             // Add WHERE clause if we have conditions
-            // if !where_conditions.is_empty() {
-            //     query.push_str(" WHERE ");
-            //     query.push_str(&where_conditions.join(" AND "));
-            // }
-
-            // Close the CTE and add basic SELECT
-            query.push_str(
-                r#"
-ORDER BY created_at DESC
-LIMIT 10
-"#,
-            );
-
-            // println!("{}", query);
-
-            let pools: Vec<PulseRow> = db.client.query(&query).fetch_all().await.map_err(|e| {
-                info!("DB query failed: {e}");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
-
-            println!("pools: {:?}", pools);
-
-            let mut data = Vec::new();
-            for pool in pools.into_iter() {
-                let top10_decimal_adjusted = (pool.top10_amount_raw as f64) / pool.scale_factor;
-                let top10_holders_percent =
-                    calculate_percentage(top10_decimal_adjusted, pool.token_supply);
-                let dev_decimal_adjusted = (pool.dev_amount_raw as f64) / pool.scale_factor;
-                let dev_holds_percent =
-                    calculate_percentage(dev_decimal_adjusted, pool.token_supply);
-                let snipers_holds_percent =
-                    calculate_percentage(pool.snipers_amount_raw, pool.token_supply);
-                let market_cap_sol =
-                    calculate_market_cap(pool.current_price_sol, pool.token_supply);
-                let pulse_data: PulseDataResponse = PulseDataResponse {
-                    pair_address: pool.pool_address,
-                    liquidity_sol: pool.liquidity_sol,
-                    liquidity_token: pool.liquidity_token,
-                    token_address: pool.mint_address,
-                    bonding_curve_percent: pool.bonding_curve_percent,
-                    token_name: pool.name,
-                    token_symbol: pool.symbol,
-                    token_decimals: pool.decimals as u8,
-                    creator: pool.creator,
-                    protocol: pool.factory,
-                    website: pool.website,
-                    twitter: pool.twitter,
-                    telegram: pool.telegram,
-                    top10_holders_percent,
-                    dev_holds_percent,
-                    snipers_holds_percent,
-                    volume_sol: pool.volume_sol,
-                    market_cap_sol,
-                    created_at: pool.created_at,
-                    migration_count: pool.migration_count as i64,
-                    num_txns: pool.num_txns,
-                    num_buys: pool.num_buys,
-                    num_sells: pool.num_sells,
-                    num_holders: pool.num_holders as i64,
-                    supply: pool.token_supply,
-                    token_image: pool.image,
-                    dev_wallet_funding: if let Some(funding_wallet) = pool.funding_wallet_address {
-                        Some(DevWalletFunding {
-                            funding_wallet_address: funding_wallet,
-                            wallet_address: pool.wallet_address.unwrap_or_default(),
-                            amount_sol: pool.amount_sol.unwrap_or_default(),
-                            hash: pool.transfer_hash.unwrap_or_default(),
-                            funded_at: pool.funded_at.unwrap_or(Utc::now()),
-                        })
-                    } else {
-                        None
-                    },
-                };
-                data.push(pulse_data);
+            if !where_conditions.is_empty() {
+                query.push_str(" WHERE ");
+                query.push_str(&where_conditions.join(" AND "));
             }
-            Ok(Json(json!({ "pools": data })))
+
+            (query, "ORDER BY created_at DESC, pool_address ASC")
         }
         PulseTable::FinalStretch => {
             let mut query = String::new();
 
-            query.push_str(
+            query.push_str(&format!(
                 r#"
 WITH all_pools AS (
   SELECT
@@ -603,9 +756,10 @@ WITH all_pools AS (
     p.factory,
     p.created_at,
     p.initial_token_base_reserve,
-    p.initial_token_quote_reserve
+    p.initial_token_quote_reserve,
+    p.reversed
   FROM pools p
- WHERE p.created_at >= now() - INTERVAL 24 HOUR
+ WHERE p.created_at >= now() - INTERVAL {lookback_hours} HOUR
 ),
 pool_curve AS (
   SELECT 
@@ -623,8 +777,8 @@ pools_with_curve AS (
 r AS (
   SELECT * FROM pools_with_curve
   WHERE 1=1
-"#,
-            );
+"#
+            ));
             if let Some(min_age) = filters.age.min {
                 query.push_str(&format!(
                     " AND created_at <= now() - INTERVAL {} MINUTE",
@@ -652,25 +806,18 @@ r AS (
                 query.push_str(")");
             }
 
-            query.push_str(
+            query.push_str(&format!(
                 r#"
           ),
 
-tok AS (
-  SELECT
-    t.mint_address,
-    t.name, t.symbol, t.image, t.decimals,
-    t.website, t.twitter, t.telegram,
-    t.supply AS token_supply,
-    pow(10, t.decimals) AS scale_factor
-  FROM tokens t
-),
+{TOKEN_CTE},
 latest_swap AS (
   SELECT
     r.pool_address,
     s.base_reserve AS latest_base_reserve,
     s.quote_reserve AS latest_quote_reserve,
-    s.price_sol AS latest_price_sol
+    s.price_sol AS latest_price_sol,
+    notEmpty(coalesce(s.pool_address, '')) AS has_trades
   FROM all_pools r
   LEFT JOIN (
     SELECT
@@ -679,39 +826,19 @@ latest_swap AS (
       argMax(quote_reserve, created_at) AS quote_reserve,
       argMax(price_sol, created_at) AS price_sol
     FROM swaps
-    WHERE created_at >= now() - INTERVAL 24 HOUR
+    WHERE created_at >= now() - INTERVAL {lookback_hours} HOUR
     GROUP BY pool_address
   ) s ON s.pool_address = r.pool_address
 ),
-holders_base AS (
-  SELECT
-    r.pool_address,
-    COUNT(DISTINCT a.owner) AS num_holders
-  FROM all_pools r
-  JOIN accounts a
-    ON a.mint = r.token_base_address
-   AND a.owner <> r.pool_address
-   AND a.owner <> r.pool_base_address
-   AND a.owner <> r.pool_quote_address
-   AND a.updated_at >= now() - INTERVAL 24 HOUR
-  GROUP BY r.pool_address
-),
-top10_holders AS (
-  SELECT pool_address, SUM(amount) AS top10_amount_raw
-  FROM (
-    SELECT r.pool_address, a.amount,
-           ROW_NUMBER() OVER (PARTITION BY r.pool_address ORDER BY a.amount DESC) AS rn
-    FROM all_pools r
-    JOIN accounts a
-      ON a.mint = r.token_base_address
-     AND a.owner <> r.pool_address
-     AND a.owner <> r.pool_base_address
-     AND a.owner <> r.pool_quote_address
-     AND a.updated_at >= now() - INTERVAL 24 HOUR
-  ) x
-  WHERE rn <= 10
-  GROUP BY pool_address
-),
+"#
+            ));
+            query.push_str(&holders_ctes(
+                "all_pools",
+                approximate_holders,
+                lookback_hours,
+            ));
+            query.push_str(&format!(
+                r#"
 dev_hold AS (
   SELECT
     r.pool_address,
@@ -721,7 +848,7 @@ dev_hold AS (
     ON a.mint  = r.token_base_address
    AND a.owner = r.creator
    AND a.owner <> r.pool_address
-   AND a.updated_at >= now() - INTERVAL 24 HOUR
+   AND a.updated_at >= now() - INTERVAL {lookback_hours} HOUR
   GROUP BY r.pool_address
 ),
 snipers_holds AS (
@@ -733,7 +860,7 @@ snipers_holds AS (
     AND s.creator <> r.pool_address
     AND s.creator <> r.pool_base_address
     AND s.creator <> r.pool_quote_address
-    AND s.created_at >= now() - INTERVAL 24 HOUR
+    AND s.created_at >= now() - INTERVAL {lookback_hours} HOUR
   GROUP BY s.pool_address
 ),
 dev_wallet_funding AS (
@@ -753,7 +880,7 @@ dev_wallet_funding AS (
       argMin(hash, created_at) AS hash,
       min(created_at) AS earliest_transfer_at
     FROM transfer_sol
-    WHERE created_at >= now() - INTERVAL 24 HOUR
+    WHERE created_at >= now() - INTERVAL {lookback_hours} HOUR
     GROUP BY destination
   ) ts ON ts.destination = r.creator
 ),
@@ -761,21 +888,14 @@ migration AS (
   SELECT r.creator,
          countIf(p2.pre_factory = 'PumpFun' AND p2.factory = 'PumpSwap') AS migration_count
   FROM all_pools r
-  LEFT JOIN pools p2 ON p2.creator = r.creator AND p2.created_at >= now() - INTERVAL 24 HOUR
+  LEFT JOIN pools p2 ON p2.creator = r.creator AND p2.created_at >= now() - INTERVAL {lookback_hours} HOUR
   GROUP BY r.creator
 ),
-
-vol_24h AS (
-  SELECT s.pool_address,
-         SUM(s.buy_volume + s.sell_volume) AS volume_sol,
-         CAST(SUM(s.buy_count) AS Int64) AS num_buys,
-         CAST(SUM(s.sell_count) AS Int64) AS num_sells,
-         CAST(SUM(s.buy_count + s.sell_count) AS Int64) AS num_txns
-  FROM pool_report_5m s
-  JOIN all_pools r ON r.pool_address = s.pool_address
-  WHERE  s.bucket_start < now() - INTERVAL 5 MINUTE
-  GROUP BY s.pool_address
-)
+"#
+            ));
+            query.push_str(&vol_24h_cte("all_pools", settling_period));
+            query.push_str(
+                r#"
 SELECT
   r.pool_address AS pool_address,
   r.creator AS creator,
@@ -785,9 +905,10 @@ SELECT
   r.created_at AS created_at,
   r.initial_token_base_reserve AS initial_token_base_reserve,
   r.initial_token_quote_reserve AS initial_token_quote_reserve,
+  r.reversed AS reversed,
   r.curve_percentage AS bonding_curve_percent,
 
-  -- token meta
+  -- token meta; defaults (decimals 0) until the tokens row is indexed
   t.name AS name,
   t.symbol AS symbol,
   t.image AS image,
@@ -795,14 +916,17 @@ SELECT
   t.website AS website,
   t.twitter AS twitter,
   t.telegram AS telegram,
-  t.mint_address AS mint_address,
+  t.discord AS discord,
+  r.token_base_address AS mint_address,
   t.token_supply AS token_supply,
-  t.scale_factor AS scale_factor,
+  if(empty(t.mint_address), 1, t.scale_factor) AS scale_factor,
+  empty(t.mint_address) AS metadata_pending,
 
   -- liquidity/price (fallback to initial if no swaps yet)
   coalesce(ls.latest_quote_reserve, r.initial_token_quote_reserve) AS liquidity_sol,
   coalesce(ls.latest_base_reserve,  r.initial_token_base_reserve)  AS liquidity_token,
   coalesce(ls.latest_price_sol, 0)                                 AS current_price_sol,
+  coalesce(ls.has_trades, 0)                                       AS has_trades,
 
   -- holders
   coalesce(h.num_holders, 0)                                       AS num_holders,
@@ -845,13 +969,13 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
             // Top 10 holders filter
             if let Some(min_top10) = filters.top10_holders.min {
                 where_conditions.push(format!(
-                        "((coalesce(th.top10_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0) >= {}",
+                        "coalesce(((coalesce(th.top10_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0), 0) >= {}",
                         min_top10
                     ));
             }
             if let Some(max_top10) = filters.top10_holders.max {
                 where_conditions.push(format!(
-                        "((coalesce(th.top10_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0) <= {}",
+                        "coalesce(((coalesce(th.top10_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0), 0) <= {}",
                         max_top10
                     ));
             }
@@ -859,25 +983,25 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
             // Dev holding filter
             if let Some(min_dev) = filters.dev_holding.min {
                 where_conditions.push(format!(
-                        "((coalesce(d.dev_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0) >= {}",
+                        "coalesce(((coalesce(d.dev_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0), 0) >= {}",
                         min_dev
                     ));
             }
             if let Some(max_dev) = filters.dev_holding.max {
                 where_conditions.push(format!(
-                        "((coalesce(d.dev_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0) <= {}",
+                        "coalesce(((coalesce(d.dev_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0), 0) <= {}",
                         max_dev
                     ));
             }
             if let Some(min_snipers) = filters.snipers_holding.min {
                 where_conditions.push(format!(
-                    "(coalesce(sh.snipers_amount_raw,0) * 100.0) / nullif(t.token_supply,0) >= {}",
+                    "coalesce((coalesce(sh.snipers_amount_raw,0) * 100.0) / nullif(t.token_supply,0), 0) >= {}",
                     min_snipers
                 ));
             }
             if let Some(max_snipers) = filters.snipers_holding.max {
                 where_conditions.push(format!(
-                    "(coalesce(sh.snipers_amount_raw,0) * 100.0) / nullif(t.token_supply,0) <= {}",
+                    "coalesce((coalesce(sh.snipers_amount_raw,0) * 100.0) / nullif(t.token_supply,0), 0) <= {}",
                     max_snipers
                 ));
             }
@@ -900,38 +1024,26 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
 
             // Liquidity filter
             if let Some(min_liquidity) = filters.liquidity.min {
-                where_conditions.push(format!(
-                    "coalesce(ls.latest_quote_reserve, r.initial_token_quote_reserve) >= {}",
-                    min_liquidity
-                ));
+                where_conditions.push(format!("{liquidity} >= {}", min_liquidity));
             }
             if let Some(max_liquidity) = filters.liquidity.max {
-                where_conditions.push(format!(
-                    "coalesce(ls.latest_quote_reserve, r.initial_token_quote_reserve) <= {}",
-                    max_liquidity
-                ));
+                where_conditions.push(format!("{liquidity} <= {}", max_liquidity));
             }
 
             // Volume filter
             if let Some(min_volume) = filters.volume.min {
-                where_conditions.push(format!("coalesce(v.volume_sol, 0) >= {}", min_volume));
+                where_conditions.push(format!("{volume} >= {}", min_volume));
             }
             if let Some(max_volume) = filters.volume.max {
-                where_conditions.push(format!("coalesce(v.volume_sol, 0) <= {}", max_volume));
+                where_conditions.push(format!("{volume} <= {}", max_volume));
             }
 
             // Market cap filter
             if let Some(min_market_cap) = filters.market_cap.min {
-                where_conditions.push(format!(
-                    "(coalesce(ls.latest_price_sol, 0) * t.token_supply) >= {}",
-                    min_market_cap
-                ));
+                where_conditions.push(format!("{market_cap} >= {}", min_market_cap));
             }
             if let Some(max_market_cap) = filters.market_cap.max {
-                where_conditions.push(format!(
-                    "(coalesce(ls.latest_price_sol, 0) * t.token_supply) <= {}",
-                    max_market_cap
-                ));
+                where_conditions.push(format!("{market_cap} <= {}", max_market_cap));
             }
 
             // Transactions filter
@@ -982,8 +1094,14 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
             if filters.telegram {
                 where_conditions.push("t.telegram IS NOT NULL AND t.telegram != ''".to_string());
             }
+            if filters.discord {
+                where_conditions.push("t.discord IS NOT NULL AND t.discord != ''".to_string());
+            }
             if filters.at_least_one_social {
-                where_conditions.push("(t.twitter IS NOT NULL AND t.twitter != '') OR (t.website IS NOT NULL AND t.website != '') OR (t.telegram IS NOT NULL AND t.telegram != '')".to_string());
+                where_conditions.push("((t.twitter IS NOT NULL AND t.twitter != '') OR (t.website IS NOT NULL AND t.website != '') OR (t.telegram IS NOT NULL AND t.telegram != '') OR (t.discord IS NOT NULL AND t.discord != ''))".to_string());
+            }
+            if let Some(min_socials) = filters.min_socials {
+                where_conditions.push(min_socials_condition(min_socials));
             }
 
             // Search keywords filter
@@ -991,6 +1109,7 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
                 let search_conditions: Vec<String> = filters.search_keywords
                         .iter()
                         .map(|keyword| {
+                            let keyword = like_literal(keyword);
                             format!(
                                 "(LOWER(t.name) LIKE LOWER('%{}%') OR LOWER(t.symbol) LIKE LOWER('%{}%'))",
                                 keyword, keyword
@@ -1005,6 +1124,7 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
                 let exclude_conditions: Vec<String> = filters.exclude_keywords
                         .iter()
                         .map(|keyword| {
+                            let keyword = like_literal(keyword);
                             format!(
                                 "(LOWER(t.name) NOT LIKE LOWER('%{}%') AND LOWER(t.symbol) NOT LIKE LOWER('%{}%'))",
                                 keyword, keyword
@@ -1020,78 +1140,15 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
                 query.push_str(&where_conditions.join(" AND "));
             }
 
-            // Close the CTE and add basic SELECT
-            query.push_str(
-                r#"
-ORDER BY bonding_curve_percent DESC
-LIMIT 10
-"#,
-            );
-
-            let pools: Vec<PulseRow> = db.client.query(&query).fetch_all().await.map_err(|e| {
-                info!("DB query failed: {e}");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
-
-            let mut data = Vec::new();
-            for pool in pools.into_iter() {
-                let top10_decimal_adjusted = (pool.top10_amount_raw as f64) / pool.scale_factor;
-                let top10_holders_percent =
-                    calculate_percentage(top10_decimal_adjusted, pool.token_supply);
-                let dev_decimal_adjusted = (pool.dev_amount_raw as f64) / pool.scale_factor;
-                let dev_holds_percent =
-                    calculate_percentage(dev_decimal_adjusted, pool.token_supply);
-                let snipers_holds_percent =
-                    calculate_percentage(pool.snipers_amount_raw, pool.token_supply);
-                let market_cap_sol =
-                    calculate_market_cap(pool.current_price_sol, pool.token_supply);
-                let pulse_data: PulseDataResponse = PulseDataResponse {
-                    pair_address: pool.pool_address,
-                    liquidity_sol: pool.liquidity_sol,
-                    liquidity_token: pool.liquidity_token,
-                    token_address: pool.mint_address,
-                    bonding_curve_percent: pool.bonding_curve_percent,
-                    token_name: pool.name,
-                    token_symbol: pool.symbol,
-                    token_decimals: pool.decimals as u8,
-                    creator: pool.creator,
-                    protocol: pool.factory,
-                    website: pool.website,
-                    twitter: pool.twitter,
-                    telegram: pool.telegram,
-                    top10_holders_percent,
-                    dev_holds_percent,
-                    snipers_holds_percent,
-                    volume_sol: pool.volume_sol,
-                    market_cap_sol,
-                    created_at: pool.created_at,
-                    migration_count: pool.migration_count as i64,
-                    num_txns: pool.num_txns,
-                    num_buys: pool.num_buys,
-                    num_sells: pool.num_sells,
-                    num_holders: pool.num_holders as i64,
-                    supply: pool.token_supply,
-                    token_image: pool.image,
-                    dev_wallet_funding: if let Some(funding_wallet) = pool.funding_wallet_address {
-                        Some(DevWalletFunding {
-                            funding_wallet_address: funding_wallet,
-                            wallet_address: pool.wallet_address.unwrap_or_default(),
-                            amount_sol: pool.amount_sol.unwrap_or_default(),
-                            hash: pool.transfer_hash.unwrap_or_default(),
-                            funded_at: pool.funded_at.unwrap_or(Utc::now()),
-                        })
-                    } else {
-                        None
-                    },
-                };
-                data.push(pulse_data);
-            }
-            Ok(Json(json!({ "pools": data })))
+            (
+                query,
+                "ORDER BY bonding_curve_percent DESC, pool_address ASC",
+            )
         }
         PulseTable::Migrated => {
             let mut query = String::new();
 
-            query.push_str(
+            query.push_str(&format!(
                 r#"
 WITH all_pools AS (
   SELECT
@@ -1105,9 +1162,10 @@ WITH all_pools AS (
     p.created_at,
     p.initial_token_base_reserve,
     p.initial_token_quote_reserve,
+    p.reversed,
     p.pre_factory
   FROM pools p
-  WHERE p.created_at >= now() - INTERVAL 24 HOUR
+  WHERE p.created_at >= now() - INTERVAL {lookback_hours} HOUR
     AND isNotNull(p.pre_factory)
     AND p.pre_factory <> ''
     AND p.factory <> ''
@@ -1127,8 +1185,8 @@ pools_with_curve AS (
 r AS (
   SELECT * FROM pools_with_curve
   WHERE 1=1
-"#,
-            );
+"#
+            ));
             if let Some(min_age) = filters.age.min {
                 query.push_str(&format!(
                     " AND created_at <= now() - INTERVAL {} MINUTE",
@@ -1156,24 +1214,17 @@ r AS (
                 query.push_str(")");
             }
 
-            query.push_str(
+            query.push_str(&format!(
                 r#"
           ),
-         tok AS (
-  SELECT
-    t.mint_address,
-    t.name, t.symbol, t.image, t.decimals,
-    t.website, t.twitter, t.telegram,
-    t.supply AS token_supply,
-    pow(10, t.decimals) AS scale_factor
-  FROM tokens t
-),
+{TOKEN_CTE},
 latest_swap AS (
   SELECT
     r.pool_address,
     s.base_reserve AS latest_base_reserve,
     s.quote_reserve AS latest_quote_reserve,
-    s.price_sol AS latest_price_sol
+    s.price_sol AS latest_price_sol,
+    notEmpty(coalesce(s.pool_address, '')) AS has_trades
   FROM all_pools r
   LEFT JOIN (
     SELECT
@@ -1182,39 +1233,19 @@ latest_swap AS (
       argMax(quote_reserve, created_at) AS quote_reserve,
       argMax(price_sol, created_at) AS price_sol
     FROM swaps
-    WHERE created_at >= now() - INTERVAL 24 HOUR
+    WHERE created_at >= now() - INTERVAL {lookback_hours} HOUR
     GROUP BY pool_address
   ) s ON s.pool_address = r.pool_address
 ),
-holders_base AS (
-  SELECT
-    r.pool_address,
-    COUNT(DISTINCT a.owner) AS num_holders
-  FROM all_pools r
-  JOIN accounts a
-    ON a.mint = r.token_base_address
-   AND a.owner <> r.pool_address
-   AND a.owner <> r.pool_base_address
-   AND a.owner <> r.pool_quote_address
-   AND a.updated_at >= now() - INTERVAL 24 HOUR
-  GROUP BY r.pool_address
-),
-top10_holders AS (
-  SELECT pool_address, SUM(amount) AS top10_amount_raw
-  FROM (
-    SELECT r.pool_address, a.amount,
-           ROW_NUMBER() OVER (PARTITION BY r.pool_address ORDER BY a.amount DESC) AS rn
-    FROM all_pools r
-    JOIN accounts a
-      ON a.mint = r.token_base_address
-     AND a.owner <> r.pool_address
-     AND a.owner <> r.pool_base_address
-     AND a.owner <> r.pool_quote_address
-     AND a.updated_at >= now() - INTERVAL 24 HOUR
-  ) x
-  WHERE rn <= 10
-  GROUP BY pool_address
-),
+"#
+            ));
+            query.push_str(&holders_ctes(
+                "all_pools",
+                approximate_holders,
+                lookback_hours,
+            ));
+            query.push_str(&format!(
+                r#"
 dev_hold AS (
   SELECT
     r.pool_address,
@@ -1224,7 +1255,7 @@ dev_hold AS (
     ON a.mint  = r.token_base_address
    AND a.owner = r.creator
    AND a.owner <> r.pool_address
-   AND a.updated_at >= now() - INTERVAL 24 HOUR
+   AND a.updated_at >= now() - INTERVAL {lookback_hours} HOUR
   GROUP BY r.pool_address
 ),
 snipers_holds AS (
@@ -1236,7 +1267,7 @@ snipers_holds AS (
     AND s.creator <> r.pool_address
     AND s.creator <> r.pool_base_address
     AND s.creator <> r.pool_quote_address
-    AND s.created_at >= now() - INTERVAL 24 HOUR
+    AND s.created_at >= now() - INTERVAL {lookback_hours} HOUR
   GROUP BY s.pool_address
 ),
 dev_wallet_funding AS (
@@ -1256,7 +1287,7 @@ dev_wallet_funding AS (
       argMin(hash, created_at) AS hash,
       min(created_at) AS earliest_transfer_at
     FROM transfer_sol
-    WHERE created_at >= now() - INTERVAL 24 HOUR
+    WHERE created_at >= now() - INTERVAL {lookback_hours} HOUR
     GROUP BY destination
   ) ts ON ts.destination = r.creator
 ),
@@ -1264,21 +1295,14 @@ migration AS (
   SELECT r.creator,
          countIf(p2.pre_factory = 'PumpFun' AND p2.factory = 'PumpSwap') AS migration_count
   FROM all_pools r
-  LEFT JOIN pools p2 ON p2.creator = r.creator AND p2.created_at >= now() - INTERVAL 24 HOUR
+  LEFT JOIN pools p2 ON p2.creator = r.creator AND p2.created_at >= now() - INTERVAL {lookback_hours} HOUR
   GROUP BY r.creator
 ),
-
-vol_24h AS (
-  SELECT s.pool_address,
-         SUM(s.buy_volume + s.sell_volume) AS volume_sol,
-         CAST(SUM(s.buy_count) AS Int64) AS num_buys,
-         CAST(SUM(s.sell_count) AS Int64) AS num_sells,
-         CAST(SUM(s.buy_count + s.sell_count) AS Int64) AS num_txns
-  FROM pool_report_5m s
-  JOIN all_pools r ON r.pool_address = s.pool_address
-  WHERE  s.bucket_start < now() - INTERVAL 5 MINUTE
-  GROUP BY s.pool_address
-)
+"#
+            ));
+            query.push_str(&vol_24h_cte("all_pools", settling_period));
+            query.push_str(
+                r#"
 SELECT
   r.pool_address AS pool_address,
   r.creator AS creator,
@@ -1288,9 +1312,10 @@ SELECT
   r.created_at AS created_at,
   r.initial_token_base_reserve AS initial_token_base_reserve,
   r.initial_token_quote_reserve AS initial_token_quote_reserve,
+  r.reversed AS reversed,
   r.curve_percentage AS bonding_curve_percent,
 
-  -- token meta
+  -- token meta; defaults (decimals 0) until the tokens row is indexed
   t.name AS name,
   t.symbol AS symbol,
   t.image AS image,
@@ -1298,14 +1323,17 @@ SELECT
   t.website AS website,
   t.twitter AS twitter,
   t.telegram AS telegram,
-  t.mint_address AS mint_address,
+  t.discord AS discord,
+  r.token_base_address AS mint_address,
   t.token_supply AS token_supply,
-  t.scale_factor AS scale_factor,
+  if(empty(t.mint_address), 1, t.scale_factor) AS scale_factor,
+  empty(t.mint_address) AS metadata_pending,
 
   -- liquidity/price (fallback to initial if no swaps yet)
   coalesce(ls.latest_quote_reserve, r.initial_token_quote_reserve) AS liquidity_sol,
   coalesce(ls.latest_base_reserve,  r.initial_token_base_reserve)  AS liquidity_token,
   coalesce(ls.latest_price_sol, 0)                                 AS current_price_sol,
+  coalesce(ls.has_trades, 0)                                       AS has_trades,
 
   -- holders
   coalesce(h.num_holders, 0)                                       AS num_holders,
@@ -1348,13 +1376,13 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
             // Top 10 holders filter
             if let Some(min_top10) = filters.top10_holders.min {
                 where_conditions.push(format!(
-                        "((coalesce(th.top10_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0) >= {}",
+                        "coalesce(((coalesce(th.top10_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0), 0) >= {}",
                         min_top10
                     ));
             }
             if let Some(max_top10) = filters.top10_holders.max {
                 where_conditions.push(format!(
-                        "((coalesce(th.top10_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0) <= {}",
+                        "coalesce(((coalesce(th.top10_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0), 0) <= {}",
                         max_top10
                     ));
             }
@@ -1362,25 +1390,25 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
             // Dev holding filter
             if let Some(min_dev) = filters.dev_holding.min {
                 where_conditions.push(format!(
-                        "((coalesce(d.dev_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0) >= {}",
+                        "coalesce(((coalesce(d.dev_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0), 0) >= {}",
                         min_dev
                     ));
             }
             if let Some(max_dev) = filters.dev_holding.max {
                 where_conditions.push(format!(
-                        "((coalesce(d.dev_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0) <= {}",
+                        "coalesce(((coalesce(d.dev_amount_raw,0) / nullif(t.scale_factor,0)) * 100.0) / nullif(t.token_supply,0), 0) <= {}",
                         max_dev
                     ));
             }
             if let Some(min_snipers) = filters.snipers_holding.min {
                 where_conditions.push(format!(
-                    "(coalesce(sh.snipers_amount_raw,0) * 100.0) / nullif(t.token_supply,0) >= {}",
+                    "coalesce((coalesce(sh.snipers_amount_raw,0) * 100.0) / nullif(t.token_supply,0), 0) >= {}",
                     min_snipers
                 ));
             }
             if let Some(max_snipers) = filters.snipers_holding.max {
                 where_conditions.push(format!(
-                    "(coalesce(sh.snipers_amount_raw,0) * 100.0) / nullif(t.token_supply,0) <= {}",
+                    "coalesce((coalesce(sh.snipers_amount_raw,0) * 100.0) / nullif(t.token_supply,0), 0) <= {}",
                     max_snipers
                 ));
             }
@@ -1395,38 +1423,26 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
 
             // Liquidity filter
             if let Some(min_liquidity) = filters.liquidity.min {
-                where_conditions.push(format!(
-                    "coalesce(ls.latest_quote_reserve, r.initial_token_quote_reserve) >= {}",
-                    min_liquidity
-                ));
+                where_conditions.push(format!("{liquidity} >= {}", min_liquidity));
             }
             if let Some(max_liquidity) = filters.liquidity.max {
-                where_conditions.push(format!(
-                    "coalesce(ls.latest_quote_reserve, r.initial_token_quote_reserve) <= {}",
-                    max_liquidity
-                ));
+                where_conditions.push(format!("{liquidity} <= {}", max_liquidity));
             }
 
             // Volume filter
             if let Some(min_volume) = filters.volume.min {
-                where_conditions.push(format!("coalesce(v.volume_sol, 0) >= {}", min_volume));
+                where_conditions.push(format!("{volume} >= {}", min_volume));
             }
             if let Some(max_volume) = filters.volume.max {
-                where_conditions.push(format!("coalesce(v.volume_sol, 0) <= {}", max_volume));
+                where_conditions.push(format!("{volume} <= {}", max_volume));
             }
 
             // Market cap filter
             if let Some(min_market_cap) = filters.market_cap.min {
-                where_conditions.push(format!(
-                    "(coalesce(ls.latest_price_sol, 0) * t.token_supply) >= {}",
-                    min_market_cap
-                ));
+                where_conditions.push(format!("{market_cap} >= {}", min_market_cap));
             }
             if let Some(max_market_cap) = filters.market_cap.max {
-                where_conditions.push(format!(
-                    "(coalesce(ls.latest_price_sol, 0) * t.token_supply) <= {}",
-                    max_market_cap
-                ));
+                where_conditions.push(format!("{market_cap} <= {}", max_market_cap));
             }
 
             // Transactions filter
@@ -1477,8 +1493,14 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
             if filters.telegram {
                 where_conditions.push("t.telegram IS NOT NULL AND t.telegram != ''".to_string());
             }
+            if filters.discord {
+                where_conditions.push("t.discord IS NOT NULL AND t.discord != ''".to_string());
+            }
             if filters.at_least_one_social {
-                where_conditions.push("(t.twitter IS NOT NULL AND t.twitter != '') OR (t.website IS NOT NULL AND t.website != '') OR (t.telegram IS NOT NULL AND t.telegram != '')".to_string());
+                where_conditions.push("((t.twitter IS NOT NULL AND t.twitter != '') OR (t.website IS NOT NULL AND t.website != '') OR (t.telegram IS NOT NULL AND t.telegram != '') OR (t.discord IS NOT NULL AND t.discord != ''))".to_string());
+            }
+            if let Some(min_socials) = filters.min_socials {
+                where_conditions.push(min_socials_condition(min_socials));
             }
 
             // Search keywords filter
@@ -1486,6 +1508,7 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
                 let search_conditions: Vec<String> = filters.search_keywords
                         .iter()
                         .map(|keyword| {
+                            let keyword = like_literal(keyword);
                             format!(
                                 "(LOWER(t.name) LIKE LOWER('%{}%') OR LOWER(t.symbol) LIKE LOWER('%{}%'))",
                                 keyword, keyword
@@ -1500,6 +1523,7 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
                 let exclude_conditions: Vec<String> = filters.exclude_keywords
                         .iter()
                         .map(|keyword| {
+                            let keyword = like_literal(keyword);
                             format!(
                                 "(LOWER(t.name) NOT LIKE LOWER('%{}%') AND LOWER(t.symbol) NOT LIKE LOWER('%{}%'))",
                                 keyword, keyword
@@ -1515,73 +1539,701 @@ LEFT JOIN migration        m  ON m.creator       = r.creator
                 query.push_str(&where_conditions.join(" AND "));
             }
 
-            // Close the CTE and add basic SELECT
-            query.push_str(
-                r#"
-ORDER BY created_at DESC
-LIMIT 10
-"#,
+            (query, "ORDER BY created_at DESC, pool_address ASC")
+        }
+    }
+}
+
+// Request checks and query building shared by `/pulse` and `/pulse/count`
+async fn prepare_pulse_query(
+    db: &ClickhouseService,
+    input: &PulseFilter,
+) -> Result<(String, &'static str), ApiError> {
+    let approximate_holders =
+        (input.approximate_holders || approximate_holders_from_env()) && db.holder_counts_mv;
+    let max_lookback = max_lookback_hours();
+    if input.lookback_hours == 0 || input.lookback_hours > max_lookback {
+        return Err(ApiError::bad_request(format!(
+            "lookbackHours must be between 1 and {}",
+            max_lookback
+        )));
+    }
+    debug!("{:?}", input.filters);
+    debug!("{:?}", input.table);
+    Ok(build_pulse_query(
+        &input.table,
+        &input.filters,
+        approximate_holders,
+        input.settling_period,
+        input.lookback_hours,
+        &db.quote_tokens,
+    ))
+}
+
+// The rows `POST /pulse` returns, typed, for callers inside the server (e.g. a websocket
+// snapshot) that would otherwise have to unwrap `{"pools": [...]}` again
+pub async fn pulse_rows(
+    db: &ClickhouseService,
+    input: &PulseFilter,
+) -> Result<Arc<Vec<PulseDataResponse>>, ApiError> {
+    let (mut query, order_by) = prepare_pulse_query(db, input).await?;
+    query.push_str(&format!("\n{}\nLIMIT 10\n", order_by));
+
+    let data = db.get_pulse_rows(&query).await.map_err(|e| {
+        info!("DB query failed: {e}");
+        ApiError::from(e)
+    })?;
+
+    debug!("pools: {:?}", data);
+    Ok(data)
+}
+
+pub async fn pulse(
+    State(db): State<ClickhouseService>,
+    FilterJson(input): FilterJson<PulseFilter>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let data = pulse_rows(&db, &input).await?;
+    Ok(Json(json!({ "pools": *data })))
+}
+
+// Number of pools the same request would match without the 10-row cap, for paging UIs
+pub async fn pulse_count(
+    State(db): State<ClickhouseService>,
+    FilterJson(input): FilterJson<PulseFilter>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let (query, _) = prepare_pulse_query(&db, &input).await?;
+    let query = format!("SELECT count() FROM ({})", query);
+
+    let count = db
+        .with_retry(|| db.client.query(&query).fetch_one::<u64>())
+        .await
+        .map_err(|e| {
+            info!("DB query failed: {e}");
+            ApiError::from(e)
+        })?;
+
+    Ok(Json(json!({ "count": count })))
+}
+
+// The same row `POST /pulse` and `new-pair` return, for one pool of any age
+pub async fn get_pool_pulse(
+    PoolAddress(pool_address): PoolAddress,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    match db.get_pool_pulse_data(pool_address).await {
+        Ok(pulse) => Ok(Json(json!(pulse))),
+        Err(e) => {
+            error!("Error getting pool pulse: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        defaults::{SOL_TOKEN, USDC_TOKEN},
+        services::clickhouse::testing::recorded_query,
+    };
+
+    // SQL the handler sends for `table` with no filters set
+    async fn pulse_sql(table: &str) -> String {
+        pulse_sql_with(table, json!({})).await
+    }
+
+    // Request body for `table` with `extra` merged over the empty filters
+    fn pulse_body(table: &str, extra: serde_json::Value) -> serde_json::Value {
+        let mut filters = json!({
+            "factories": { "pumpFun": true, "pumpSwap": true },
+            "searchKeywords": [],
+            "excludeKeywords": [],
+            "twitter": false,
+            "website": false,
+            "telegram": false,
+            "atLeastOneSocial": false,
+        });
+        for (key, value) in extra.as_object().unwrap() {
+            filters[key] = value.clone();
+        }
+        json!({ "table": table, "filters": filters })
+    }
+
+    fn pulse_input(table: &str, extra: serde_json::Value) -> PulseFilter {
+        serde_json::from_value(pulse_body(table, extra)).unwrap()
+    }
+
+    // SQL the handler sends for `table` with `extra` merged over the empty filters
+    async fn pulse_sql_with(table: &str, extra: serde_json::Value) -> String {
+        let input = pulse_input(table, extra);
+        recorded_query(|db| pulse(State(db), FilterJson(input))).await
+    }
+
+    // SQL `/pulse/count` sends for the same request
+    async fn count_sql_with(table: &str, extra: serde_json::Value) -> String {
+        let input = pulse_input(table, extra);
+        recorded_query(|db| pulse_count(State(db), FilterJson(input))).await
+    }
+
+    #[tokio::test]
+    async fn count_wraps_the_pulse_query_without_its_limit() {
+        for table in ["newPairs", "finalStretch", "migrated"] {
+            let rows = pulse_sql(table).await;
+            let count = count_sql_with(table, json!({})).await;
+            let (inner, _) = rows.rsplit_once("ORDER BY").unwrap();
+            let wrapped = count
+                .strip_prefix("SELECT count() FROM (")
+                .and_then(|q| q.strip_suffix(')'))
+                .unwrap();
+            assert_eq!(wrapped.trim_end(), inner.trim_end(), "{table}");
+        }
+    }
+
+    #[tokio::test]
+    async fn tightening_a_filter_narrows_the_count() {
+        for table in ["newPairs", "finalStretch", "migrated"] {
+            let loose = count_sql_with(table, json!({})).await;
+            let tight = count_sql_with(table, json!({ "twitter": true })).await;
+            assert!(!loose.contains("t.twitter IS NOT NULL"), "{table}");
+            assert!(tight.contains("t.twitter IS NOT NULL"), "{table}");
+        }
+
+        let mock = clickhouse::test::Mock::new();
+        mock.add(clickhouse::test::handlers::provide(vec![42_u64]));
+        let db = ClickhouseService::with_client(clickhouse::Client::default().with_url(mock.url()));
+        let input = pulse_input("newPairs", json!({ "twitter": true }));
+        let Json(body) = pulse_count(State(db), FilterJson(input)).await.unwrap();
+        assert_eq!(body, json!({ "count": 42 }));
+    }
+
+    #[tokio::test]
+    async fn every_table_breaks_ties_on_pool_address() {
+        for (table, order) in [
+            ("newPairs", "ORDER BY created_at DESC, pool_address ASC"),
+            (
+                "finalStretch",
+                "ORDER BY bonding_curve_percent DESC, pool_address ASC",
+            ),
+            ("migrated", "ORDER BY created_at DESC, pool_address ASC"),
+        ] {
+            let sql = pulse_sql(table).await;
+            assert!(sql.contains(order), "{table} has no tie-breaker");
+        }
+    }
+
+    #[tokio::test]
+    async fn recent_buckets_count_toward_volume() {
+        // A swap from 2 minutes ago sits in a bucket that started at most 7 minutes
+        // ago, which only the old `< now() - INTERVAL 5 MINUTE` cutoff would drop
+        for table in ["newPairs", "finalStretch", "migrated"] {
+            let sql = pulse_sql(table).await;
+            assert!(
+                sql.contains("s.bucket_start <= now() - INTERVAL 0 SECOND"),
+                "{table} leaves out recent volume"
             );
+            assert!(!sql.contains("INTERVAL 5 MINUTE"));
+        }
+        assert!(vol_24h_cte("all_pools", 300).contains("now() - INTERVAL 300 SECOND"));
+    }
 
-            let pools: Vec<PulseRow> = db.client.query(&query).fetch_all().await.map_err(|e| {
-                info!("DB query failed: {e}");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
-
-            let mut data = Vec::new();
-            for pool in pools.into_iter() {
-                let top10_decimal_adjusted = (pool.top10_amount_raw as f64) / pool.scale_factor;
-                let top10_holders_percent =
-                    calculate_percentage(top10_decimal_adjusted, pool.token_supply);
-                let dev_decimal_adjusted = (pool.dev_amount_raw as f64) / pool.scale_factor;
-                let dev_holds_percent =
-                    calculate_percentage(dev_decimal_adjusted, pool.token_supply);
-                let snipers_holds_percent =
-                    calculate_percentage(pool.snipers_amount_raw, pool.token_supply);
-                let market_cap_sol =
-                    calculate_market_cap(pool.current_price_sol, pool.token_supply);
-                let pulse_data: PulseDataResponse = PulseDataResponse {
-                    pair_address: pool.pool_address,
-                    liquidity_sol: pool.liquidity_sol,
-                    liquidity_token: pool.liquidity_token,
-                    token_address: pool.mint_address,
-                    bonding_curve_percent: pool.bonding_curve_percent,
-                    token_name: pool.name,
-                    token_symbol: pool.symbol,
-                    token_decimals: pool.decimals as u8,
-                    creator: pool.creator,
-                    protocol: pool.factory,
-                    website: pool.website,
-                    twitter: pool.twitter,
-                    telegram: pool.telegram,
-                    top10_holders_percent,
-                    dev_holds_percent,
-                    snipers_holds_percent,
-                    volume_sol: pool.volume_sol,
-                    market_cap_sol,
-                    created_at: pool.created_at,
-                    migration_count: pool.migration_count as i64,
-                    num_txns: pool.num_txns,
-                    num_buys: pool.num_buys,
-                    num_sells: pool.num_sells,
-                    num_holders: pool.num_holders as i64,
-                    supply: pool.token_supply,
-                    token_image: pool.image,
-                    dev_wallet_funding: if let Some(funding_wallet) = pool.funding_wallet_address {
-                        Some(DevWalletFunding {
-                            funding_wallet_address: funding_wallet,
-                            wallet_address: pool.wallet_address.unwrap_or_default(),
-                            amount_sol: pool.amount_sol.unwrap_or_default(),
-                            hash: pool.transfer_hash.unwrap_or_default(),
-                            funded_at: pool.funded_at.unwrap_or(Utc::now()),
-                        })
-                    } else {
-                        None
-                    },
-                };
-                data.push(pulse_data);
-            }
-            Ok(Json(json!({ "pools": data })))
+    #[tokio::test]
+    async fn min_socials_counts_the_links_a_token_has() {
+        // A twitter-only token scores 1, so it fails a threshold of 2
+        for table in ["newPairs", "finalStretch", "migrated"] {
+            let sql = pulse_sql_with(table, json!({ "minSocials": 2 })).await;
+            assert!(
+                sql.contains(
+                    "(notEmpty(coalesce(t.twitter, '')) + notEmpty(coalesce(t.website, '')) \
+                     + notEmpty(coalesce(t.telegram, '')) + notEmpty(coalesce(t.discord, ''))) >= 2"
+                ),
+                "{table} ignores minSocials"
+            );
+        }
+        for table in ["newPairs", "migrated"] {
+            assert!(
+                !pulse_sql(table)
+                    .await
+                    .contains("notEmpty(coalesce(t.twitter"),
+                "{table}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn discord_filter_requires_a_discord_link() {
+        for table in ["newPairs", "finalStretch", "migrated"] {
+            let sql = pulse_sql_with(table, json!({ "discord": true })).await;
+            assert!(
+                sql.contains("t.discord IS NOT NULL AND t.discord != ''"),
+                "{table}"
+            );
+            assert!(sql.contains("t.discord AS discord"), "{table}");
+        }
+        // Older clients don't send the flag
+        assert!(
+            !pulse_sql("migrated")
+                .await
+                .contains("t.discord IS NOT NULL")
+        );
+    }
+
+    #[tokio::test]
+    async fn keywords_are_matched_literally() {
+        for table in ["newPairs", "finalStretch", "migrated"] {
+            let sql = pulse_sql_with(
+                table,
+                json!({ "searchKeywords": ["it's 100%_"], "excludeKeywords": ["a\\b"] }),
+            )
+            .await;
+            assert!(
+                sql.contains(r"LOWER(t.name) LIKE LOWER('%it\'s 100\\%\\_%')"),
+                "{table}"
+            );
+            assert!(
+                sql.contains(r"LOWER(t.name) NOT LIKE LOWER('%a\\\\b%')"),
+                "{table}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn long_keywords_are_422() {
+        let (status, body) = rejected(pulse_body(
+            "newPairs",
+            json!({ "excludeKeywords": ["x".repeat(65)] }),
+        ))
+        .await;
+        assert_eq!(status, axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"]["field"], "filters.excludeKeywords");
+        assert_eq!(
+            body["error"]["reason"],
+            "keywords can be at most 64 characters"
+        );
+    }
+
+    #[tokio::test]
+    async fn any_social_stays_one_condition() {
+        // Unparenthesized, the OR chain would let a token with only a website skip `twitter`
+        for table in ["newPairs", "finalStretch", "migrated"] {
+            let sql =
+                pulse_sql_with(table, json!({ "atLeastOneSocial": true, "twitter": true })).await;
+            assert!(
+                sql.contains(
+                    "AND ((t.twitter IS NOT NULL AND t.twitter != '') OR \
+                     (t.website IS NOT NULL AND t.website != '') OR \
+                     (t.telegram IS NOT NULL AND t.telegram != '') OR \
+                     (t.discord IS NOT NULL AND t.discord != ''))"
+                ),
+                "{table}"
+            );
+        }
+    }
+
+    #[test]
+    fn discord_is_serialized_by_name() {
+        let row = PulseRow {
+            discord: Some("https://discord.gg/example".to_string()),
+            ..Default::default()
+        };
+        let data = json!(row.into_pulse_data(&QuoteTokenRegistry::default()));
+        assert_eq!(data["discord"], "https://discord.gg/example");
+    }
+
+    #[tokio::test]
+    async fn lookback_hours_widen_every_window_but_volume() {
+        let input: PulseFilter = serde_json::from_value(json!({
+            "table": "migrated",
+            "lookbackHours": 48,
+            "filters": {
+                "factories": { "pumpFun": true, "pumpSwap": true },
+                "searchKeywords": [],
+                "excludeKeywords": [],
+                "twitter": false,
+                "website": false,
+                "telegram": false,
+                "atLeastOneSocial": false,
+            },
+        }))
+        .unwrap();
+        let sql = recorded_query(|db| pulse(State(db), FilterJson(input))).await;
+        assert!(sql.contains("p.created_at >= now() - INTERVAL 48 HOUR"));
+        assert!(sql.contains("a.updated_at >= now() - INTERVAL 48 HOUR"));
+        // Only the 24h volume CTE keeps its fixed window
+        let fixed = vol_24h_cte("all_pools", 0)
+            .matches("INTERVAL 24 HOUR")
+            .count();
+        assert_eq!(sql.matches("INTERVAL 24 HOUR").count(), fixed);
+
+        let default = pulse_sql("migrated").await;
+        assert!(default.contains("p.created_at >= now() - INTERVAL 24 HOUR"));
+    }
+
+    #[tokio::test]
+    async fn lookback_past_the_configured_max_is_rejected() {
+        for lookback_hours in [0, 169] {
+            let input: PulseFilter = serde_json::from_value(json!({
+                "table": "newPairs",
+                "lookbackHours": lookback_hours,
+                "filters": {
+                    "factories": { "pumpFun": true, "pumpSwap": true },
+                    "searchKeywords": [],
+                    "excludeKeywords": [],
+                    "twitter": false,
+                    "website": false,
+                    "telegram": false,
+                    "atLeastOneSocial": false,
+                },
+            }))
+            .unwrap();
+            let mock = clickhouse::test::Mock::new();
+            let db =
+                ClickhouseService::with_client(clickhouse::Client::default().with_url(mock.url()));
+            let error = pulse(State(db), FilterJson(input)).await.unwrap_err();
+            assert_eq!(error.status, axum::http::StatusCode::BAD_REQUEST);
+        }
+    }
+
+    // Status and body `POST /pulse` rejects `body` with
+    async fn rejected(body: serde_json::Value) -> (axum::http::StatusCode, serde_json::Value) {
+        use axum::extract::FromRequest;
+
+        let request = axum::http::Request::post("/pulse")
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap();
+        let response = FilterJson::<PulseFilter>::from_request(request, &())
+            .await
+            .unwrap_err();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn out_of_range_filters_are_422_with_the_field() {
+        let max_age = max_lookback_hours() as i64 * 60;
+        let (status, body) = rejected(pulse_body(
+            "newPairs",
+            json!({ "age": { "max": max_age + 1 } }),
+        ))
+        .await;
+        assert_eq!(status, axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"]["code"], "invalid_filter");
+        assert_eq!(body["error"]["field"], "filters.age");
+        assert_eq!(
+            body["error"]["reason"],
+            format!("max cannot exceed {max_age}")
+        );
+
+        let (status, body) = rejected(pulse_body(
+            "newPairs",
+            json!({ "bondingCurve": { "max": 101 } }),
+        ))
+        .await;
+        assert_eq!(status, axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"]["field"], "filters.bondingCurve");
+        assert_eq!(body["error"]["reason"], "max cannot exceed 100");
+    }
+
+    #[tokio::test]
+    async fn inverted_ranges_are_422() {
+        let (status, body) = rejected(pulse_body(
+            "finalStretch",
+            json!({ "liquidity": { "min": 100, "max": 10 } }),
+        ))
+        .await;
+        assert_eq!(status, axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"]["field"], "filters.liquidity");
+        assert_eq!(body["error"]["reason"], "min 100 is greater than max 10");
+    }
+
+    #[tokio::test]
+    async fn huge_keyword_lists_are_422() {
+        let keywords: Vec<String> = (0..10_000).map(|i| format!("k{i}")).collect();
+        let (status, body) = rejected(pulse_body(
+            "newPairs",
+            json!({ "searchKeywords": keywords }),
+        ))
+        .await;
+        assert_eq!(status, axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"]["field"], "filters.searchKeywords");
+        assert_eq!(body["error"]["reason"], "at most 50 keywords are allowed");
+    }
+
+    #[tokio::test]
+    async fn malformed_pulse_json_is_still_a_400() {
+        use axum::extract::FromRequest;
+
+        let request = axum::http::Request::post("/pulse")
+            .body(axum::body::Body::from("{\"table\":"))
+            .unwrap();
+        let response = FilterJson::<PulseFilter>::from_request(request, &())
+            .await
+            .unwrap_err();
+        assert_eq!(response.status(), axum::http::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn identical_requests_within_the_ttl_share_one_query() {
+        use clickhouse::test::{Mock, handlers};
+
+        let mock = Mock::new();
+        // Only one response: a second round trip would fail
+        mock.add(handlers::provide(vec![PulseRow {
+            pool_address: "pool".to_string(),
+            ..Default::default()
+        }]));
+        let db = ClickhouseService::with_client(clickhouse::Client::default().with_url(mock.url()));
+
+        for _ in 0..2 {
+            let input = pulse_input("newPairs", json!({}));
+            let Json(body) = pulse(State(db.clone()), FilterJson(input)).await.unwrap();
+            assert_eq!(body["pools"][0]["pairAddress"], "pool");
+        }
+    }
+
+    #[tokio::test]
+    async fn internal_callers_get_typed_rows() {
+        use clickhouse::test::{Mock, handlers};
+
+        let mock = Mock::new();
+        mock.add(handlers::provide(vec![PulseRow {
+            pool_address: "pool".to_string(),
+            factory: "PumpFun".to_string(),
+            ..Default::default()
+        }]));
+        let db = ClickhouseService::with_client(clickhouse::Client::default().with_url(mock.url()));
+
+        let rows: Arc<Vec<PulseDataResponse>> =
+            pulse_rows(&db, &pulse_input("newPairs", json!({})))
+                .await
+                .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].pair_address, "pool");
+        assert_eq!(rows[0].protocol, "PumpFun");
+    }
+
+    #[tokio::test]
+    async fn default_filters_keep_pools_without_a_token_row() {
+        // Every request gets top10/dev/snipers ranges of 0..=100. For a pool whose token
+        // isn't indexed, token_supply is 0, so an unguarded percentage is NULL and the
+        // comparison would drop the pool.
+        for table in ["newPairs", "finalStretch", "migrated"] {
+            let sql = pulse_sql(table).await;
+            assert!(sql.contains("LEFT JOIN tok"), "{table}");
+            for holder in ["th.top10", "d.dev", "sh.snipers"] {
+                assert!(
+                    sql.contains(&format!("coalesce(((coalesce({holder}_amount_raw,0)"))
+                        || sql.contains(&format!("coalesce((coalesce({holder}_amount_raw,0)")),
+                    "{table} {holder}"
+                );
+            }
+            assert!(!sql.contains("nullif(t.token_supply,0) >="), "{table}");
+            assert!(!sql.contains("nullif(t.token_supply,0) <="), "{table}");
+            assert!(sql.contains("nullif(t.token_supply,0), 0) >= 0"), "{table}");
+        }
+    }
+
+    #[tokio::test]
+    async fn decimals_fall_back_to_the_mint_initialization() {
+        for table in ["newPairs", "finalStretch", "migrated"] {
+            let sql = pulse_sql(table).await;
+            assert!(
+                sql.contains("pow(10, coalesce(t.decimals, ti.decimals)) AS scale_factor"),
+                "{table}"
+            );
+            // Tokens with neither source are left out, not scaled by 1
+            assert!(
+                sql.contains("WHERE isNotNull(t.decimals) OR notEmpty(ti.mint_address)"),
+                "{table}"
+            );
         }
+
+        // A 9-decimal mint whose tokens row has no decimals: the query resolves them
+        // from token_initialize_events and scales the holder amounts accordingly
+        let pulse = PulseRow {
+            decimals: 9,
+            scale_factor: 1e9,
+            token_supply: 1_000_000_000.0,
+            top10_amount_raw: 100_000_000 * 1_000_000_000,
+            ..Default::default()
+        }
+        .into_pulse_data(&QuoteTokenRegistry::default());
+        assert_eq!(pulse.token_decimals, 9);
+        assert_eq!(pulse.top10_holders_percent, 10.0);
+    }
+
+    #[test]
+    fn rows_carry_their_age_and_lifecycle() {
+        let row = |factory: &str| PulseRow {
+            factory: factory.to_string(),
+            created_at: Utc::now() - chrono::Duration::minutes(10),
+            ..Default::default()
+        };
+        let pulse = row("PumpFun").into_pulse_data(&QuoteTokenRegistry::default());
+        // Allow for the clock ticking between building the row and converting it
+        assert!(
+            (600..=601).contains(&pulse.age_seconds),
+            "{}",
+            pulse.age_seconds
+        );
+        assert_eq!(pulse.lifecycle, Lifecycle::New);
+        assert_eq!(json!(pulse)["lifecycle"], "new");
+
+        let migrated = row("PumpSwap").into_pulse_data(&QuoteTokenRegistry::default());
+        assert_eq!(migrated.lifecycle, Lifecycle::Migrated);
+        assert_eq!(Lifecycle::classify("PumpFun", 3600), Lifecycle::Bonding);
+    }
+
+    #[test]
+    fn usdc_quoted_rows_are_rescaled() {
+        let registry = QuoteTokenRegistry::default();
+        let row = |token_quote_address: &str| PulseRow {
+            token_quote_address: token_quote_address.to_string(),
+            current_price_sol: 0.000_15,
+            token_supply: 1_000_000.0,
+            liquidity_sol: 2.0,
+            volume_sol: 0.5,
+            ..Default::default()
+        };
+
+        let sol = row(SOL_TOKEN.address).into_pulse_data(&registry);
+        assert_eq!(sol.liquidity_sol, 2.0);
+        assert_eq!(sol.volume_sol, 0.5);
+        let usdc = row(USDC_TOKEN.address).into_pulse_data(&registry);
+        assert_eq!(usdc.liquidity_sol, 2000.0);
+        assert_eq!(usdc.volume_sol, 500.0);
+        assert!((usdc.market_cap_sol - 1000.0 * sol.market_cap_sol).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn sql_filters_compare_rescaled_values() {
+        let factor = format!(
+            "multiIf(r.token_quote_address = '{}', 1000, 1)",
+            USDC_TOKEN.address
+        );
+        for table in ["newPairs", "finalStretch", "migrated"] {
+            let sql = pulse_sql_with(
+                table,
+                json!({
+                    "liquidity": { "min": 10 },
+                    "volume": { "min": 20 },
+                    "marketCap": { "max": 30 },
+                }),
+            )
+            .await;
+            assert!(
+                sql.contains(&format!(
+                    "coalesce(ls.latest_quote_reserve, r.initial_token_quote_reserve)) * {factor}) >= 10"
+                )),
+                "{table}"
+            );
+            assert!(
+                sql.contains(&format!("(coalesce(v.volume_sol, 0) * {factor}) >= 20")),
+                "{table}"
+            );
+            assert!(
+                sql.contains(&format!("* {factor} * t.token_supply) <= 30")),
+                "{table}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn sql_filters_orient_reversed_pools() {
+        for table in ["newPairs", "finalStretch", "migrated"] {
+            let sql = pulse_sql_with(
+                table,
+                json!({ "liquidity": { "min": 10 }, "marketCap": { "min": 30 } }),
+            )
+            .await;
+            // The same swap and inversion `PoolReserves::oriented` applies to the rows
+            assert!(
+                sql.contains(
+                    "(if(r.reversed, coalesce(ls.latest_base_reserve, r.initial_token_base_reserve), \
+                     coalesce(ls.latest_quote_reserve, r.initial_token_quote_reserve))"
+                ),
+                "{table}"
+            );
+            assert!(
+                sql.contains(
+                    "(if(r.reversed, if(coalesce(ls.latest_price_sol, 0) = 0, 0, \
+                     1 / coalesce(ls.latest_price_sol, 0)), coalesce(ls.latest_price_sol, 0))"
+                ),
+                "{table}"
+            );
+        }
+    }
+
+    #[test]
+    fn reversed_pools_are_reported_base_against_quote() {
+        let registry = QuoteTokenRegistry::default();
+        let row = |reversed: bool| PulseRow {
+            token_quote_address: SOL_TOKEN.address.to_string(),
+            reversed,
+            token_supply: 1_000_000.0,
+            ..Default::default()
+        };
+        let straight = PulseRow {
+            liquidity_token: 500_000.0,
+            liquidity_sol: 50.0,
+            current_price_sol: 0.0001,
+            ..row(false)
+        }
+        .into_pulse_data(&registry);
+        // The same pool indexed with its sides swapped
+        let reversed = PulseRow {
+            liquidity_token: 50.0,
+            liquidity_sol: 500_000.0,
+            current_price_sol: 10_000.0,
+            ..row(true)
+        }
+        .into_pulse_data(&registry);
+
+        assert_eq!(reversed.liquidity_sol, straight.liquidity_sol);
+        assert_eq!(reversed.liquidity_token, straight.liquidity_token);
+        assert!((reversed.market_cap_sol - straight.market_cap_sol).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn swapless_pools_are_told_apart_from_quiet_ones() {
+        for table in ["newPairs", "finalStretch", "migrated"] {
+            let sql = pulse_sql(table).await;
+            assert!(
+                sql.contains("coalesce(ls.has_trades, 0)"),
+                "{table} has no has_trades"
+            );
+        }
+
+        let registry = QuoteTokenRegistry::default();
+        let row = |has_trades: bool| PulseRow {
+            token_quote_address: SOL_TOKEN.address.to_string(),
+            has_trades,
+            ..Default::default()
+        };
+        let fresh = row(false).into_pulse_data(&registry);
+        let quiet = row(true).into_pulse_data(&registry);
+        // Both report zero volume, only has_trades differs
+        assert_eq!(fresh.volume_sol, quiet.volume_sol);
+        assert!(!fresh.has_trades);
+        assert!(quiet.has_trades);
+    }
+
+    #[test]
+    fn approximate_holders_read_the_materialized_view() {
+        let approximate = holders_ctes("all_pools", true, 24);
+        assert!(approximate.contains("FROM pool_holder_counts FINAL"));
+        // Pools missing from the view are counted exactly, and only those
+        assert!(
+            approximate.contains("LEFT JOIN holders_mv mv ON mv.pool_address = r.pool_address")
+        );
+        assert!(approximate.contains("FROM holders_pending r\n  JOIN accounts a"));
+        assert!(!approximate.contains("FROM all_pools r\n  JOIN accounts a"));
+
+        let exact = holders_ctes("all_pools", false, 24);
+        assert!(exact.contains("JOIN accounts a"));
+        assert!(!exact.contains("pool_holder_counts"));
     }
 }
@@ -0,0 +1,54 @@
+use axum::{Json, extract::State};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    routes::{error::ApiError, extractors::ApiQuery},
+    services::clickhouse::ClickhouseService,
+};
+
+const MAX_RECENT_POOLS: u64 = 100;
+const MAX_RECENT_HOURS: u32 = 168;
+
+#[derive(Debug, Deserialize)]
+pub struct RecentPoolsParams {
+    #[serde(default = "default_hours")]
+    hours: u32,
+    #[serde(default = "default_limit")]
+    limit: u64,
+}
+
+fn default_hours() -> u32 {
+    24
+}
+
+fn default_limit() -> u64 {
+    20
+}
+
+pub async fn get_recent_pools(
+    ApiQuery(params): ApiQuery<RecentPoolsParams>,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if params.hours == 0 || params.hours > MAX_RECENT_HOURS {
+        return Err(ApiError::bad_request(format!(
+            "hours must be between 1 and {}",
+            MAX_RECENT_HOURS
+        )));
+    }
+    if params.limit == 0 || params.limit > MAX_RECENT_POOLS {
+        return Err(ApiError::bad_request(format!(
+            "limit must be between 1 and {}",
+            MAX_RECENT_POOLS
+        )));
+    }
+
+    match db.get_recent_pools(params.hours, params.limit).await {
+        Ok(pools) => Ok(Json(json!(pools))),
+        Err(e) => {
+            error!("Error getting recent pools: {}", e);
+            Err(e.into())
+        }
+    }
+}
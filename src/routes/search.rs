@@ -1,18 +1,14 @@
 use std::str::FromStr;
 
-use axum::{
-    Json,
-    extract::{Query, State},
-    http::StatusCode,
-};
+use axum::{Json, extract::State};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tracing::{error, warn};
 
 use crate::models::swap::SwapType;
 use crate::{
-    defaults::{SOL_TOKEN, USDC_TOKEN},
     models::sniper::{DevHolding, SniperSummary},
+    routes::{error::ApiError, extractors::ApiQuery},
     services::clickhouse::ClickhouseService,
 };
 use chrono::{DateTime, Utc};
@@ -27,19 +23,18 @@ pub struct SearchParams {
 
 pub async fn search_pools(
     data: State<ClickhouseService>,
-    query: Query<SearchParams>,
-) -> Result<Json<serde_json::Value>, axum::http::StatusCode> {
+    ApiQuery(query): ApiQuery<SearchParams>,
+) -> Result<Json<serde_json::Value>, ApiError> {
     let search_term = query.search.trim_matches('"');
-    if search_term.len() == 44 {
-        // let pool_address = Pubkey::from_str_const(search_term).to_bytes().to_vec();
-        let pool_address = Pubkey::from_str(&search_term).map_err(|_| StatusCode::BAD_REQUEST)?;
+    // Base58 pubkeys are 32-44 chars; anything that decodes to one is an address lookup
+    if let Ok(pool_address) = Pubkey::from_str(search_term) {
         let pool_and_token_data = data.get_pool_and_token_data(pool_address.to_string()).await;
         match pool_and_token_data {
             Ok(pool_and_token_data) => Ok(Json(json!({ "data": pool_and_token_data }))),
 
             Err(e) => {
                 error!("Error getting pool and token data: {}", e);
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
+                Err(e.into())
             }
         }
     } else {
@@ -63,7 +58,7 @@ pub async fn search_pools(
             }
             Err(e) => {
                 error!("Error searching tokens: {}", e);
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
+                Err(e.into())
             }
         }
     }
@@ -0,0 +1,34 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::{Stream, stream};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
+
+use crate::services::pulse_feed::PulseFeed;
+
+// Streams the same `new-pair` rows the socket.io server emits, for clients that can't
+// speak socket.io
+pub async fn sse_pulse(
+    State(feed): State<PulseFeed>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let events = stream::unfold(feed.subscribe(), |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(pulse) => match Event::default().event("new-pair").json_data(&*pulse) {
+                    Ok(event) => return Some((Ok(event), rx)),
+                    Err(e) => warn!("Failed to encode pulse for SSE: {}", e),
+                },
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("SSE client fell behind, skipped {} pulse rows", skipped);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
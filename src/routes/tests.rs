@@ -0,0 +1,592 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    body::Body,
+    extract::{Query, State},
+    http::{Request, StatusCode, Uri},
+    routing::get,
+};
+use clickhouse::{
+    Client,
+    test::{Mock, handlers},
+};
+use futures_util::StreamExt;
+use serde_json::json;
+use tokio::sync::OnceCell;
+use tower::ServiceExt;
+use tower_http::compression::CompressionLayer;
+
+use super::{
+    error::ApiError,
+    extractors::{ApiPath, ApiQuery, PoolAddress},
+    get_candlestick::get_candlestick,
+    get_holders::get_holders,
+    get_pair_info::get_pair_info,
+    get_swap::get_swap,
+    get_token_info::get_token_info,
+    get_top_traders::get_top_traders,
+    get_trader_details::get_trader_details,
+    get_trades::get_trades,
+    pnl::get_wallet_pnl,
+    pool_report::get_pool_report,
+    pulse::PulseRow,
+    recent_pools::get_recent_pools,
+    search::search_pools,
+    sse::sse_pulse,
+    whale_trades::get_whale_trades,
+};
+use crate::{
+    defaults::{SOL_TOKEN, USDC_TOKEN},
+    models::{extra::HolderResponse, swap::DBSwap},
+    services::{
+        clickhouse::{
+            ClickhouseService,
+            testing::{ExistsRow, SwapDecimalsRow, recorded_query},
+        },
+        pulse_feed::PulseFeed,
+        quote_tokens::QuoteTokenRegistry,
+    },
+    types::{candlestick::Interval, token_info::TokenInfoRow},
+};
+
+const POOL: &str = "So11111111111111111111111111111111111111112";
+
+// A service whose only query comes back with no rows
+fn empty_db(mock: &Mock) -> State<ClickhouseService> {
+    mock.add(handlers::provide(Vec::<u8>::new()));
+    State(ClickhouseService::with_client(
+        Client::default().with_url(mock.url()),
+    ))
+}
+
+fn query<T: serde::de::DeserializeOwned>(uri: &str) -> ApiQuery<T> {
+    ApiQuery(Query::try_from_uri(&uri.parse::<Uri>().unwrap()).unwrap().0)
+}
+
+#[tokio::test]
+async fn list_endpoints_return_an_empty_array() {
+    let mock = Mock::new();
+    let Json(body) = get_pool_report(
+        query(&format!("/pool-report?pool_address={POOL}&report_type=5m")),
+        empty_db(&mock),
+    )
+    .await
+    .unwrap();
+    assert_eq!(body, json!([]));
+
+    let Json(body) = get_trades(
+        empty_db(&mock),
+        query(&format!("/trades?pool_address={POOL}")),
+    )
+    .await
+    .unwrap();
+    assert_eq!(body, json!([]));
+
+    let Json(body) = get_top_traders(PoolAddress(POOL.to_string()), empty_db(&mock))
+        .await
+        .unwrap();
+    assert_eq!(body, json!([]));
+}
+
+async fn candlestick(pool_rows: Vec<ExistsRow>) -> Result<Json<serde_json::Value>, ApiError> {
+    let mock = Mock::new();
+    // No candles in range, then the pool lookup
+    let db = empty_db(&mock);
+    mock.add(handlers::provide(pool_rows));
+    get_candlestick(
+        query(&format!(
+            "/candlestick?pool_address={POOL}&interval=1m&limit=100"
+        )),
+        db,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn candlestick_is_empty_for_a_known_pool_without_candles() {
+    let Json(body) = candlestick(vec![ExistsRow {
+        factory: "PumpFun".to_string(),
+        has_trades: false,
+    }])
+    .await
+    .unwrap();
+    assert_eq!(body, json!([]));
+}
+
+#[tokio::test]
+async fn candlestick_is_404_for_an_unknown_pool() {
+    let error = candlestick(Vec::new()).await.unwrap_err();
+    assert_eq!(error.status, StatusCode::NOT_FOUND);
+    assert_eq!(error.message, format!("pool not found: {POOL}"));
+}
+
+#[tokio::test]
+async fn candlestick_rejects_intervals_without_a_table() {
+    let mock = Mock::new();
+    let db = ClickhouseService {
+        candle_intervals: Arc::new(OnceCell::from(vec![
+            Interval::OneMinute,
+            Interval::FiveMinutes,
+        ])),
+        candles_swap_fallback: false,
+        ..ClickhouseService::with_client(Client::default().with_url(mock.url()))
+    };
+
+    let error = get_candlestick(
+        query(&format!(
+            "/candlestick?pool_address={POOL}&interval=15s&limit=100"
+        )),
+        State(db),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.status, StatusCode::BAD_REQUEST);
+    assert!(error.message.contains("supported: 1m, 5m"));
+}
+
+#[tokio::test]
+async fn intervals_without_a_table_are_served_by_default() {
+    let mock = Mock::new();
+    // No 15s table, so the candles come from swaps; then the pool lookup
+    mock.add(handlers::provide(Vec::<u8>::new()));
+    mock.add(handlers::provide(vec![ExistsRow {
+        factory: "PumpFun".to_string(),
+        has_trades: true,
+    }]));
+    let db = ClickhouseService {
+        candle_intervals: Arc::new(OnceCell::from(vec![Interval::OneMinute])),
+        ..ClickhouseService::with_client(Client::default().with_url(mock.url()))
+    };
+
+    let Json(body) = get_candlestick(
+        query(&format!(
+            "/candlestick?pool_address={POOL}&interval=15s&limit=100"
+        )),
+        State(db),
+    )
+    .await
+    .unwrap();
+    assert_eq!(body, json!([]));
+}
+
+#[tokio::test]
+async fn candlestick_limit_is_bounded() {
+    let mock = Mock::new();
+    for limit in ["0", "-1", "2000000000"] {
+        let error = get_candlestick(
+            query(&format!(
+                "/candlestick?pool_address={POOL}&interval=1s&limit={limit}&fill_gaps=true"
+            )),
+            State(ClickhouseService::with_client(
+                Client::default().with_url(mock.url()),
+            )),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.status, StatusCode::BAD_REQUEST, "{limit}");
+        assert_eq!(error.message, "limit must be between 1 and 5000");
+    }
+}
+
+#[tokio::test]
+async fn malformed_candlestick_queries_get_a_json_error() {
+    let mock = Mock::new();
+    let app = Router::new()
+        .route("/candlestick", get(get_candlestick))
+        .with_state(ClickhouseService::with_client(
+            Client::default().with_url(mock.url()),
+        ));
+    let request = Request::get(format!("/candlestick?pool_address={POOL}&interval=foo"))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(body["error"]["code"], "bad_request");
+}
+
+const SIGNATURE: &str =
+    "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW";
+
+#[tokio::test]
+async fn swap_lookup_returns_the_indexed_swap() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![DBSwap {
+        hash: SIGNATURE.to_string(),
+        swap_type: "BUY".to_string(),
+        ..Default::default()
+    }]));
+    let db = State(ClickhouseService::with_client(
+        Client::default().with_url(mock.url()),
+    ));
+
+    let Json(body) = get_swap(ApiPath(SIGNATURE.to_string()), db).await.unwrap();
+    assert_eq!(body["hash"], SIGNATURE);
+    assert_eq!(body["swap_type"], "BUY");
+}
+
+async fn trades_body(include_reserves: &str) -> serde_json::Value {
+    trades_body_quoted(SOL_TOKEN.address, false, include_reserves).await
+}
+
+async fn trades_body_quoted(
+    token_quote_address: &str,
+    reversed: bool,
+    include_reserves: &str,
+) -> serde_json::Value {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![DBSwap {
+        hash: SIGNATURE.to_string(),
+        base_amount: 1_500.25,
+        quote_amount: 0.1234567891,
+        base_reserve: 1000.0,
+        quote_reserve: 30.0,
+        ..Default::default()
+    }]));
+    mock.add(handlers::provide(vec![SwapDecimalsRow {
+        token_quote_address: token_quote_address.to_string(),
+        reversed,
+        base_decimals: Some(6),
+    }]));
+    let db = State(ClickhouseService::with_client(
+        Client::default().with_url(mock.url()),
+    ));
+    let Json(body) = get_trades(
+        db,
+        query(&format!("/trades?pool_address={POOL}{include_reserves}")),
+    )
+    .await
+    .unwrap();
+    body
+}
+
+#[tokio::test]
+async fn lean_trades_leave_out_the_reserves() {
+    let lean = trades_body("&include_reserves=false").await;
+    assert_eq!(lean[0]["hash"], SIGNATURE);
+    assert!(lean[0].get("base_reserve").is_none());
+    assert!(lean[0].get("quote_reserve").is_none());
+
+    for param in ["", "&include_reserves=true"] {
+        let full = trades_body(param).await;
+        assert_eq!(full[0]["base_reserve"], 1000.0, "{param}");
+        assert_eq!(full[0]["quote_reserve"], 30.0, "{param}");
+    }
+}
+
+#[tokio::test]
+async fn trades_carry_amounts_in_whole_tokens() {
+    for param in ["", "&include_reserves=false"] {
+        let trades = trades_body(param).await;
+        // Amounts are already whole tokens; the UI amounts only round to each token's decimals
+        assert_eq!(trades[0]["base_amount"], 1_500.25, "{param}");
+        assert_eq!(trades[0]["base_amount_ui"], 1_500.25, "{param}");
+        assert_eq!(trades[0]["quote_amount"], 0.1234567891, "{param}");
+        assert_eq!(trades[0]["quote_amount_ui"], 0.123456789, "{param}");
+    }
+}
+
+#[tokio::test]
+async fn usdc_trade_amounts_are_rescaled_to_whole_usdc() {
+    // Quote amounts are indexed on SOL's 9-decimal scale whatever the quote token
+    let trades = trades_body_quoted(USDC_TOKEN.address, false, "").await;
+    assert_eq!(trades[0]["quote_amount"], 0.1234567891);
+    assert_eq!(trades[0]["quote_amount_ui"], 123.456789);
+    assert_eq!(trades[0]["base_amount_ui"], 1_500.25);
+
+    // A reversed pool holds the USDC on its base side
+    let trades = trades_body_quoted(USDC_TOKEN.address, true, "").await;
+    assert_eq!(trades[0]["base_amount_ui"], 1_500_250.0);
+    assert_eq!(trades[0]["quote_amount_ui"], 0.123457);
+}
+
+#[tokio::test]
+async fn swap_lookup_is_404_for_unknown_hashes() {
+    let mock = Mock::new();
+    let error = get_swap(ApiPath(SIGNATURE.to_string()), empty_db(&mock))
+        .await
+        .unwrap_err();
+    assert_eq!(error.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn swap_lookup_rejects_malformed_signatures() {
+    let mock = Mock::new();
+    let db = State(ClickhouseService::with_client(
+        Client::default().with_url(mock.url()),
+    ));
+    let error = get_swap(ApiPath("not-a-signature".to_string()), db)
+        .await
+        .unwrap_err();
+    assert_eq!(error.status, StatusCode::BAD_REQUEST);
+}
+
+async fn pair_info_status(db: ClickhouseService, address: &str) -> StatusCode {
+    let app = Router::new()
+        .route("/pair-info/{pool_address}", get(get_pair_info))
+        .with_state(db);
+    let request = Request::get(format!("/pair-info/{address}"))
+        .body(Body::empty())
+        .unwrap();
+    app.oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn pair_info_is_404_for_unknown_pools() {
+    let mock = Mock::new();
+    let State(db) = empty_db(&mock);
+    assert_eq!(pair_info_status(db, POOL).await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn pair_info_rejects_malformed_addresses_before_querying() {
+    let mock = Mock::new();
+    let db = ClickhouseService::with_client(Client::default().with_url(mock.url()));
+    assert_eq!(
+        pair_info_status(db, "not-a-pool").await,
+        StatusCode::BAD_REQUEST
+    );
+}
+
+async fn token_info_status(db: ClickhouseService) -> StatusCode {
+    let app = Router::new()
+        .route("/token-info/{pool_address}", get(get_token_info))
+        .with_state(db);
+    let request = Request::get(format!("/token-info/{POOL}"))
+        .body(Body::empty())
+        .unwrap();
+    app.oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn token_info_is_404_for_unknown_pools() {
+    let mock = Mock::new();
+    let State(db) = empty_db(&mock);
+    assert_eq!(token_info_status(db).await, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn token_info_is_returned_for_indexed_pools() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![TokenInfoRow {
+        top10_amount_raw: 0.0,
+        top_n: 10,
+        top_n_amount_raw: 0.0,
+        dev_amount_raw: 0.0,
+        snipers_amount_raw: 0.0,
+        num_holders: 10,
+        token_supply: 1_000_000_000.0,
+        decimals: 6,
+        bundlers_amount_raw: 0.0,
+        has_decimals: true,
+    }]));
+    let db = ClickhouseService::with_client(Client::default().with_url(mock.url()));
+    assert_eq!(token_info_status(db).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn token_info_top_n_is_one_of_the_allowed_counts() {
+    for top_n in [0, 7, 100] {
+        let mock = Mock::new();
+        let db = State(ClickhouseService::with_client(
+            Client::default().with_url(mock.url()),
+        ));
+        let error = get_token_info(
+            db,
+            PoolAddress(POOL.to_string()),
+            query(&format!("/token-info?top_n={top_n}")),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.status, StatusCode::BAD_REQUEST, "{top_n}");
+    }
+
+    let sql = recorded_query(|db| {
+        get_token_info(
+            State(db),
+            PoolAddress(POOL.to_string()),
+            query("/token-info?top_n=20"),
+        )
+    })
+    .await;
+    assert!(sql.contains("toUInt32(20) AS top_n"));
+}
+
+#[tokio::test]
+async fn pnl_rejects_a_window_that_ends_before_it_starts() {
+    let mock = Mock::new();
+    let db = State(ClickhouseService::with_client(
+        Client::default().with_url(mock.url()),
+    ));
+    let error = get_wallet_pnl(
+        PoolAddress(POOL.to_string()),
+        query("/pnl?from=1700600000&to=1700000000"),
+        db,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.status, StatusCode::BAD_REQUEST);
+}
+
+// Error /trader-details returns for `params`, which never reach the database
+async fn trader_details_error(params: &str) -> ApiError {
+    let mock = Mock::new();
+    let db = State(ClickhouseService::with_client(
+        Client::default().with_url(mock.url()),
+    ));
+    get_trader_details(query(&format!("/trader-details?{params}")), db)
+        .await
+        .unwrap_err()
+}
+
+#[tokio::test]
+async fn trader_details_names_a_missing_maker_address() {
+    let error = trader_details_error(&format!("poolAddress={POOL}")).await;
+    assert_eq!(error.status, StatusCode::BAD_REQUEST);
+    assert_eq!(error.message, "missing makerAddress");
+}
+
+#[tokio::test]
+async fn trader_details_names_an_invalid_pool_address() {
+    let error = trader_details_error(&format!("makerAddress={POOL}&poolAddress=not-a-pool")).await;
+    assert_eq!(error.status, StatusCode::BAD_REQUEST);
+    assert_eq!(error.message, "invalid poolAddress: not-a-pool");
+}
+
+// First query /pools sends for `search`
+async fn search_sql(search: &str) -> String {
+    let params = query(&format!("/pools?search={search}"));
+    recorded_query(|db| search_pools(State(db), params)).await
+}
+
+#[tokio::test]
+async fn search_looks_up_any_valid_address() {
+    // 43 chars, which the old exactly-44 check sent to token search
+    let address = "nACToH83CofeRTiuqPaGyBTnmCxyHdo4XNJpXPUW3pL";
+    let sql = search_sql(address).await;
+    assert!(sql.contains("FROM pools"), "{sql}");
+    assert!(!sql.contains("FROM tokens t"));
+}
+
+#[tokio::test]
+async fn search_sends_symbols_to_token_search() {
+    assert!(search_sql("SOL").await.contains("FROM tokens t"));
+}
+
+#[tokio::test]
+async fn holders_limit_is_bounded() {
+    for limit in [0, 1001] {
+        let mock = Mock::new();
+        let db = State(ClickhouseService::with_client(
+            Client::default().with_url(mock.url()),
+        ));
+        let error = get_holders(
+            PoolAddress(POOL.to_string()),
+            query(&format!("/holders?limit={limit}")),
+            db,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.status, StatusCode::BAD_REQUEST, "{limit}");
+    }
+}
+
+#[tokio::test]
+async fn large_holder_lists_are_gzipped_when_accepted() {
+    let mock = Mock::new();
+    let holders: Vec<HolderResponse> = (0..500)
+        .map(|i| HolderResponse {
+            address: format!("Wallet{i}"),
+            account: format!("Account{i}"),
+            mint: POOL.to_string(),
+            decimals: 6,
+            amount: 1000.0 - i as f64,
+            delegated_amount: 0,
+            is_sniper: false,
+            is_bundler: false,
+        })
+        .collect();
+    mock.add(handlers::provide(holders));
+    mock.add(handlers::provide(vec![500_u64]));
+    let app = Router::new()
+        .route("/holders/{token_address}", get(get_holders))
+        .with_state(ClickhouseService::with_client(
+            Client::default().with_url(mock.url()),
+        ))
+        .layer(CompressionLayer::new());
+
+    let request = Request::get(format!("/holders/{POOL}?limit=500"))
+        .header("accept-encoding", "gzip")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-encoding"], "gzip");
+}
+
+#[tokio::test]
+async fn recent_pools_bounds_the_window_and_limit() {
+    for uri in [
+        "/recent-pools?hours=0",
+        "/recent-pools?hours=169",
+        "/recent-pools?limit=0",
+        "/recent-pools?limit=101",
+    ] {
+        let mock = Mock::new();
+        let db = State(ClickhouseService::with_client(
+            Client::default().with_url(mock.url()),
+        ));
+        let error = get_recent_pools(query(uri), db).await.unwrap_err();
+        assert_eq!(error.status, StatusCode::BAD_REQUEST, "{uri}");
+    }
+}
+
+#[tokio::test]
+async fn whale_trades_reject_a_non_positive_threshold() {
+    for uri in [
+        "/whale-trades?min_sol=0",
+        "/whale-trades?min_sol=-5",
+        "/whale-trades?min_sol=NaN",
+        "/whale-trades?limit=201",
+    ] {
+        let mock = Mock::new();
+        let db = State(ClickhouseService::with_client(
+            Client::default().with_url(mock.url()),
+        ));
+        let error = get_whale_trades(query(uri), db).await.unwrap_err();
+        assert_eq!(error.status, StatusCode::BAD_REQUEST, "{uri}");
+    }
+}
+
+#[tokio::test]
+async fn sse_clients_receive_published_pulse_rows() {
+    let feed = PulseFeed::from_env();
+    let app = Router::new()
+        .route("/sse/pulse", get(sse_pulse))
+        .with_state(feed.clone());
+    let response = app
+        .oneshot(Request::get("/sse/pulse").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+    let row = PulseRow {
+        pool_address: POOL.to_string(),
+        ..Default::default()
+    };
+    feed.publish(row.into_pulse_data(&QuoteTokenRegistry::default()));
+
+    let mut body = response.into_body().into_data_stream();
+    let chunk = tokio::time::timeout(std::time::Duration::from_secs(1), body.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    let event = String::from_utf8(chunk.to_vec()).unwrap();
+    assert!(event.starts_with("event: new-pair\n"), "{event}");
+    assert!(event.contains(POOL), "{event}");
+}
@@ -0,0 +1,42 @@
+use axum::{Json, extract::State};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    routes::{error::ApiError, extractors::ApiQuery},
+    services::clickhouse::ClickhouseService,
+};
+
+const MAX_TRENDING_POOLS: u64 = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct TrendingParams {
+    #[serde(default = "default_limit")]
+    limit: u64,
+    factory: Option<String>,
+}
+
+fn default_limit() -> u64 {
+    20
+}
+
+pub async fn get_trending_pools(
+    ApiQuery(params): ApiQuery<TrendingParams>,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if params.limit == 0 || params.limit > MAX_TRENDING_POOLS {
+        return Err(ApiError::bad_request(format!(
+            "limit must be between 1 and {}",
+            MAX_TRENDING_POOLS
+        )));
+    }
+
+    match db.get_trending_pools(params.limit, params.factory).await {
+        Ok(pools) => Ok(Json(json!(pools))),
+        Err(e) => {
+            error!("Error getting trending pools: {}", e);
+            Err(e.into())
+        }
+    }
+}
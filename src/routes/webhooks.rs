@@ -0,0 +1,179 @@
+use axum::{Extension, Json, extract::State, http::StatusCode};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    middleware::auth::ApiKey,
+    routes::{
+        error::ApiError,
+        extractors::{ApiPath, FilterJson},
+    },
+    services::webhooks::{WebhookRegistry, is_public_host},
+    types::filter::Filters,
+};
+
+const MAX_WEBHOOKS_PER_KEY: usize = 10;
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookRequest {
+    url: String,
+    // Shared secret used to sign deliveries; never returned
+    secret: String,
+    filters: Filters,
+}
+
+// Hooks belong to the API key that registered them. With auth disabled there is no key,
+// so every client shares the same owner.
+fn owner(key: Option<Extension<ApiKey>>) -> String {
+    key.map(|Extension(ApiKey(key))| key).unwrap_or_default()
+}
+
+pub async fn create_webhook(
+    State(webhooks): State<WebhookRegistry>,
+    key: Option<Extension<ApiKey>>,
+    FilterJson(body): FilterJson<WebhookRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let url = reqwest::Url::parse(&body.url)
+        .map_err(|_| ApiError::bad_request("url must be an absolute http(s) URL"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(ApiError::bad_request("url must be an absolute http(s) URL"));
+    }
+    if !is_public_host(&url).await {
+        return Err(ApiError::bad_request(
+            "url must resolve to a public address",
+        ));
+    }
+    if body.secret.is_empty() {
+        return Err(ApiError::bad_request("secret must not be empty"));
+    }
+    let owner = owner(key);
+    if webhooks.count_for(&owner) >= MAX_WEBHOOKS_PER_KEY {
+        return Err(ApiError::bad_request(format!(
+            "at most {} webhooks can be registered per API key",
+            MAX_WEBHOOKS_PER_KEY
+        )));
+    }
+
+    let id = webhooks.register(&owner, url.to_string(), body.secret, body.filters);
+    Ok(Json(json!({ "id": id, "url": url.to_string() })))
+}
+
+// Another key's hook is reported as not found rather than forbidden, so ids can't be probed
+pub async fn delete_webhook(
+    State(webhooks): State<WebhookRegistry>,
+    key: Option<Extension<ApiKey>>,
+    ApiPath(id): ApiPath<String>,
+) -> Result<StatusCode, ApiError> {
+    if webhooks.remove(&owner(key), &id) {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::not_found("webhook not found"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::Body,
+        http::Request,
+        routing::{delete, post},
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn app(webhooks: WebhookRegistry) -> Router {
+        Router::new()
+            .route("/webhooks", post(create_webhook))
+            .route("/webhooks/{id}", delete(delete_webhook))
+            .with_state(webhooks)
+    }
+
+    async fn call(app: Router, request: Request<Body>) -> (StatusCode, serde_json::Value) {
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or_default())
+    }
+
+    fn create(url: &str) -> Request<Body> {
+        let body = json!({
+            "url": url,
+            "secret": "secret",
+            "filters": {
+                "factories": { "pumpFun": false, "pumpSwap": false },
+                "searchKeywords": [],
+                "excludeKeywords": [],
+                "twitter": false,
+                "website": false,
+                "telegram": false,
+                "atLeastOneSocial": false,
+            },
+        });
+        Request::post("/webhooks")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    fn with_key(mut request: Request<Body>, key: &str) -> Request<Body> {
+        request.extensions_mut().insert(ApiKey(key.to_string()));
+        request
+    }
+
+    #[tokio::test]
+    async fn internal_urls_are_rejected() {
+        for url in [
+            "http://127.0.0.1/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://10.0.0.5:8080/hook",
+            "http://[::1]/hook",
+        ] {
+            let (status, body) = call(app(WebhookRegistry::default()), create(url)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{url}");
+            assert_eq!(body["error"]["code"], "bad_request", "{url}");
+        }
+    }
+
+    #[tokio::test]
+    async fn invalid_filters_are_422() {
+        let request = Request::post("/webhooks")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({ "url": "https://1.1.1.1/hook", "secret": "s", "filters": {} }).to_string(),
+            ))
+            .unwrap();
+        let (status, body) = call(app(WebhookRegistry::default()), request).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"]["code"], "invalid_filter");
+    }
+
+    #[tokio::test]
+    async fn only_the_registering_key_can_delete_a_hook() {
+        let webhooks = WebhookRegistry::default();
+        let (status, body) = call(
+            app(webhooks.clone()),
+            with_key(create("https://1.1.1.1/hook"), "key-1"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let id = body["id"].as_str().unwrap().to_string();
+        assert_eq!(webhooks.count_for("key-1"), 1);
+        assert_eq!(webhooks.count_for("key-2"), 0);
+
+        let remove = |key: &str| {
+            let request = Request::delete(format!("/webhooks/{id}"))
+                .body(Body::empty())
+                .unwrap();
+            with_key(request, key)
+        };
+        let (status, _) = call(app(webhooks.clone()), remove("key-2")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = call(app(webhooks.clone()), remove("key-1")).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert_eq!(webhooks.count_for("key-1"), 0);
+    }
+}
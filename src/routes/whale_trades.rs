@@ -0,0 +1,50 @@
+use axum::{Json, extract::State};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    routes::{error::ApiError, extractors::ApiQuery},
+    services::clickhouse::ClickhouseService,
+};
+
+const MAX_WHALE_TRADES: u64 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct WhaleTradesParams {
+    #[serde(default = "default_min_sol")]
+    min_sol: f64,
+    #[serde(default = "default_limit")]
+    limit: u64,
+}
+
+fn default_min_sol() -> f64 {
+    10.0
+}
+
+fn default_limit() -> u64 {
+    50
+}
+
+pub async fn get_whale_trades(
+    ApiQuery(params): ApiQuery<WhaleTradesParams>,
+    State(db): State<ClickhouseService>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if params.min_sol.is_nan() || params.min_sol <= 0.0 {
+        return Err(ApiError::bad_request("min_sol must be greater than 0"));
+    }
+    if params.limit == 0 || params.limit > MAX_WHALE_TRADES {
+        return Err(ApiError::bad_request(format!(
+            "limit must be between 1 and {}",
+            MAX_WHALE_TRADES
+        )));
+    }
+
+    match db.get_whale_trades(params.min_sol, params.limit).await {
+        Ok(trades) => Ok(Json(json!(trades))),
+        Err(e) => {
+            error!("Error getting whale trades: {}", e);
+            Err(e.into())
+        }
+    }
+}
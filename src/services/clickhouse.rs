@@ -1,9 +1,12 @@
 use clickhouse::Compression;
 use serde_json::Value;
 use tokio::io::AsyncBufReadExt;
+use tokio::sync::OnceCell;
 
 use std::any::Any;
-use std::time::{Duration, UNIX_EPOCH};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant, UNIX_EPOCH};
 
 use chrono::{DateTime, Utc};
 use fixnum::ops::{CheckedAdd, CheckedMul, RoundMode};
@@ -11,26 +14,373 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 use clickhouse::sql::Identifier;
-use clickhouse::{Client, Row, error::Result};
-use tracing::{error, warn};
+use clickhouse::{Client, Row};
+use tracing::{debug, error, instrument, warn};
 
 use crate::defaults::QuoteTokenData;
-use crate::defaults::{SOL_TOKEN, USDC_TOKEN};
+use crate::defaults::{
+    DEFAULT_TOKEN_DECIMALS, DEFAULT_TOP_HOLDERS, HOLDERS_EXPORT_LIMIT, LP_BURN_ADDRESSES,
+    LP_RESERVE_TOLERANCE, SOL_TOKEN,
+};
+use crate::metrics::METRICS;
 use crate::models::account::{Account, DBTokenAccount};
-use crate::models::extra::{HolderResponse, PairInfo, TopTrader};
+use crate::models::extra::{
+    FirstBuyer, HolderResponse, PairInfo, TopTrader, TopTraderRow, TraderDetails,
+};
 use crate::models::ohlcv::OHLCV;
 use crate::models::pool::{DBPool, Pool};
-use crate::models::pool_report::PoolReport;
-use crate::models::swap::{DBSwap, Swap};
+use crate::models::pool_report::{PoolReport, PoolReportEntry};
+use crate::models::swap::{DBSwap, Swap, SwapDecimals};
 use crate::models::token::{DBToken, Token};
 use crate::models::transfer::{DbTransferSol, TransferSol};
 use crate::routes::pool_report::ReportType;
+use crate::routes::pulse::{PulseRow, TOKEN_CTE};
+use crate::services::error::{Result, ServiceError};
+use crate::services::pulse_cache::PulseCache;
+use crate::services::quote_tokens::QuoteTokenRegistry;
+use crate::types::authority::TokenAuthority;
+use crate::types::candlestick::Interval;
+use crate::types::creator::{CreatorMigrations, CreatorPool};
+use crate::types::curve::CurvePoint;
+use crate::types::dev_activity::{DevActivityEntry, DevActivityResponse};
+use crate::types::exists::PoolExists;
+use crate::types::funding::FundingTransfer;
+use crate::types::holders::{
+    HolderBucket, HolderCountPoint, HolderDistribution, HolderExportEntry, HolderList,
+};
+use crate::types::liquidity::{LiquidityLockResponse, LiquidityLockStatus};
+use crate::types::market_stats::{FactoryStat, MarketStats};
+use crate::types::pnl::{PoolPnl, WalletPnl};
+use crate::types::pulse::PulseDataResponse;
 use crate::types::token_info::{TokenInfo, TokenInfoRow};
-use crate::utils::{Decimal18, calculate_percentage};
+use crate::types::whale_trades::WhaleTrade;
+use crate::utils::{
+    PoolReserves, average_price, buy_pressure, calculate_market_cap, calculate_percentage,
+    clamp_percentage, gini_coefficient, price_change_percent, quote_decimals_factor,
+};
+
+// Base bought / sold summed as Decimal(38, 18), so net flow is taken without float error
+const BASE_EXACT_SUMS: &str = r#"CAST(coalesce(sumIf(toDecimal128(s.base_amount, 18), s.swap_type = 'BUY'), 0)
+                AS Decimal(38, 18)) AS base_bought_exact,
+            CAST(coalesce(sumIf(toDecimal128(s.base_amount, 18), s.swap_type = 'SELL'), 0)
+                AS Decimal(38, 18)) AS base_sold_exact"#;
+
+// A holder's `a.amount` of a base token in whole tokens. Decimals come from `tokens` joined
+// as `t`, else from the mint's `token_initialize_events` row joined as `ti`; with neither
+// the amount is NULL instead of being scaled by 10^0.
+const HELD_BASE_TOKENS: &str = r#"if(isNotNull(t.decimals) OR notEmpty(ti.mint_address),
+                coalesce(a.amount / pow(10, coalesce(t.decimals, ti.decimals)), 0), NULL)"#;
+
+// First BUY/SELL slot of the pool; buys landing in it count as snipes. Binds pool_address.
+const FIRST_SWAP_CTE: &str = r#"first_swap AS (
+            SELECT slot
+            FROM swaps
+            WHERE pool_address = ?
+              AND (swap_type = 'BUY' OR swap_type = 'SELL')
+            ORDER BY slot ASC
+            LIMIT 1
+        )"#;
+
+// Pool + base token columns shared by the single and batch pair-info queries. A pool whose
+// token isn't indexed yet still returns, with default token columns and metadata_pending set
+const PAIR_INFO_SELECT: &str = r#"
+        SELECT
+            pools.pool_address as pool_address,
+            pools.pool_base_address as pool_base_address,
+            pools.pool_quote_address as pool_quote_address,
+            pools.token_base_address as token_base_address,
+            pools.token_quote_address as token_quote_address,
+            pools.creator as creator,
+            pools.hash as hash,
+            pools.factory as factory,
+            pools.pre_factory as pre_factory,
+            pools.reversed as reversed,
+            COALESCE(pcu.curve_percentage, 0) as curve_percentage,
+            pools.initial_token_base_reserve as initial_token_base_reserve,
+            pools.initial_token_quote_reserve as initial_token_quote_reserve,
+            pools.slot as slot,
+            pools.metadata as metadata,
+            pools.created_at as created_at,
+            t.hash AS token_hash,
+            pools.token_base_address as mint_address,
+            COALESCE(t.name, '') as name,
+            COALESCE(t.symbol, '') as symbol,
+            t.decimals as decimals,
+            COALESCE(t.uri, '') as uri,
+            t.mint_authority as mint_authority,
+            t.supply as supply,
+            t.freeze_authority as freeze_authority,
+            t.slot AS token_slot,
+            t.image as image,
+            t.twitter as twitter,
+            t.telegram as telegram,
+            t.website as website,
+            t.discord as discord,
+            t.program_id as program_id,
+            empty(t.mint_address) as metadata_pending
+        FROM pools
+        LEFT JOIN (SELECT * FROM pool_curve_updates FINAL) pcu ON pools.pool_address = pcu.pool_address
+        LEFT JOIN tokens t ON pools.token_base_address = t.mint_address"#;
+
+// Swap prices as indexed at the start and end of a window
+#[derive(Debug, Clone, Copy)]
+struct PriceWindow {
+    open: f64,
+    close: f64,
+}
+
+// Row of the `get_pool_exists` lookup
+#[derive(Debug, serde::Deserialize, Row, Serialize)]
+pub(crate) struct ExistsRow {
+    pub(crate) factory: String,
+    pub(crate) has_trades: bool,
+}
+
+// Row of the `get_swap_decimals` lookup; `base_decimals` is `None` when unknown
+#[derive(Debug, serde::Deserialize, Row, Serialize)]
+pub(crate) struct SwapDecimalsRow {
+    pub(crate) token_quote_address: String,
+    pub(crate) reversed: bool,
+    pub(crate) base_decimals: Option<u8>,
+}
+
+// Row of the whale trade scan
+#[derive(Debug, serde::Deserialize, Row, Serialize)]
+struct WhaleTradeRow {
+    hash: String,
+    pool_address: String,
+    trader: String,
+    swap_type: String,
+    base_amount: f64,
+    quote_amount: f64,
+    price_sol: f64,
+    slot: i64,
+    #[serde(with = "clickhouse::serde::chrono::datetime")]
+    created_at: DateTime<Utc>,
+    token_address: String,
+    name: String,
+    symbol: String,
+    image: Option<String>,
+}
+
+// Quote token and orientation of one pool
+#[derive(Debug, serde::Deserialize, Row, Serialize)]
+struct PoolQuoteRow {
+    token_quote_address: String,
+    reversed: bool,
+}
+
+impl PriceWindow {
+    fn change_percent(&self, reversed: bool) -> Option<f64> {
+        let open = PoolReserves::oriented(reversed, 0.0, 0.0, self.open).price;
+        let close = PoolReserves::oriented(reversed, 0.0, 0.0, self.close).price;
+        price_change_percent(open, close)
+    }
+}
+
+// Use a struct for strict schema alignment (schema: 32 columns, struct: 32 fields)
+#[derive(Debug, serde::Deserialize, Row, Serialize)]
+struct PairInfoRow {
+    pool_address: String,
+    pool_base_address: String,
+    pool_quote_address: String,
+    token_base_address: String,
+    token_quote_address: String,
+    creator: String,
+    hash: String,
+    factory: String,
+    pre_factory: Option<String>,
+    reversed: bool,
+    curve_percentage: f32,
+    initial_token_base_reserve: f64,
+    initial_token_quote_reserve: f64,
+    slot: i64,
+    metadata: String,
+    #[serde(with = "clickhouse::serde::chrono::datetime")]
+    created_at: DateTime<Utc>,
+
+    // tokens.* fields
+    token_hash: String,
+    mint_address: String,
+    name: String,
+    symbol: String,
+    decimals: i8,
+    uri: String,
+    supply: f64,
+    token_slot: i64,
+    mint_authority: Option<String>,
+    freeze_authority: Option<String>,
+    image: Option<String>,
+    twitter: Option<String>,
+    telegram: Option<String>,
+    website: Option<String>,
+    discord: Option<String>,
+    program_id: String,
+    metadata_pending: bool,
+}
+
+impl From<PairInfoRow> for PairInfo {
+    fn from(row: PairInfoRow) -> Self {
+        PairInfo {
+            pool: DBPool {
+                pool_address: row.pool_address,
+                pool_base_address: row.pool_base_address,
+                pool_quote_address: row.pool_quote_address,
+                token_base_address: row.token_base_address,
+                token_quote_address: row.token_quote_address,
+                creator: row.creator,
+                hash: row.hash,
+                factory: row.factory,
+                pre_factory: row.pre_factory,
+                reversed: row.reversed,
+
+                initial_token_base_reserve: row.initial_token_base_reserve,
+                initial_token_quote_reserve: row.initial_token_quote_reserve,
+                slot: row.slot,
+                metadata: row.metadata.to_string(),
+                created_at: row.created_at,
+            },
+            base_token: DBToken {
+                mint_address: row.mint_address,
+                name: row.name,
+                symbol: row.symbol,
+                decimals: row.decimals,
+                uri: row.uri,
+                supply: row.supply,
+                slot: row.token_slot,
+                mint_authority: row.mint_authority,
+                freeze_authority: row.freeze_authority,
+                hash: row.token_hash,
+                image: row.image,
+                twitter: row.twitter,
+                telegram: row.telegram,
+                website: row.website,
+                discord: row.discord,
+                program_id: row.program_id,
+            },
+            metadata_pending: row.metadata_pending,
+            open_trading: None,
+            price_change_24h_percent: None,
+        }
+    }
+}
+
+// Client settings operators may want to tune per deployment; unset or unparsable
+// variables keep the defaults
+#[derive(Debug, Clone)]
+pub struct ClientSettings {
+    // CLICKHOUSE_COMPRESSION: `lz4` (default) or `none`
+    pub compression: Compression,
+    pub async_insert_max_data_size: u64,
+    pub async_insert_busy_timeout_ms: u64,
+    pub async_insert_stale_timeout_ms: u64,
+    pub max_insert_block_size: u64,
+    pub min_insert_block_size_rows: u64,
+    pub min_insert_block_size_bytes: u64,
+}
+
+impl Default for ClientSettings {
+    fn default() -> Self {
+        Self {
+            compression: Compression::Lz4,
+            async_insert_max_data_size: 104_857_600, // 100MB
+            async_insert_busy_timeout_ms: 400,
+            async_insert_stale_timeout_ms: 0,
+            max_insert_block_size: 1_048_576,
+            min_insert_block_size_rows: 1_048_576,
+            min_insert_block_size_bytes: 268_435_456,
+        }
+    }
+}
+
+impl ClientSettings {
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    // Reads each setting through `var`, so the parsing can be checked without touching
+    // the process environment
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let env_u64 = |name: &str, default: u64| -> u64 {
+            var(name).and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+
+        let defaults = Self::default();
+        let compression = match var("CLICKHOUSE_COMPRESSION") {
+            Some(v) if v.eq_ignore_ascii_case("none") => Compression::None,
+            Some(v) if !v.eq_ignore_ascii_case("lz4") => {
+                warn!("Unknown CLICKHOUSE_COMPRESSION {:?}, using lz4", v);
+                defaults.compression
+            }
+            _ => defaults.compression,
+        };
+
+        Self {
+            compression,
+            async_insert_max_data_size: env_u64(
+                "CLICKHOUSE_ASYNC_INSERT_MAX_DATA_SIZE",
+                defaults.async_insert_max_data_size,
+            ),
+            async_insert_busy_timeout_ms: env_u64(
+                "CLICKHOUSE_ASYNC_INSERT_BUSY_TIMEOUT_MS",
+                defaults.async_insert_busy_timeout_ms,
+            ),
+            async_insert_stale_timeout_ms: env_u64(
+                "CLICKHOUSE_ASYNC_INSERT_STALE_TIMEOUT_MS",
+                defaults.async_insert_stale_timeout_ms,
+            ),
+            max_insert_block_size: env_u64(
+                "CLICKHOUSE_MAX_INSERT_BLOCK_SIZE",
+                defaults.max_insert_block_size,
+            ),
+            min_insert_block_size_rows: env_u64(
+                "CLICKHOUSE_MIN_INSERT_BLOCK_SIZE_ROWS",
+                defaults.min_insert_block_size_rows,
+            ),
+            min_insert_block_size_bytes: env_u64(
+                "CLICKHOUSE_MIN_INSERT_BLOCK_SIZE_BYTES",
+                defaults.min_insert_block_size_bytes,
+            ),
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct ClickhouseService {
     pub client: Client,
+    pub query_timeout: Duration,
+    // Intervals with a candles_{interval} table. Empty until discovery first succeeds.
+    pub candle_intervals: Arc<OnceCell<Vec<Interval>>>,
+    // Serve intervals without a table by aggregating swaps on the fly
+    pub candles_swap_fallback: bool,
+    pub quote_tokens: Arc<QuoteTokenRegistry>,
+    // Extra attempts `with_retry` makes after a transient error
+    pub read_retries: u32,
+    pub pulse_cache: PulseCache,
+    // Whether the `pool_holder_counts` MV existed at startup
+    pub holder_counts_mv: bool,
+}
+
+// Backoff before the first read retry; doubles on each further one
+const READ_RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+// Connection-level failures that are worth repeating an idempotent read for. Query
+// errors such as bad SQL come back as BadResponse and would just fail again.
+fn is_transient(error: &clickhouse::error::Error) -> bool {
+    matches!(
+        error,
+        clickhouse::error::Error::Network(_) | clickhouse::error::Error::TimedOut
+    )
+}
+
+// Up to +50% of `delay`, so retrying requests don't all land at once
+fn with_jitter(delay: Duration) -> Duration {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    let max = (delay.as_millis() as u64 / 2).max(1);
+    delay + Duration::from_millis(nanos % max)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -56,14 +406,35 @@ impl ClickhouseService {
         let password = std::env::var("CLICKHOUSE_PASSWORD").unwrap_or_else(|_| "".to_string());
         let database =
             std::env::var("CLICKHOUSE_DATABASE").unwrap_or_else(|_| "default".to_string());
-
+        let query_timeout_ms = std::env::var("CLICKHOUSE_QUERY_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(10_000);
+        let candles_swap_fallback = std::env::var("CANDLES_SWAP_FALLBACK")
+            .map(|v| v != "0" && !v.eq_ignore_ascii_case("false"))
+            .unwrap_or(true);
+        let read_retries = std::env::var("CLICKHOUSE_READ_RETRIES")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(2);
+
+        let settings = ClientSettings::from_env();
         let client = Client::default()
             .with_url(&url)
             .with_option("async_insert", "1")
             .with_option("wait_for_async_insert", "0")
-            .with_option("async_insert_max_data_size", "104857600") // 100MB (keep this)
-            .with_option("async_insert_busy_timeout_ms", "400") // Increase to 2 seconds
-            .with_option("async_insert_stale_timeout_ms", "0") // Set to 1 second
+            .with_option(
+                "async_insert_max_data_size",
+                settings.async_insert_max_data_size.to_string(),
+            )
+            .with_option(
+                "async_insert_busy_timeout_ms",
+                settings.async_insert_busy_timeout_ms.to_string(),
+            )
+            .with_option(
+                "async_insert_stale_timeout_ms",
+                settings.async_insert_stale_timeout_ms.to_string(),
+            )
             // Memory optimization
             .with_option("max_memory_usage", "0")
             .with_option("max_memory_usage_for_user", "0")
@@ -79,18 +450,235 @@ impl ClickhouseService {
             .with_option("input_format_binary_read_json_as_string", "1")
             .with_option("output_format_binary_write_json_as_string", "1")
             // Performance tuning
-            .with_option("max_insert_block_size", "1048576")
-            .with_option("min_insert_block_size_rows", "1048576")
-            .with_option("min_insert_block_size_bytes", "268435456")
-            .with_compression(Compression::Lz4)
+            .with_option(
+                "max_insert_block_size",
+                settings.max_insert_block_size.to_string(),
+            )
+            .with_option(
+                "min_insert_block_size_rows",
+                settings.min_insert_block_size_rows.to_string(),
+            )
+            .with_option(
+                "min_insert_block_size_bytes",
+                settings.min_insert_block_size_bytes.to_string(),
+            )
+            .with_compression(settings.compression)
             .with_user(&user)
             .with_password(&password)
             .with_database(&database);
 
-        let service = Self { client };
+        let mut service = Self {
+            client,
+            query_timeout: Duration::from_millis(query_timeout_ms),
+            candle_intervals: Arc::new(OnceCell::new()),
+            candles_swap_fallback,
+            quote_tokens: Arc::new(QuoteTokenRegistry::from_env()),
+            read_retries,
+            pulse_cache: PulseCache::from_env(),
+            holder_counts_mv: false,
+        };
+
+        // Failing here only delays discovery to the first candle request
+        service.known_candle_intervals().await;
+        // Checked once here rather than on every `/pulse`; without it pulse counts exactly
+        service.holder_counts_mv = match service.table_exists("pool_holder_counts").await {
+            Ok(exists) => exists,
+            Err(e) => {
+                warn!("Could not check for pool_holder_counts: {}", e);
+                false
+            }
+        };
 
         service
     }
+
+    async fn discover_candle_intervals(&self) -> Result<Vec<Interval>> {
+        let query = r#"
+            SELECT name
+            FROM system.tables
+            WHERE database = currentDatabase() AND startsWith(name, 'candles_')
+        "#;
+
+        let tables = self
+            .with_timeout(self.client.query(query).fetch_all::<String>())
+            .await?;
+
+        Ok(tables
+            .iter()
+            .filter_map(|name| {
+                Interval::ALL
+                    .iter()
+                    .find(|interval| interval.candle_table() == name)
+                    .cloned()
+            })
+            .collect())
+    }
+
+    // Intervals with a candle table, or None while that can't be checked. A failed
+    // discovery is retried on the next call rather than guessed at.
+    async fn known_candle_intervals(&self) -> Option<&[Interval]> {
+        match self
+            .candle_intervals
+            .get_or_try_init(|| self.discover_candle_intervals())
+            .await
+        {
+            Ok(intervals) => Some(intervals),
+            Err(e) => {
+                warn!("Could not discover candle tables: {}", e);
+                None
+            }
+        }
+    }
+
+    // Whether `interval` is read from its candle table. While the tables are unknown,
+    // candles come from swaps when the fallback is on, and from the table otherwise.
+    async fn has_candle_table(&self, interval: &Interval) -> bool {
+        match self.known_candle_intervals().await {
+            Some(known) => known.contains(interval),
+            None => !self.candles_swap_fallback,
+        }
+    }
+
+    pub async fn supports_candle_interval(&self, interval: &Interval) -> bool {
+        self.candles_swap_fallback || self.has_candle_table(interval).await
+    }
+
+    // Intervals clients can request, in Interval::ALL order
+    pub async fn supported_candle_intervals(&self) -> Vec<String> {
+        let mut supported = Vec::new();
+        for interval in Interval::ALL {
+            if self.supports_candle_interval(&interval).await {
+                supported.push(interval.to_string());
+            }
+        }
+        supported
+    }
+
+    // Bounds a ClickHouse fetch by `query_timeout` so a runaway query can't hang a request
+    pub async fn with_timeout<T, F>(&self, fetch: F) -> Result<T>
+    where
+        F: std::future::Future<Output = clickhouse::error::Result<T>>,
+    {
+        self.with_deadline(self.query_timeout, fetch).await
+    }
+
+    // `with_timeout` for idempotent reads: repeats the fetch `fetch` builds after a
+    // transient connection error. Hitting `query_timeout` is not retried.
+    pub async fn with_retry<T, F, Fut>(&self, mut fetch: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = clickhouse::error::Result<T>>,
+    {
+        let mut delay = READ_RETRY_BACKOFF;
+        let mut attempt = 0;
+        loop {
+            match self.with_timeout(fetch()).await {
+                Err(ServiceError::Db(e)) if attempt < self.read_retries && is_transient(&e) => {
+                    attempt += 1;
+                    let wait = with_jitter(delay);
+                    warn!(
+                        "Transient ClickHouse error, retry {}/{} in {:?}: {}",
+                        attempt, self.read_retries, wait, e
+                    );
+                    tokio::time::sleep(wait).await;
+                    delay *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    pub async fn with_deadline<T, F>(&self, deadline: Duration, fetch: F) -> Result<T>
+    where
+        F: std::future::Future<Output = clickhouse::error::Result<T>>,
+    {
+        let started = Instant::now();
+        let result = match tokio::time::timeout(deadline, fetch).await {
+            Ok(result) => result.map_err(ServiceError::Db),
+            Err(_) => Err(ServiceError::Timeout),
+        };
+        let outcome = match &result {
+            Ok(_) => "ok",
+            Err(ServiceError::Timeout) => "timeout",
+            Err(_) => "error",
+        };
+        METRICS.observe_clickhouse(outcome, started.elapsed());
+        result
+    }
+
+    pub async fn ping(&self) -> Result<()> {
+        self.with_timeout(self.client.query("SELECT 1").fetch_one::<u8>())
+            .await?;
+
+        Ok(())
+    }
+
+    // Runs a trimmed pulse query (one recent pool through every table/view the
+    // pulse pipeline joins) so readiness fails when the schema or MVs are broken
+    pub async fn deep_ping(&self, deadline: Duration) -> Result<()> {
+        let query = r#"
+WITH recent_pool AS (
+  SELECT pool_address, token_base_address, creator
+  FROM pools
+  WHERE created_at >= now() - INTERVAL 24 HOUR
+  ORDER BY created_at DESC
+  LIMIT 1
+)
+SELECT count()
+FROM recent_pool r
+LEFT JOIN (
+  SELECT pool_address, argMax(curve_percentage, updated_at) AS curve_percentage
+  FROM pool_curve_updates FINAL
+  WHERE pool_address IN (SELECT pool_address FROM recent_pool)
+  GROUP BY pool_address
+) pcu ON pcu.pool_address = r.pool_address
+LEFT JOIN tokens t ON t.mint_address = r.token_base_address
+LEFT JOIN (
+  SELECT pool_address, argMax(price_sol, created_at) AS price_sol
+  FROM swaps
+  WHERE pool_address IN (SELECT pool_address FROM recent_pool)
+  GROUP BY pool_address
+) ls ON ls.pool_address = r.pool_address
+LEFT JOIN (
+  SELECT mint, count(DISTINCT owner) AS num_holders
+  FROM accounts
+  WHERE mint IN (SELECT token_base_address FROM recent_pool)
+  GROUP BY mint
+) h ON h.mint = r.token_base_address
+LEFT JOIN (
+  SELECT pool_address, sum(buy_volume + sell_volume) AS volume_sol
+  FROM pool_report_5m
+  WHERE pool_address IN (SELECT pool_address FROM recent_pool)
+  GROUP BY pool_address
+) v ON v.pool_address = r.pool_address
+LEFT JOIN (
+  SELECT destination, min(created_at) AS funded_at
+  FROM transfer_sol
+  WHERE destination IN (SELECT creator FROM recent_pool)
+  GROUP BY destination
+) df ON df.destination = r.creator
+        "#;
+
+        self.with_deadline(deadline, self.client.query(query).fetch_one::<u64>())
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn table_exists(&self, table: &str) -> Result<bool> {
+        let query = r#"
+            SELECT count() > 0
+            FROM system.tables
+            WHERE database = currentDatabase() AND name = ?
+        "#;
+
+        let exists = self
+            .with_timeout(self.client.query(query).bind(table).fetch_one::<bool>())
+            .await?;
+
+        Ok(exists)
+    }
+
     pub async fn search_pools(&self, pool_address: String) -> Result<Vec<DBPool>> {
         let query = r#"
         SELECT
@@ -119,18 +707,12 @@ impl ClickhouseService {
     "#;
 
         let pools: Vec<DBPool> = self
-            .client
-            .query(query)
-            .bind(pool_address)
-            .fetch_all()
+            .with_timeout(self.client.query(query).bind(pool_address).fetch_all())
             .await?;
 
         Ok(pools)
     }
-    pub async fn search_tokens(
-        &self,
-        search: String,
-    ) -> Result<Vec<Token>, clickhouse::error::Error> {
+    pub async fn search_tokens(&self, search: String) -> Result<Vec<Token>> {
         let query = r#"
             SELECT
                 t.hash           AS hash,
@@ -147,12 +729,23 @@ impl ClickhouseService {
                 t.twitter        AS twitter,
                 t.telegram       AS telegram,
                 t.website        AS website,
+                t.discord        AS discord,
                 t.program_id     AS program_id
             FROM tokens t
             WHERE
                 lower(COALESCE(t.name,   ''))   LIKE lower({search:String}) OR
                 lower(COALESCE(t.symbol, ''))   LIKE lower({search:String})
-            ORDER BY COALESCE(t.name, '') DESC
+            -- exact symbol, exact name, symbol prefix, name prefix, then any substring
+            ORDER BY
+                multiIf(
+                    lower(COALESCE(t.symbol, '')) = lower({term:String}), 0,
+                    lower(COALESCE(t.name, '')) = lower({term:String}), 1,
+                    startsWith(lower(COALESCE(t.symbol, '')), lower({term:String})), 2,
+                    startsWith(lower(COALESCE(t.name, '')), lower({term:String})), 3,
+                    4
+                ) ASC,
+                length(COALESCE(t.symbol, '')) ASC,
+                COALESCE(t.name, '') ASC
             LIMIT 10;
         "#;
 
@@ -160,10 +753,13 @@ impl ClickhouseService {
         let search_pattern = format!("%{}%", search);
 
         let tokens: Vec<DBToken> = self
-            .client
-            .query(query)
-            .param("search", &search_pattern) // used for both occurrences
-            .fetch_all()
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .param("search", &search_pattern) // used for both occurrences
+                    .param("term", &search)
+                    .fetch_all(),
+            )
             .await?;
 
         let response_tokens = tokens
@@ -176,7 +772,7 @@ impl ClickhouseService {
     pub async fn get_pool_and_token_data(
         &self,
         address: String,
-    ) -> Result<Option<PoolAndTokenData>, clickhouse::error::Error> {
+    ) -> Result<Option<PoolAndTokenData>> {
         // Alternative strategy: perform multiple simpler queries and combine results in Rust
 
         // 1. Try to find the pool by pool_address or token_base_address
@@ -203,11 +799,13 @@ impl ClickhouseService {
         "#;
 
         let mut pools: Vec<DBPool> = self
-            .client
-            .query(pool_query)
-            .bind(&address)
-            .bind(&address)
-            .fetch_all()
+            .with_timeout(
+                self.client
+                    .query(pool_query)
+                    .bind(&address)
+                    .bind(&address)
+                    .fetch_all(),
+            )
             .await?;
 
         let pool = match pools.pop() {
@@ -232,6 +830,7 @@ impl ClickhouseService {
             twitter,
             telegram,
             website,
+            discord,
             program_id
         FROM tokens
         WHERE mint_address = ?
@@ -239,10 +838,12 @@ impl ClickhouseService {
     "#;
 
         let token: DBToken = self
-            .client
-            .query(token_query)
-            .bind(&pool.token_base_address)
-            .fetch_one()
+            .with_timeout(
+                self.client
+                    .query(token_query)
+                    .bind(&pool.token_base_address)
+                    .fetch_one(),
+            )
             .await?;
 
         // 3. Get the latest swap info for this pool
@@ -255,10 +856,12 @@ impl ClickhouseService {
         "#;
 
         let swap_row = self
-            .client
-            .query(swap_query)
-            .bind(&pool.pool_address)
-            .fetch_one::<DBSwap>()
+            .with_timeout(
+                self.client
+                    .query(swap_query)
+                    .bind(&pool.pool_address)
+                    .fetch_one::<DBSwap>(),
+            )
             .await?;
 
         // 4. Get the latest 24h volume for this pool
@@ -274,7 +877,9 @@ impl ClickhouseService {
     countIf(swap_type = 'SELL') AS sell_count,
     uniq(creator) AS unique_traders,
     uniqIf(creator, swap_type = 'BUY') AS unique_buyers,
-    uniqIf(creator, swap_type = 'SELL') AS unique_sellers
+    uniqIf(creator, swap_type = 'SELL') AS unique_sellers,
+    argMin(price_sol, created_at) AS open_price,
+    argMax(price_sol, created_at) AS close_price
 FROM swaps
 WHERE pool_address = ?
 GROUP BY
@@ -283,48 +888,42 @@ GROUP BY
         "#;
 
         let volume_row: PoolReport = self
-            .client
-            .query(volume_query)
-            .bind(&pool.pool_address)
-            .fetch_one()
+            .with_timeout(
+                self.client
+                    .query(volume_query)
+                    .bind(&pool.pool_address)
+                    .fetch_one(),
+            )
             .await?;
 
         // 5. Build quote_token using the pool's token_quote_address
-        let quote_token = if pool.token_quote_address == SOL_TOKEN.address {
-            QuoteTokenData {
-                address: SOL_TOKEN.address.to_string(),
-                name: SOL_TOKEN.name.to_string(),
-                symbol: SOL_TOKEN.symbol.to_string(),
-                decimals: SOL_TOKEN.decimals,
-                logo: SOL_TOKEN.logo.to_string(),
-            }
-        } else if pool.token_quote_address == USDC_TOKEN.address {
-            QuoteTokenData {
-                address: USDC_TOKEN.address.to_string(),
-                name: USDC_TOKEN.name.to_string(),
-                symbol: USDC_TOKEN.symbol.to_string(),
-                decimals: USDC_TOKEN.decimals,
-                logo: USDC_TOKEN.logo.to_string(),
-            }
-        } else {
-            // You may want to handle this more gracefully in production
-            panic!("Quote token not found");
-        };
+        let quote_token = self.quote_tokens.resolve(&pool.token_quote_address);
+        let reserves = PoolReserves::oriented(
+            pool.reversed,
+            swap_row.base_reserve,
+            swap_row.quote_reserve,
+            swap_row.price_sol,
+        );
+        let quote_factor = quote_decimals_factor(quote_token.decimals);
+        let quote_liquidity = reserves.quote * quote_factor;
+        let marketcap_sol =
+            calculate_market_cap(reserves.price, token.supply, quote_token.decimals);
 
         Ok(Some(PoolAndTokenData {
             pool_address: pool.pool_address.clone(),
-            base_liquidity: swap_row.base_reserve,
-            quote_liquidity: swap_row.quote_reserve,
-            marketcap_sol: swap_row.price_sol * token.supply,
+            base_liquidity: reserves.base,
+            quote_liquidity,
+            marketcap_sol,
             factory: pool.factory.clone(),
             pre_factory: pool.pre_factory.clone(),
 
-            volume_quote: volume_row.buy_volume + volume_row.sell_volume,
+            volume_quote: (volume_row.buy_volume + volume_row.sell_volume) * quote_factor,
             base_token: token,
             quote_token,
         }))
     }
 
+    #[instrument(skip_all, fields(pool_address = %pool_address))]
     pub async fn get_last_transaction(&self, pool_address: String) -> Result<Option<DBSwap>> {
         // Try to be explicit with columns and types, as SELECT * can cause issues if struct and table don't match
 
@@ -349,29 +948,39 @@ GROUP BY
         "#;
 
         match self
-            .client
-            .query(query)
-            .bind(&pool_address)
-            .fetch_optional::<DBSwap>() // Use DBSwap to deserialize the row into your struct
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(&pool_address)
+                    .fetch_optional::<DBSwap>(), // Use DBSwap to deserialize the row into your struct
+            )
             .await
         {
             Ok(result) => Ok(result),
             Err(e) => {
-                println!("Error getting last transaction: {}", e);
+                error!("Error getting last transaction: {}", e);
                 Err(e)
             }
         }
     }
 
+    #[instrument(skip_all, fields(pool_address = %pool_address))]
     pub async fn get_candlestick(
         &self,
         pool_address: String,
-        interval: String,
+        interval: &Interval,
         start_time: i64,
         end_time: i64,
         limit: i32,
-    ) -> Result<Vec<serde_json::Value>, clickhouse::error::Error> {
-        let table_name = format!("candles_{}", interval);
+    ) -> Result<Vec<serde_json::Value>> {
+        let table_name = interval.candle_table();
+        if !self.has_candle_table(interval).await {
+            warn!("{} does not exist, building candles from swaps", table_name);
+            return self
+                .get_candlestick_from_swaps(pool_address, interval, start_time, end_time, limit)
+                .await;
+        }
+
         let query = format!(
             "SELECT timestamp, open, high, low, close, volume_base, volume_quote, trades \
              FROM {} WHERE pool_address = ? AND timestamp >= ? AND timestamp <= ? \
@@ -379,40 +988,95 @@ GROUP BY
             table_name
         );
 
-        let mut lines = self
+        let lines = self
             .client
             .query(&query)
             .bind(&pool_address)
             .bind(start_time)
             .bind(end_time)
             .bind(limit)
-            .fetch_bytes("JSONEachRow")
-            .unwrap()
+            .fetch_bytes("JSONEachRow")?
             .lines();
 
-        let mut values = vec![];
-        while let Some(line) = lines.next_line().await.unwrap() {
-            let value: serde_json::Value = serde_json::de::from_str(&line).unwrap();
-            // println!("JSONEachRow value: {value}");
-            values.push(value);
-        }
-
-        Ok(values)
+        // Sending the query, not just reading it, happens inside read_json_rows' timeout
+        self.read_json_rows(lines).await
     }
 
-    pub async fn get_top_traders(
+    // Same shape as the candles_* tables, aggregated from raw swaps. Much slower, only
+    // used for intervals that have no materialized candle table.
+    async fn get_candlestick_from_swaps(
         &self,
         pool_address: String,
-    ) -> Result<Vec<TopTrader>, clickhouse::error::Error> {
+        interval: &Interval,
+        start_time: i64,
+        end_time: i64,
+        limit: i32,
+    ) -> Result<Vec<serde_json::Value>> {
         let query = r#"
-        WITH first_swap AS (
-            SELECT slot
+            SELECT
+                toStartOfInterval(created_at, toIntervalSecond(?)) AS timestamp,
+                argMin(price_sol, (slot, created_at)) AS open,
+                max(price_sol) AS high,
+                min(price_sol) AS low,
+                argMax(price_sol, (slot, created_at)) AS close,
+                sum(abs(base_amount)) AS volume_base,
+                sum(abs(quote_amount)) AS volume_quote,
+                count() AS trades
             FROM swaps
             WHERE pool_address = ?
-              AND (swap_type = 'BUY' OR swap_type = 'SELL')
-            ORDER BY slot ASC
-            LIMIT 1
-        ),
+              AND swap_type IN ('BUY', 'SELL')
+              AND created_at >= toDateTime(?)
+              AND created_at <= toDateTime(?)
+            GROUP BY timestamp
+            ORDER BY timestamp ASC
+            LIMIT ?
+        "#;
+
+        let lines = self
+            .client
+            .query(query)
+            .bind(interval.seconds())
+            .bind(&pool_address)
+            .bind(start_time)
+            .bind(end_time)
+            .bind(limit)
+            .fetch_bytes("JSONEachRow")?
+            .lines();
+
+        self.read_json_rows(lines).await
+    }
+
+    // Reads a JSONEachRow response under `query_timeout`. The request is only sent once the
+    // cursor is polled, so a failing query (a missing table, say) surfaces here as Db.
+    async fn read_json_rows<R>(
+        &self,
+        mut lines: tokio::io::Lines<R>,
+    ) -> Result<Vec<serde_json::Value>>
+    where
+        R: tokio::io::AsyncBufRead + Unpin,
+    {
+        self.with_timeout(async move {
+            let mut values = vec![];
+            while let Some(line) = lines
+                .next_line()
+                .await
+                .map_err(|e| clickhouse::error::Error::Network(Box::new(e)))?
+            {
+                let value: serde_json::Value = serde_json::de::from_str(&line).map_err(|e| {
+                    clickhouse::error::Error::Custom(format!("invalid JSONEachRow row: {}", e))
+                })?;
+                values.push(value);
+            }
+            Ok(values)
+        })
+        .await
+    }
+
+    #[instrument(skip_all, fields(pool_address = %pool_address))]
+    pub async fn get_top_traders(&self, pool_address: String) -> Result<Vec<TopTrader>> {
+        let query = format!(
+            r#"
+        WITH {FIRST_SWAP_CTE},
         pool_info AS (
             SELECT token_base_address
             FROM pools
@@ -423,9 +1087,10 @@ GROUP BY
             max(s.slot = f.slot) as is_sniper,
             coalesce(sumIf(s.base_amount, s.swap_type = 'BUY'), 0) as base_bought,
             coalesce(sumIf(s.base_amount, s.swap_type = 'SELL'), 0) as base_sold,
+            {BASE_EXACT_SUMS},
             coalesce(sumIf(s.quote_amount, s.swap_type = 'BUY'), 0) as quote_bought,
             coalesce(sumIf(s.quote_amount, s.swap_type = 'SELL'), 0) as quote_sold,
-            coalesce(max(a.amount) / pow(10, COALESCE(max(t.decimals), 0)), 0) as holding_base_token
+            any({HELD_BASE_TOKENS}) as holding_base_token
         FROM swaps s
         CROSS JOIN first_swap f
         CROSS JOIN pool_info pi
@@ -434,199 +1099,503 @@ GROUP BY
             FROM accounts FINAL
             WHERE amount > 0
         ) a ON a.owner = s.creator AND a.mint = pi.token_base_address
-        LEFT JOIN token_initialize_events t ON t.mint_address = pi.token_base_address
+        LEFT JOIN tokens t ON t.mint_address = pi.token_base_address
+        LEFT JOIN token_initialize_events ti ON ti.mint_address = pi.token_base_address
         WHERE s.pool_address = ?
           AND (s.swap_type = 'BUY' OR s.swap_type = 'SELL')
         GROUP BY s.creator
         ORDER BY base_bought DESC
         LIMIT 20
-        "#;
+        "#
+        );
 
-        let traders: Vec<TopTrader> = self
-            .client
-            .query(query)
-            .bind(&pool_address) // For first_swap CTE
-            .bind(&pool_address) // For pool_info CTE
-            .bind(&pool_address) // For main WHERE clause
-            .fetch_all()
+        let traders = self
+            .with_timeout(
+                self.client
+                    .query(&query)
+                    .bind(&pool_address) // For first_swap CTE
+                    .bind(&pool_address) // For pool_info CTE
+                    .bind(&pool_address) // For main WHERE clause
+                    .fetch_all::<TopTraderRow>(),
+            )
+            .await?;
+
+        Ok(traders.into_iter().map(TopTrader::from).collect())
+    }
+
+    #[instrument(skip_all, fields(pool_address = %pool_address))]
+    pub async fn get_first_buyers(
+        &self,
+        pool_address: String,
+        limit: u64,
+    ) -> Result<Vec<FirstBuyer>> {
+        let query = format!(
+            r#"
+        WITH {FIRST_SWAP_CTE},
+        pool_info AS (
+            SELECT token_base_address
+            FROM pools
+            WHERE pool_address = ?
+        ),
+        first_buys AS (
+            SELECT
+                creator,
+                min(slot) AS slot,
+                min(created_at) AS first_buy_at,
+                argMin(base_amount, (slot, created_at)) AS base_amount
+            FROM swaps
+            WHERE pool_address = ?
+              AND swap_type = 'BUY'
+            GROUP BY creator
+        )
+        SELECT
+            b.creator AS creator,
+            b.slot AS slot,
+            b.base_amount AS base_amount,
+            {HELD_BASE_TOKENS} AS remaining,
+            b.slot = f.slot AS is_sniper
+        FROM first_buys b
+        CROSS JOIN first_swap f
+        CROSS JOIN pool_info pi
+        LEFT JOIN (
+            SELECT owner, mint, amount
+            FROM accounts FINAL
+            WHERE amount > 0
+        ) a ON a.owner = b.creator AND a.mint = pi.token_base_address
+        LEFT JOIN tokens t ON t.mint_address = pi.token_base_address
+        LEFT JOIN token_initialize_events ti ON ti.mint_address = pi.token_base_address
+        ORDER BY b.slot ASC, b.first_buy_at ASC, b.creator ASC
+        LIMIT ?
+        "#
+        );
+
+        let buyers: Vec<FirstBuyer> = self
+            .with_timeout(
+                self.client
+                    .query(&query)
+                    .bind(&pool_address) // For first_swap CTE
+                    .bind(&pool_address) // For pool_info CTE
+                    .bind(&pool_address) // For first_buys CTE
+                    .bind(limit)
+                    .fetch_all(),
+            )
             .await?;
 
-        Ok(traders)
+        Ok(buyers)
     }
 
+    #[instrument(skip_all, fields(pool_address = %pool_address))]
     pub async fn get_pair_info(&self, pool_address: String) -> Result<PairInfo> {
         warn!("Getting pair info for pool address: {}", pool_address);
+        let query = format!(
+            r#"{PAIR_INFO_SELECT}
+        WHERE pools.pool_address = ? OR pools.token_base_address = ?
+        LIMIT 1
+    "#
+        );
+
+        let rows: Vec<PairInfoRow> = self
+            .with_retry(|| {
+                self.client
+                    .query(&query)
+                    .bind(&pool_address)
+                    .bind(&pool_address)
+                    .fetch_all()
+            })
+            .await?;
+
+        debug!("rows: {:?}", rows);
+
+        let mut pair_info = rows
+            .into_iter()
+            .next()
+            .map(PairInfo::from)
+            .ok_or(ServiceError::NotFound)?;
+
+        let address = pair_info.pool.pool_address.clone();
+        pair_info.open_trading = self
+            .get_open_trading(std::slice::from_ref(&address))
+            .await?
+            .into_values()
+            .next();
+        pair_info.price_change_24h_percent = self
+            .get_price_window_24h(&[address])
+            .await?
+            .into_values()
+            .next()
+            .and_then(|window| window.change_percent(pair_info.pool.reversed));
+
+        Ok(pair_info)
+    }
+
+    // Pair info for several pools in one query; unknown addresses are simply absent
+    pub async fn get_pair_info_batch(
+        &self,
+        pool_addresses: &[String],
+    ) -> Result<HashMap<String, PairInfo>> {
+        if pool_addresses.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = format!(
+            r#"{PAIR_INFO_SELECT}
+        WHERE pools.pool_address IN ?
+        LIMIT 1 BY pools.pool_address
+    "#
+        );
+
+        let rows: Vec<PairInfoRow> = self
+            .with_retry(|| self.client.query(&query).bind(pool_addresses).fetch_all())
+            .await?;
+
+        let mut open_trading = self.get_open_trading(pool_addresses).await?;
+        let mut price_windows = self.get_price_window_24h(pool_addresses).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let address = row.pool_address.clone();
+                let mut pair_info = PairInfo::from(row);
+                pair_info.open_trading = open_trading.remove(&address);
+                pair_info.price_change_24h_percent = price_windows
+                    .remove(&address)
+                    .and_then(|window| window.change_percent(pair_info.pool.reversed));
+                (address, pair_info)
+            })
+            .collect())
+    }
+
+    // Time of the first swap per pool; pools that never traded are absent
+    async fn get_open_trading(
+        &self,
+        pool_addresses: &[String],
+    ) -> Result<HashMap<String, DateTime<Utc>>> {
+        #[derive(Debug, Deserialize, Row)]
+        struct OpenTradingRow {
+            pool_address: String,
+            #[serde(with = "clickhouse::serde::chrono::datetime")]
+            open_trading: DateTime<Utc>,
+        }
+
         let query = r#"
-        SELECT
-            pools.pool_address as pool_address,
-            pools.pool_base_address as pool_base_address,
-            pools.pool_quote_address as pool_quote_address,
-            pools.token_base_address as token_base_address,
-            pools.token_quote_address as token_quote_address,
-            pools.creator as creator,
-            pools.hash as hash,
-            pools.factory as factory,
-            pools.pre_factory as pre_factory,
-            pools.reversed as reversed,
-            COALESCE(pcu.curve_percentage, 0) as curve_percentage,
-            pools.initial_token_base_reserve as initial_token_base_reserve,
-            pools.initial_token_quote_reserve as initial_token_quote_reserve,
-            pools.slot as slot,
-            pools.metadata as metadata,
-            pools.created_at as created_at,
-            t.hash AS token_hash,
-            t.mint_address as mint_address,
-            COALESCE(t.name, '') as name,
-            COALESCE(t.symbol, '') as symbol,
-            t.decimals as decimals,
-            COALESCE(t.uri, '') as uri,
-            t.mint_authority as mint_authority,
-            t.supply as supply,
-            t.freeze_authority as freeze_authority,
-            t.slot AS token_slot,
-            t.image as image,
-            t.twitter as twitter,
-            t.telegram as telegram,
-            t.website as website,
-            t.program_id as program_id
-        FROM pools
-        LEFT JOIN (SELECT * FROM pool_curve_updates FINAL) pcu ON pools.pool_address = pcu.pool_address
-        INNER JOIN tokens t ON pools.token_base_address = t.mint_address
-        WHERE pools.pool_address = ? OR pools.token_base_address = ?
-        LIMIT 1
-    "#;
+            SELECT pool_address, MIN(created_at) AS open_trading
+            FROM swaps
+            WHERE pool_address IN ?
+            GROUP BY pool_address
+        "#;
 
-        // Use a struct for strict schema alignment (schema: 31 columns, struct: 31 fields)
-        #[derive(Debug, serde::Deserialize, Row, Serialize)]
-        struct PairInfoRow {
-            pool_address: String,
-            pool_base_address: String,
-            pool_quote_address: String,
-            token_base_address: String,
-            token_quote_address: String,
-            creator: String,
-            hash: String,
-            factory: String,
-            pre_factory: Option<String>,
-            reversed: bool,
-            curve_percentage: f32,
-            initial_token_base_reserve: f64,
-            initial_token_quote_reserve: f64,
-            slot: i64,
-            metadata: String,
-            #[serde(with = "clickhouse::serde::chrono::datetime")]
-            created_at: DateTime<Utc>,
+        let rows: Vec<OpenTradingRow> = self
+            .with_retry(|| self.client.query(query).bind(pool_addresses).fetch_all())
+            .await?;
 
-            // tokens.* fields
-            token_hash: String,
-            mint_address: String,
-            name: String,
-            symbol: String,
-            decimals: i8,
-            uri: String,
-            supply: f64,
-            token_slot: i64,
-            mint_authority: Option<String>,
-            freeze_authority: Option<String>,
-            image: Option<String>,
-            twitter: Option<String>,
-            telegram: Option<String>,
-            website: Option<String>,
-            program_id: String,
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.pool_address, row.open_trading))
+            .collect())
+    }
+
+    // First and last swap price per pool over the last 24h; pools with no swaps in that
+    // window are absent
+    async fn get_price_window_24h(
+        &self,
+        pool_addresses: &[String],
+    ) -> Result<HashMap<String, PriceWindow>> {
+        #[derive(Debug, Deserialize, Row)]
+        struct PriceWindowRow {
+            pool_address: String,
+            open_price: f64,
+            close_price: f64,
         }
 
-        let rows: Vec<PairInfoRow> = self
-            .client
-            .query(query)
-            .bind(&pool_address)
-            .bind(&pool_address)
-            .fetch_all()
-            .await?;
+        let query = r#"
+            SELECT
+                pool_address,
+                argMin(price_sol, created_at) AS open_price,
+                argMax(price_sol, created_at) AS close_price
+            FROM swaps
+            WHERE pool_address IN ?
+              AND created_at >= now() - INTERVAL 24 HOUR
+              AND swap_type IN ('BUY', 'SELL')
+              AND price_sol > 0
+            GROUP BY pool_address
+        "#;
 
-        println!("rows: {:?}", rows);
+        let rows: Vec<PriceWindowRow> = self
+            .with_retry(|| self.client.query(query).bind(pool_addresses).fetch_all())
+            .await?;
 
-        if let Some(row) = rows.into_iter().next() {
-            let pair_info = PairInfo {
-                pool: DBPool {
-                    pool_address: row.pool_address,
-                    pool_base_address: row.pool_base_address,
-                    pool_quote_address: row.pool_quote_address,
-                    token_base_address: row.token_base_address,
-                    token_quote_address: row.token_quote_address,
-                    creator: row.creator,
-                    hash: row.hash,
-                    factory: row.factory,
-                    pre_factory: row.pre_factory,
-                    reversed: row.reversed,
-
-                    initial_token_base_reserve: row.initial_token_base_reserve,
-                    initial_token_quote_reserve: row.initial_token_quote_reserve,
-                    slot: row.slot,
-                    metadata: row.metadata.to_string(),
-                    created_at: row.created_at,
-                },
-                base_token: DBToken {
-                    mint_address: row.mint_address,
-                    name: row.name,
-                    symbol: row.symbol,
-                    decimals: row.decimals,
-                    uri: row.uri,
-                    supply: row.supply,
-                    slot: row.token_slot,
-                    mint_authority: row.mint_authority,
-                    freeze_authority: row.freeze_authority,
-                    hash: row.token_hash,
-                    image: row.image,
-                    twitter: row.twitter,
-                    telegram: row.telegram,
-                    website: row.website,
-                    program_id: row.program_id,
-                },
-            };
-            Ok(pair_info)
-        } else {
-            Err(clickhouse::error::Error::Custom("No pair found".into()))
-        }
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let window = PriceWindow {
+                    open: row.open_price,
+                    close: row.close_price,
+                };
+                (row.pool_address, window)
+            })
+            .collect())
     }
-    pub async fn get_holders(
-        &self,
-        mint: String,
-    ) -> Result<Vec<HolderResponse>, clickhouse::error::Error> {
+
+    #[instrument(skip_all, fields(mint = %mint))]
+    pub async fn get_holders(&self, mint: String, limit: u64) -> Result<HolderList> {
+        // Same rule as get_token_info: buys in a pool's creation slot are bundlers, buys
+        // in the slot after are snipers
         let query = r#"
+            WITH pool_slots AS (
+                SELECT pool_address, slot
+                FROM pools
+                WHERE token_base_address = ?
+            ),
+            early_buyers AS (
+                SELECT
+                    s.creator AS owner,
+                    max(s.slot = ps.slot + 1) AS is_sniper,
+                    max(s.slot = ps.slot) AS is_bundler
+                FROM swaps s
+                INNER JOIN pool_slots ps ON s.pool_address = ps.pool_address
+                WHERE s.swap_type = 'BUY'
+                  AND (s.slot = ps.slot OR s.slot = ps.slot + 1)
+                GROUP BY s.creator
+            )
             SELECT
                 assumeNotNull(accounts.owner) as address,
                 accounts.account as account,
                 assumeNotNull(accounts.mint) as mint,
                 CAST(t.decimals AS UInt8) as decimals,
                 CAST(accounts.amount / POW(10, t.decimals) AS Float64) as amount,
-                COALESCE(accounts.delegated_amount, 0) as delegated_amount
+                COALESCE(accounts.delegated_amount, 0) as delegated_amount,
+                COALESCE(eb.is_sniper, false) as is_sniper,
+                COALESCE(eb.is_bundler, false) as is_bundler
             FROM accounts FINAL
             INNER JOIN token_initialize_events t ON accounts.mint = t.mint_address
+            LEFT JOIN early_buyers eb ON eb.owner = accounts.owner
             WHERE accounts.mint = ?
               AND accounts.amount > 0
               AND accounts.owner IS NOT NULL
               AND accounts.mint IS NOT NULL
               AND accounts.account IS NOT NULL
             ORDER BY amount DESC
-            LIMIT 50
+            LIMIT ?
         "#;
 
         let holders = self
-            .client
-            .query(query)
-            .bind(&mint)
-            .fetch_all::<HolderResponse>()
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(&mint)
+                    .bind(&mint)
+                    .bind(limit)
+                    .fetch_all::<HolderResponse>(),
+            )
+            .await?;
+
+        // Same filters as above, minus the token join and the limit
+        let count_query = r#"
+            SELECT count(DISTINCT owner)
+            FROM accounts FINAL
+            WHERE mint = ?
+              AND amount > 0
+              AND owner IS NOT NULL
+              AND account IS NOT NULL
+        "#;
+
+        let total = self
+            .with_timeout(
+                self.client
+                    .query(count_query)
+                    .bind(&mint)
+                    .fetch_one::<u64>(),
+            )
+            .await?;
+
+        Ok(HolderList { holders, total })
+    }
+
+    #[instrument(skip_all, fields(pool_address = %pool_address))]
+    pub async fn get_holders_export(&self, pool_address: String) -> Result<Vec<HolderExportEntry>> {
+        // Pool-owned accounts (the pool itself and its vaults) are not holders
+        let query = r#"
+        WITH pool_info AS (
+            SELECT
+                pool_address,
+                token_base_address,
+                pool_base_address,
+                pool_quote_address
+            FROM pools
+            WHERE pool_address = ?
+            LIMIT 1
+        ),
+        tok AS (
+            SELECT
+                i.mint_address,
+                i.decimals,
+                COALESCE(s.total_supply, 0) AS token_supply
+            FROM token_initialize_events i
+            LEFT JOIN (
+                SELECT mint_address, sum(raw_amount) AS total_supply
+                FROM token_mint_events
+                GROUP BY mint_address
+            ) s ON i.mint_address = s.mint_address
+            JOIN pool_info pi ON i.mint_address = pi.token_base_address
+        )
+        SELECT
+            assumeNotNull(a.owner) AS owner,
+            CAST(a.amount AS Float64) AS amount_raw,
+            CAST(coalesce(tk.token_supply, 0) AS Float64) AS token_supply,
+            coalesce(tk.decimals, 0) AS decimals,
+            notEmpty(coalesce(tk.mint_address, '')) AS has_decimals
+        FROM pool_info pi
+        JOIN (SELECT * FROM accounts FINAL) a ON a.mint = pi.token_base_address
+        LEFT JOIN tok tk ON 1=1
+        WHERE a.amount > 0
+          AND a.owner IS NOT NULL
+          AND a.owner != pi.pool_address
+          AND a.owner != pi.pool_base_address
+          AND a.owner != pi.pool_quote_address
+        ORDER BY a.amount DESC, owner ASC
+        LIMIT ?
+        "#;
+
+        #[derive(Debug, Deserialize, Row)]
+        struct HolderExportRow {
+            owner: String,
+            amount_raw: f64,
+            token_supply: f64,
+            decimals: u8,
+            has_decimals: bool,
+        }
+
+        let rows = self
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(&pool_address)
+                    .bind(HOLDERS_EXPORT_LIMIT)
+                    .fetch_all::<HolderExportRow>(),
+            )
             .await?;
 
+        let holders = rows
+            .into_iter()
+            .map(|row| {
+                let decimals = if row.has_decimals {
+                    row.decimals
+                } else {
+                    DEFAULT_TOKEN_DECIMALS
+                };
+                let scale_factor = 10.0_f64.powi(decimals as i32);
+
+                HolderExportEntry {
+                    owner: row.owner,
+                    amount: row.amount_raw / scale_factor,
+                    percent_of_supply: clamp_percentage(calculate_percentage(
+                        row.amount_raw,
+                        row.token_supply,
+                    )),
+                }
+            })
+            .collect();
+
         Ok(holders)
     }
 
+    #[instrument(skip_all, fields(mint = %mint))]
+    pub async fn get_holder_distribution(&self, mint: String) -> Result<HolderDistribution> {
+        // Balances are kept raw; only ratios are needed, so decimals don't matter here
+        let query = r#"
+        WITH pool_owned AS (
+            SELECT arrayJoin([pool_address, pool_base_address, pool_quote_address]) AS owner
+            FROM pools
+            WHERE token_base_address = ?
+        )
+        SELECT CAST(a.amount AS Float64) AS amount_raw
+        FROM (SELECT * FROM accounts FINAL) a
+        WHERE a.mint = ?
+          AND a.amount > 0
+          AND a.owner IS NOT NULL
+          AND a.owner NOT IN (SELECT owner FROM pool_owned)
+        "#;
+
+        let supply_query = r#"
+            SELECT CAST(coalesce(sum(raw_amount), 0) AS Float64) AS token_supply
+            FROM token_mint_events
+            WHERE mint_address = ?
+        "#;
+
+        let mut amounts = self
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(&mint)
+                    .bind(&mint)
+                    .fetch_all::<f64>(),
+            )
+            .await?;
+
+        let minted = self
+            .with_timeout(
+                self.client
+                    .query(supply_query)
+                    .bind(&mint)
+                    .fetch_one::<f64>(),
+            )
+            .await?;
+
+        // Mints without indexed mint events fall back to what holders currently have
+        let token_supply = if minted > 0.0 {
+            minted
+        } else {
+            amounts.iter().sum()
+        };
+
+        // (label, min %, max %) of supply held by a single wallet
+        let bounds: [(&str, f64, Option<f64>); 3] = [
+            (">1%", 1.0, None),
+            ("0.1-1%", 0.1, Some(1.0)),
+            ("<0.1%", 0.0, Some(0.1)),
+        ];
+
+        let total_holders = amounts.len() as u64;
+        let mut counts = [0u64; 3];
+        let mut held = [0.0f64; 3];
+        for amount in &amounts {
+            let percent = calculate_percentage(*amount, token_supply);
+            let idx = bounds
+                .iter()
+                .position(|(_, min, _)| percent >= *min)
+                .unwrap_or(bounds.len() - 1);
+            counts[idx] += 1;
+            held[idx] += amount;
+        }
+
+        let buckets = bounds
+            .iter()
+            .enumerate()
+            .map(|(i, (label, min, max))| HolderBucket {
+                label: label.to_string(),
+                min_percent: *min,
+                max_percent: *max,
+                holder_count: counts[i],
+                holders_percent: calculate_percentage(counts[i] as f64, total_holders as f64),
+                supply_percent: clamp_percentage(calculate_percentage(held[i], token_supply)),
+            })
+            .collect();
+
+        Ok(HolderDistribution {
+            mint,
+            total_holders,
+            buckets,
+            gini: gini_coefficient(&mut amounts),
+        })
+    }
+
+    #[instrument(skip_all, fields(pool_address = %pool_address))]
     pub async fn get_pool_swaps(
         &self,
         pool_address: String,
         start_date: Option<DateTime<Utc>>,
         end_date: Option<DateTime<Utc>>,
-    ) -> Result<Vec<DBSwap>, clickhouse::error::Error> {
+    ) -> Result<Vec<DBSwap>> {
         // ClickHouse expects Unix timestamp (i64) for date comparisons in many data models.
         // Convert Option<DateTime<Utc>> to i64 (unix timestamp), or use a default min/max if None.
         use chrono::{TimeZone, Utc};
@@ -645,22 +1614,106 @@ GROUP BY
         "#;
 
         let swaps: Vec<DBSwap> = self
-            .client
-            .query(query)
-            .bind(&pool_address)
-            .bind(start_ts)
-            .bind(end_ts)
-            .fetch_all()
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(&pool_address)
+                    .bind(start_ts)
+                    .bind(end_ts)
+                    .fetch_all(),
+            )
             .await?;
 
         Ok(swaps)
     }
 
+    // Decimals for a pool's swap amounts. The base token's come from `tokens`, else from
+    // its mint initialization; the quote token's from the registry. A reversed pool holds
+    // the quote token on its base side, so the two are swapped.
+    #[instrument(skip_all, fields(pool_address = %pool_address))]
+    pub async fn get_swap_decimals(&self, pool_address: &str) -> Result<SwapDecimals> {
+        let query = r#"
+            SELECT
+                p.token_quote_address AS token_quote_address,
+                p.reversed AS reversed,
+                if(isNotNull(t.decimals) OR notEmpty(ti.mint_address),
+                   toUInt8(coalesce(t.decimals, ti.decimals)), NULL) AS base_decimals
+            FROM pools p
+            LEFT JOIN tokens t ON t.mint_address = p.token_base_address
+            LEFT JOIN token_initialize_events ti ON ti.mint_address = p.token_base_address
+            WHERE p.pool_address = ?
+            LIMIT 1
+        "#;
+
+        let Some(row) = self
+            .with_retry(|| {
+                self.client
+                    .query(query)
+                    .bind(pool_address)
+                    .fetch_optional::<SwapDecimalsRow>()
+            })
+            .await?
+        else {
+            return Ok(SwapDecimals::default());
+        };
+
+        let quote = self
+            .quote_tokens
+            .get(&row.token_quote_address)
+            .map(|token| token.decimals);
+        Ok(if row.reversed {
+            SwapDecimals {
+                base: quote,
+                quote: row.base_decimals,
+                reversed: true,
+            }
+        } else {
+            SwapDecimals {
+                base: row.base_decimals,
+                quote,
+                reversed: false,
+            }
+        })
+    }
+
+    #[instrument(skip_all, fields(hash = %hash))]
+    pub async fn get_swap_by_hash(&self, hash: String) -> Result<Option<DBSwap>> {
+        let query = r#"
+            SELECT
+                creator,
+                pool_address,
+                hash,
+                base_amount,
+                quote_amount,
+                base_reserve,
+                quote_reserve,
+                price_sol,
+                swap_type,
+                slot,
+                created_at
+            FROM swaps
+            WHERE hash = ?
+            LIMIT 1
+        "#;
+
+        let swap = self
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(&hash)
+                    .fetch_optional::<DBSwap>(),
+            )
+            .await?;
+
+        Ok(swap)
+    }
+
+    #[instrument(skip_all, fields(pool_address = %pool_address))]
     pub async fn get_pool_report(
         &self,
         pool_address: String,
         report_type: ReportType,
-    ) -> Result<Option<Vec<PoolReport>>> {
+    ) -> Result<Vec<PoolReportEntry>> {
         // Match projection patterns exactly
         let (bucket_expression, table_query) = match report_type {
             ReportType::OneMinute => (
@@ -675,7 +1728,9 @@ GROUP BY
                     countIf(swap_type = 'SELL') AS sell_count,
                     uniq(creator) AS unique_traders,
                     uniqIf(creator, swap_type = 'BUY') AS unique_buyers,
-                    uniqIf(creator, swap_type = 'SELL') AS unique_sellers
+                    uniqIf(creator, swap_type = 'SELL') AS unique_sellers,
+                    argMin(price_sol, created_at) AS open_price,
+                    argMax(price_sol, created_at) AS close_price
                 FROM swaps
                 WHERE pool_address = ?
                 GROUP BY pool_address, bucket_start
@@ -695,7 +1750,9 @@ GROUP BY
                     countIf(swap_type = 'SELL') AS sell_count,
                     uniq(creator) AS unique_traders,
                     uniqIf(creator, swap_type = 'BUY') AS unique_buyers,
-                    uniqIf(creator, swap_type = 'SELL') AS unique_sellers
+                    uniqIf(creator, swap_type = 'SELL') AS unique_sellers,
+                    argMin(price_sol, created_at) AS open_price,
+                    argMax(price_sol, created_at) AS close_price
                 FROM swaps
                 WHERE pool_address = ?
                 GROUP BY pool_address, bucket_start
@@ -715,7 +1772,9 @@ GROUP BY
                     countIf(swap_type = 'SELL') AS sell_count,
                     uniq(creator) AS unique_traders,
                     uniqIf(creator, swap_type = 'BUY') AS unique_buyers,
-                    uniqIf(creator, swap_type = 'SELL') AS unique_sellers
+                    uniqIf(creator, swap_type = 'SELL') AS unique_sellers,
+                    argMin(price_sol, created_at) AS open_price,
+                    argMax(price_sol, created_at) AS close_price
                 FROM swaps
                 WHERE pool_address = ?
                 GROUP BY pool_address, bucket_start
@@ -735,7 +1794,9 @@ GROUP BY
                     countIf(swap_type = 'SELL') AS sell_count,
                     uniq(creator) AS unique_traders,
                     uniqIf(creator, swap_type = 'BUY') AS unique_buyers,
-                    uniqIf(creator, swap_type = 'SELL') AS unique_sellers
+                    uniqIf(creator, swap_type = 'SELL') AS unique_sellers,
+                    argMin(price_sol, created_at) AS open_price,
+                    argMax(price_sol, created_at) AS close_price
                 FROM swaps
                 WHERE pool_address = ?
                 GROUP BY pool_address, bucket_start
@@ -755,7 +1816,9 @@ GROUP BY
                     countIf(swap_type = 'SELL') AS sell_count,
                     uniq(creator) AS unique_traders,
                     uniqIf(creator, swap_type = 'BUY') AS unique_buyers,
-                    uniqIf(creator, swap_type = 'SELL') AS unique_sellers
+                    uniqIf(creator, swap_type = 'SELL') AS unique_sellers,
+                    argMin(price_sol, created_at) AS open_price,
+                    argMax(price_sol, created_at) AS close_price
                 FROM swaps
                 WHERE pool_address = ?
                 GROUP BY pool_address, bucket_start
@@ -766,20 +1829,57 @@ GROUP BY
         };
 
         let data: Vec<PoolReport> = self
-            .client
-            .query(table_query)
-            .bind(&pool_address)
-            .fetch_all()
+            .with_timeout(
+                self.client
+                    .query(table_query)
+                    .bind(&pool_address)
+                    .fetch_all(),
+            )
             .await?;
 
-        Ok(Some(data))
+        if data.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Volumes are sums of quote_amount, so they are in the pool's quote token
+        let pool = self
+            .with_timeout(
+                self.client
+                    .query(
+                        "SELECT token_quote_address, reversed FROM pools \
+                         WHERE pool_address = ? LIMIT 1",
+                    )
+                    .bind(&pool_address)
+                    .fetch_optional::<PoolQuoteRow>(),
+            )
+            .await?
+            .unwrap_or_else(|| PoolQuoteRow {
+                token_quote_address: SOL_TOKEN.address.to_string(),
+                reversed: false,
+            });
+        let quote_token = self.quote_tokens.resolve(&pool.token_quote_address);
+
+        Ok(data
+            .into_iter()
+            .map(|report| PoolReportEntry {
+                price_change_percent: PriceWindow {
+                    open: report.open_price,
+                    close: report.close_price,
+                }
+                .change_percent(pool.reversed),
+                buy_pressure: buy_pressure(report.buy_volume, report.sell_volume),
+                report,
+                quote_mint: quote_token.address.clone(),
+                quote_decimals: quote_token.decimals,
+            })
+            .collect())
     }
 
-    pub async fn get_token_info(
-        &self,
-        pool_address: String,
-    ) -> Result<TokenInfo, clickhouse::error::Error> {
-        let query = r#"
+    #[instrument(skip_all, fields(pool_address = %pool_address))]
+    pub async fn get_token_info(&self, pool_address: String, top_n: u32) -> Result<TokenInfo> {
+        // top_n is one of a few fixed values checked by the route, so it is inlined
+        let query = format!(
+            r#"
 WITH pool_info AS (
     SELECT
         p.pool_address,
@@ -791,8 +1891,10 @@ WITH pool_info AS (
     FROM pools p
     WHERE p.pool_address = ?
 ),
-top10_holders AS (
-    SELECT CAST(sum(x.amount) AS Float64) AS top10_amount_raw
+top_holders AS (
+    SELECT
+        CAST(sumIf(x.amount, x.rn <= 10) AS Float64) AS top10_amount_raw,
+        CAST(sumIf(x.amount, x.rn <= {top_n}) AS Float64) AS top_n_amount_raw
     FROM (
         SELECT
             a.amount,
@@ -807,7 +1909,7 @@ top10_holders AS (
                OR pi2.pool_quote_address = a.owner
         )
     ) x
-    WHERE rn <= 10
+    WHERE rn <= greatest(10, {top_n})
 ),
 dev_hold AS (
     SELECT CAST(coalesce(a.amount, 0) AS Float64) AS dev_amount_raw
@@ -867,26 +1969,27 @@ tok AS (
 )
 SELECT
     coalesce(th.top10_amount_raw, 0) AS top10_amount_raw,
+    toUInt32({top_n}) AS top_n,
+    coalesce(th.top_n_amount_raw, 0) AS top_n_amount_raw,
     coalesce(d.dev_amount_raw, 0) AS dev_amount_raw,
     coalesce(sh.snipers_amount_raw, 0) AS snipers_amount_raw,
     coalesce(t.num_holders, 0) AS num_holders,
     coalesce(tk.token_supply, 0) AS token_supply,
     coalesce(tk.decimals, 0) AS decimals,
-    coalesce(bh.bundlers_amount_raw, 0) AS bundlers_amount_raw
+    coalesce(bh.bundlers_amount_raw, 0) AS bundlers_amount_raw,
+    notEmpty(coalesce(tk.mint_address, '')) AS has_decimals
 FROM pool_info pi
-LEFT JOIN top10_holders th ON 1=1
+LEFT JOIN top_holders th ON 1=1
 LEFT JOIN dev_hold d ON 1=1
 LEFT JOIN snipers_holds sh ON 1=1
 LEFT JOIN bundlers_holds bh ON 1=1
 LEFT JOIN total_holders t ON 1=1
 LEFT JOIN tok tk ON 1=1
-        "#;
+        "#
+        );
 
         let rows: Vec<TokenInfoRow> = match self
-            .client
-            .query(query)
-            .bind(&pool_address)
-            .fetch_all()
+            .with_timeout(self.client.query(&query).bind(&pool_address).fetch_all())
             .await
         {
             Ok(rows) => rows,
@@ -896,41 +1999,163 @@ LEFT JOIN tok tk ON 1=1
             }
         };
 
-        println!("rows: {:?}", rows);
+        debug!("rows: {:?}", rows);
 
-        let row = rows
-            .into_iter()
+        rows.into_iter()
             .next()
-            .ok_or_else(|| clickhouse::error::Error::Custom("No token info found".into()))?;
+            .map(TokenInfo::from)
+            .ok_or(ServiceError::NotFound)
+    }
+
+    // Same figures as get_token_info (with the default top_n) for many pools in one query;
+    // each CTE is grouped by pool and excludes only that pool's own accounts. Unknown
+    // pools are absent.
+    pub async fn get_token_info_batch(
+        &self,
+        pool_addresses: &[String],
+    ) -> Result<HashMap<String, TokenInfo>> {
+        if pool_addresses.is_empty() {
+            return Ok(HashMap::new());
+        }
 
-        // Calculate scale factor in Rust: 10^decimals
-        let scale_factor = 10.0_f64.powi(row.decimals as i32);
+        let query = r#"
+WITH pool_info AS (
+    SELECT
+        p.pool_address,
+        p.token_base_address,
+        p.creator,
+        p.pool_base_address,
+        p.pool_quote_address,
+        p.slot
+    FROM pools p
+    WHERE p.pool_address IN ?
+),
+holder_accounts AS (
+    SELECT pi.pool_address AS pool_address, a.owner AS owner, a.amount AS amount
+    FROM pool_info pi
+    JOIN (SELECT * FROM accounts FINAL) a ON a.mint = pi.token_base_address
+    WHERE a.amount > 0
+      AND a.owner <> pi.pool_address
+      AND a.owner <> pi.pool_base_address
+      AND a.owner <> pi.pool_quote_address
+),
+top10_holders AS (
+    SELECT pool_address, CAST(sum(amount) AS Float64) AS top10_amount_raw
+    FROM (
+        SELECT
+            pool_address,
+            amount,
+            row_number() OVER (PARTITION BY pool_address ORDER BY amount DESC) AS rn
+        FROM holder_accounts
+    ) x
+    WHERE rn <= 10
+    GROUP BY pool_address
+),
+total_holders AS (
+    SELECT pool_address, count(DISTINCT owner) AS num_holders
+    FROM holder_accounts
+    GROUP BY pool_address
+),
+dev_hold AS (
+    SELECT pi.pool_address, CAST(coalesce(any(a.amount), 0) AS Float64) AS dev_amount_raw
+    FROM pool_info pi
+    LEFT JOIN (SELECT * FROM accounts FINAL) a
+        ON a.mint = pi.token_base_address
+        AND a.owner = pi.creator
+    GROUP BY pi.pool_address
+),
+bundlers_holds AS (
+    SELECT pi.pool_address, CAST(sum(s.base_amount) AS Float64) AS bundlers_amount_raw
+    FROM swaps s
+    JOIN pool_info pi ON s.pool_address = pi.pool_address
+    WHERE s.swap_type = 'BUY' AND s.slot = pi.slot
+    GROUP BY pi.pool_address
+),
+snipers_holds AS (
+    SELECT pi.pool_address, CAST(sum(s.base_amount) AS Float64) AS snipers_amount_raw
+    FROM swaps s
+    JOIN pool_info pi ON s.pool_address = pi.pool_address
+    WHERE s.swap_type = 'BUY' AND s.slot = pi.slot + 1
+    GROUP BY pi.pool_address
+),
+tok AS (
+    SELECT
+        i.mint_address,
+        i.decimals,
+        COALESCE(s.total_supply, 0) AS token_supply
+    FROM token_initialize_events i
+    LEFT JOIN (
+        SELECT mint_address, sum(raw_amount) as total_supply
+        FROM token_mint_events
+        GROUP BY mint_address
+    ) s ON i.mint_address = s.mint_address
+    WHERE i.mint_address IN (SELECT token_base_address FROM pool_info)
+)
+SELECT
+    pi.pool_address AS pool_address,
+    coalesce(th.top10_amount_raw, 0) AS top10_amount_raw,
+    coalesce(d.dev_amount_raw, 0) AS dev_amount_raw,
+    coalesce(sh.snipers_amount_raw, 0) AS snipers_amount_raw,
+    coalesce(t.num_holders, 0) AS num_holders,
+    coalesce(tk.token_supply, 0) AS token_supply,
+    coalesce(tk.decimals, 0) AS decimals,
+    coalesce(bh.bundlers_amount_raw, 0) AS bundlers_amount_raw,
+    notEmpty(coalesce(tk.mint_address, '')) AS has_decimals
+FROM pool_info pi
+LEFT JOIN top10_holders th ON th.pool_address = pi.pool_address
+LEFT JOIN dev_hold d ON d.pool_address = pi.pool_address
+LEFT JOIN snipers_holds sh ON sh.pool_address = pi.pool_address
+LEFT JOIN bundlers_holds bh ON bh.pool_address = pi.pool_address
+LEFT JOIN total_holders t ON t.pool_address = pi.pool_address
+LEFT JOIN tok tk ON tk.mint_address = pi.token_base_address
+LIMIT 1 BY pi.pool_address
+        "#;
 
-        let bundlers_decimal_adjusted = row.bundlers_amount_raw / scale_factor;
-        let dev_decimal_adjusted = row.dev_amount_raw / scale_factor;
-        let snipers_decimal_adjusted = row.snipers_amount_raw / scale_factor;
-        let top10_decimal_adjusted = row.top10_amount_raw / scale_factor;
+        #[derive(Debug, Deserialize, Row)]
+        struct TokenInfoBatchRow {
+            pool_address: String,
+            top10_amount_raw: f64,
+            dev_amount_raw: f64,
+            snipers_amount_raw: f64,
+            num_holders: u64,
+            token_supply: f64,
+            decimals: i8,
+            bundlers_amount_raw: f64,
+            has_decimals: bool,
+        }
 
-        let token_info = TokenInfo {
-            bundlers_hold_percent: calculate_percentage(
-                bundlers_decimal_adjusted,
-                row.token_supply,
-            ),
-            dev_holds_percent: calculate_percentage(dev_decimal_adjusted, row.token_supply),
-            num_holders: row.num_holders as i64,
-            snipers_hold_percent: calculate_percentage(snipers_decimal_adjusted, row.token_supply),
-            top10_holders_percent: calculate_percentage(top10_decimal_adjusted, row.token_supply),
-        };
+        let rows: Vec<TokenInfoBatchRow> = self
+            .with_timeout(self.client.query(query).bind(pool_addresses).fetch_all())
+            .await?;
 
-        Ok(token_info)
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let info = TokenInfo::from(TokenInfoRow {
+                    top10_amount_raw: row.top10_amount_raw,
+                    top_n: DEFAULT_TOP_HOLDERS,
+                    top_n_amount_raw: row.top10_amount_raw,
+                    dev_amount_raw: row.dev_amount_raw,
+                    snipers_amount_raw: row.snipers_amount_raw,
+                    num_holders: row.num_holders,
+                    token_supply: row.token_supply,
+                    decimals: row.decimals,
+                    bundlers_amount_raw: row.bundlers_amount_raw,
+                    has_decimals: row.has_decimals,
+                });
+                (row.pool_address, info)
+            })
+            .collect())
     }
 
+    #[instrument(skip_all, fields(pool_address = %pool_address, user = %creator))]
     pub async fn get_trader_details(
         &self,
         creator: String,
         pool_address: String,
-    ) -> Result<Option<TopTrader>> {
-        let query = r#"
+    ) -> Result<Option<TraderDetails>> {
+        let query = format!(
+            r#"
         WITH pool_info AS (
             SELECT token_base_address
             FROM pools
@@ -947,9 +2172,10 @@ LEFT JOIN tok tk ON 1=1
             max(s.slot = fs.first_slot) AS is_sniper,
             coalesce(sumIf(s.base_amount, s.swap_type = 'BUY'), 0) AS base_bought,
             coalesce(sumIf(s.base_amount, s.swap_type = 'SELL'), 0) AS base_sold,
+            {BASE_EXACT_SUMS},
             coalesce(sumIf(s.quote_amount, s.swap_type = 'BUY'), 0) AS quote_bought,
             coalesce(sumIf(s.quote_amount, s.swap_type = 'SELL'), 0) AS quote_sold,
-            coalesce(anyOrNull(a.amount) / pow(10, COALESCE(anyOrNull(t.decimals), 0)), 0) AS holding_base_token
+            any({HELD_BASE_TOKENS}) AS holding_base_token
         FROM swaps s
         CROSS JOIN pool_info pi
         CROSS JOIN first_swap fs
@@ -957,23 +2183,1286 @@ LEFT JOIN tok tk ON 1=1
             ON a.owner = ?
            AND a.mint = pi.token_base_address
            AND a.amount > 0
-        LEFT JOIN token_initialize_events t ON t.mint_address = pi.token_base_address
+        LEFT JOIN tokens t ON t.mint_address = pi.token_base_address
+        LEFT JOIN token_initialize_events ti ON ti.mint_address = pi.token_base_address
         PREWHERE s.pool_address = ? AND s.creator = ?
         WHERE s.swap_type IN ('BUY', 'SELL')
         GROUP BY s.creator
+        "#
+        );
+
+        let rows: Option<TopTraderRow> = self
+            .with_timeout(
+                self.client
+                    .query(&query)
+                    .bind(&pool_address) // pool_info
+                    .bind(&pool_address) // first_swap
+                    .bind(&creator) // accounts join filter
+                    .bind(&pool_address) // PREWHERE s.pool_address
+                    .bind(&creator) // PREWHERE s.creator
+                    .fetch_optional(),
+            )
+            .await?;
+
+        let Some(trader) = rows.map(TopTrader::from) else {
+            return Ok(None);
+        };
+        let current_price_sol = self.get_current_price(&pool_address).await?;
+        Ok(Some(TraderDetails::new(trader, current_price_sol)))
+    }
+
+    // Latest swap price of a pool as quote per base token; 0 until it has traded
+    async fn get_current_price(&self, pool_address: &str) -> Result<f64> {
+        let query = r#"
+        SELECT
+            CAST(coalesce(argMax(price_sol, (slot, created_at)), 0) AS Float64) AS price_sol,
+            (SELECT any(reversed) FROM pools WHERE pool_address = ?) AS reversed
+        FROM swaps
+        WHERE pool_address = ?
         "#;
 
-        let rows: Option<TopTrader> = self
-            .client
-            .query(query)
-            .bind(&pool_address) // pool_info
-            .bind(&pool_address) // first_swap
-            .bind(&creator) // accounts join filter
-            .bind(&pool_address) // PREWHERE s.pool_address
-            .bind(&creator) // PREWHERE s.creator
-            .fetch_optional()
+        #[derive(Debug, Deserialize, Row)]
+        struct CurrentPriceRow {
+            price_sol: f64,
+            reversed: bool,
+        }
+
+        let row = self
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(pool_address) // pools subquery
+                    .bind(pool_address) // swaps
+                    .fetch_one::<CurrentPriceRow>(),
+            )
             .await?;
 
-        Ok(rows)
+        Ok(PoolReserves::oriented(row.reversed, 0.0, 0.0, row.price_sol).price)
     }
-}
+
+    pub async fn get_batch_pulse_data(
+        &self,
+        pool_addresses: &[String],
+    ) -> Result<Vec<PulseDataResponse>> {
+        self.get_pulse_data_for_pools(pool_addresses, Some(1)).await
+    }
+
+    // Pulse rows for a `POST /pulse` query. Identical queries within
+    // PULSE_CACHE_TTL_MS, including ones still in flight, share one ClickHouse round trip.
+    #[instrument(skip_all)]
+    pub async fn get_pulse_rows(&self, query: &str) -> Result<Arc<Vec<PulseDataResponse>>> {
+        self.pulse_cache
+            .get_or_load(query, || async {
+                let rows: Vec<PulseRow> = self
+                    .with_retry(|| self.client.query(query).fetch_all())
+                    .await?;
+                Ok(Arc::new(
+                    rows.into_iter()
+                        .map(|row| row.into_pulse_data(&self.quote_tokens))
+                        .collect(),
+                ))
+            })
+            .await
+    }
+
+    // The pulse row of one pool whatever its age, curve progress or activity. Only unknown
+    // pools are NotFound.
+    #[instrument(skip_all, fields(pool_address = %pool_address))]
+    pub async fn get_pool_pulse_data(&self, pool_address: String) -> Result<PulseDataResponse> {
+        self.get_pulse_data_for_pools(&[pool_address], None)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(ServiceError::NotFound)
+    }
+
+    // Pulse rows for the given pools. With `max_age_hours` set this is the live feed: pools
+    // created within that many hours, below 50% of the curve and traded in the last 24h,
+    // with swaps, holders and funding read from the last hour. Without it nothing is bounded.
+    async fn get_pulse_data_for_pools(
+        &self,
+        pool_addresses: &[String],
+        max_age_hours: Option<u32>,
+    ) -> Result<Vec<PulseDataResponse>> {
+        if pool_addresses.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let (age_filter, since, curve_filter, vol_join) = match max_age_hours {
+            Some(hours) => (
+                format!("AND p.created_at >= now() - INTERVAL {} HOUR", hours),
+                "now() - INTERVAL 1 HOUR",
+                "WHERE coalesce(pcu.curve_percentage, 0) < 50",
+                "JOIN",
+            ),
+            // The epoch keeps the window clauses in place while letting every row through
+            None => (String::new(), "toDateTime(0)", "", "LEFT JOIN"),
+        };
+
+        // One round-trip for the whole batch instead of one pulse query per pool
+        let query = format!(
+            r#"
+WITH all_pools AS (
+  SELECT
+    p.pool_address,
+    p.creator,
+    p.token_base_address,
+    p.token_quote_address,
+    p.pool_base_address,
+    p.pool_quote_address,
+    p.factory,
+    p.created_at,
+    p.initial_token_base_reserve,
+    p.initial_token_quote_reserve,
+    p.reversed
+  FROM pools p
+  WHERE p.pool_address IN ?
+    {age_filter}
+),
+pool_curve AS (
+  SELECT
+    pool_address,
+    argMax(curve_percentage, updated_at) as curve_percentage
+  FROM pool_curve_updates FINAL
+  GROUP BY pool_address
+),
+pools_with_curve AS (
+  SELECT r.*, coalesce(pcu.curve_percentage, 0) as curve_percentage
+  FROM all_pools r
+  LEFT JOIN pool_curve pcu ON pcu.pool_address = r.pool_address
+  {curve_filter}
+),
+r AS (
+  SELECT * FROM pools_with_curve
+),
+{TOKEN_CTE},
+latest_swap AS (
+  SELECT
+    r.pool_address,
+    s.base_reserve AS latest_base_reserve,
+    s.quote_reserve AS latest_quote_reserve,
+    s.price_sol AS latest_price_sol,
+    notEmpty(coalesce(s.pool_address, '')) AS has_trades
+  FROM pools_with_curve r
+  LEFT JOIN (
+    SELECT
+      pool_address,
+      argMax(base_reserve, created_at) AS base_reserve,
+      argMax(quote_reserve, created_at) AS quote_reserve,
+      argMax(price_sol, created_at) AS price_sol
+    FROM swaps
+    WHERE pool_address IN (SELECT pool_address FROM pools_with_curve)
+      AND created_at >= {since}
+    GROUP BY pool_address
+  ) s ON s.pool_address = r.pool_address
+),
+holders_base AS (
+  SELECT
+    r.pool_address,
+    COUNT(DISTINCT a.owner) AS num_holders
+  FROM pools_with_curve r
+  JOIN accounts a
+    ON a.mint = r.token_base_address
+   AND a.owner <> r.pool_address
+   AND a.owner <> r.pool_base_address
+   AND a.owner <> r.pool_quote_address
+   AND a.updated_at >= {since}
+  GROUP BY r.pool_address
+),
+top10_holders AS (
+  SELECT pool_address, SUM(amount) AS top10_amount_raw
+  FROM (
+    SELECT r.pool_address, a.amount,
+           ROW_NUMBER() OVER (PARTITION BY r.pool_address ORDER BY a.amount DESC) AS rn
+    FROM pools_with_curve r
+    JOIN accounts a
+      ON a.mint = r.token_base_address
+     AND a.owner <> r.pool_address
+     AND a.owner <> r.pool_base_address
+     AND a.owner <> r.pool_quote_address
+     AND a.updated_at >= {since}
+  ) x
+  WHERE rn <= 10
+  GROUP BY pool_address
+),
+dev_hold AS (
+  SELECT
+    r.pool_address,
+    coalesce(max(a.amount), 0) AS dev_amount_raw
+  FROM pools_with_curve r
+  LEFT JOIN accounts a
+    ON a.mint  = r.token_base_address
+   AND a.owner = r.creator
+   AND a.owner <> r.pool_address
+   AND a.updated_at >= {since}
+  GROUP BY r.pool_address
+),
+snipers_holds AS (
+  SELECT s.pool_address,
+         COALESCE(SUM(s.base_amount), 0) AS snipers_amount_raw
+  FROM swaps s
+  JOIN pools_with_curve r ON r.pool_address = s.pool_address
+  WHERE s.swap_type = 'BUY'
+    AND s.creator <> r.pool_address
+    AND s.creator <> r.pool_base_address
+    AND s.creator <> r.pool_quote_address
+    AND s.created_at >= {since}
+  GROUP BY s.pool_address
+),
+dev_wallet_funding AS (
+  SELECT
+    r.pool_address,
+    ts.source,
+    ts.destination,
+    ts.amount,
+    ts.hash,
+    ts.earliest_transfer_at AS created_at
+  FROM pools_with_curve r
+  LEFT JOIN (
+    SELECT
+      destination,
+      argMin(source, created_at) AS source,
+      argMin(amount, created_at) AS amount,
+      argMin(hash, created_at) AS hash,
+      min(created_at) AS earliest_transfer_at
+    FROM transfer_sol
+    WHERE destination IN (SELECT creator FROM pools_with_curve)
+      AND created_at >= {since}
+    GROUP BY destination
+  ) ts ON ts.destination = r.creator
+),
+migration AS (
+  SELECT r.creator,
+         countIf(p2.pre_factory = 'PumpFun' AND p2.factory = 'PumpSwap') AS migration_count
+  FROM pools_with_curve r
+  LEFT JOIN pools p2 ON p2.creator = r.creator AND p2.created_at >= now() - INTERVAL 24 HOUR
+  GROUP BY r.creator
+),
+vol_24h AS (
+  SELECT
+    pool_address,
+    SUM(CASE WHEN swap_type = 'BUY' THEN quote_amount ELSE 0 END) +
+    SUM(CASE WHEN swap_type = 'SELL' THEN quote_amount ELSE 0 END) AS volume_sol,
+    CAST(COUNT(CASE WHEN swap_type = 'BUY' THEN 1 END) AS Int64) AS num_buys,
+    CAST(COUNT(CASE WHEN swap_type = 'SELL' THEN 1 END) AS Int64) AS num_sells,
+    CAST(COUNT(*) AS Int64) AS num_txns
+  FROM swaps
+  WHERE pool_address IN (SELECT pool_address FROM pools_with_curve)
+    AND created_at >= now() - INTERVAL 24 HOUR
+  GROUP BY pool_address
+)
+SELECT
+  r.pool_address AS pool_address,
+  r.creator AS creator,
+  r.token_base_address AS token_base_address,
+  r.token_quote_address AS token_quote_address,
+  r.factory AS factory,
+  r.created_at AS created_at,
+  r.initial_token_base_reserve AS initial_token_base_reserve,
+  r.initial_token_quote_reserve AS initial_token_quote_reserve,
+  r.reversed AS reversed,
+  r.curve_percentage AS bonding_curve_percent,
+  t.name AS name,
+  t.symbol AS symbol,
+  t.image AS image,
+  t.decimals AS decimals,
+  t.website AS website,
+  t.twitter AS twitter,
+  t.telegram AS telegram,
+  t.discord AS discord,
+  r.token_base_address AS mint_address,
+  t.token_supply AS token_supply,
+  if(empty(t.mint_address), 1, t.scale_factor) AS scale_factor,
+  empty(t.mint_address) AS metadata_pending,
+  coalesce(ls.latest_quote_reserve, r.initial_token_quote_reserve) AS liquidity_sol,
+  coalesce(ls.latest_base_reserve, r.initial_token_base_reserve) AS liquidity_token,
+  ls.latest_price_sol AS current_price_sol,
+  coalesce(ls.has_trades, 0) AS has_trades,
+  coalesce(h.num_holders, 0) AS num_holders,
+  coalesce(th.top10_amount_raw, 0) AS top10_amount_raw,
+  coalesce(d.dev_amount_raw, 0) AS dev_amount_raw,
+  coalesce(sh.snipers_amount_raw, 0) AS snipers_amount_raw,
+  coalesce(m.migration_count, 0) AS migration_count,
+  coalesce(v.volume_sol, 0) AS volume_sol,
+  v.num_txns AS num_txns,
+  coalesce(v.num_buys, 0) AS num_buys,
+  coalesce(v.num_sells, 0) AS num_sells,
+  nullIf(df.source, '') AS funding_wallet_address,
+  nullIf(df.destination, '') AS wallet_address,
+  if(df.source = '', NULL, df.amount) AS amount_sol,
+  nullIf(df.hash, '') AS transfer_hash,
+  if(df.source = '', NULL, df.created_at) AS funded_at
+FROM pools_with_curve r
+{vol_join} vol_24h v ON v.pool_address = r.pool_address
+LEFT JOIN tok t ON t.mint_address = r.token_base_address
+JOIN latest_swap ls ON ls.pool_address = r.pool_address
+LEFT JOIN holders_base h ON h.pool_address = r.pool_address
+LEFT JOIN top10_holders th ON th.pool_address = r.pool_address
+LEFT JOIN dev_hold d ON d.pool_address = r.pool_address
+LEFT JOIN snipers_holds sh ON sh.pool_address = r.pool_address
+LEFT JOIN dev_wallet_funding df ON df.pool_address = r.pool_address
+LEFT JOIN migration m ON m.creator = r.creator
+        "#
+        );
+
+        let rows: Vec<PulseRow> = self
+            .with_timeout(self.client.query(&query).bind(pool_addresses).fetch_all())
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| row.into_pulse_data(&self.quote_tokens))
+            .collect())
+    }
+
+    // Pools from the last 24h ranked by momentum: the share of their last hour of volume
+    // that traded in the last 5 minutes. Both windows are summed from the 5m buckets so
+    // they line up; pool_report_1h buckets are aligned to the hour instead.
+    pub async fn get_trending_pools(
+        &self,
+        limit: u64,
+        factory: Option<String>,
+    ) -> Result<Vec<PulseDataResponse>> {
+        let query = format!(
+            r#"
+        WITH vol AS (
+            SELECT
+                s.pool_address,
+                CAST(sumIf(s.buy_volume + s.sell_volume,
+                           s.bucket_start >= now() - INTERVAL 5 MINUTE) AS Float64) AS volume_5m,
+                CAST(sum(s.buy_volume + s.sell_volume) AS Float64) AS volume_1h
+            FROM pool_report_5m s
+            WHERE s.bucket_start >= now() - INTERVAL 1 HOUR
+            GROUP BY s.pool_address
+        )
+        SELECT v.pool_address AS pool_address
+        FROM vol v
+        JOIN pools p ON p.pool_address = v.pool_address
+        WHERE p.created_at >= now() - INTERVAL 24 HOUR
+          AND v.volume_1h > 0
+          {}
+        ORDER BY v.volume_5m / v.volume_1h DESC, v.volume_5m DESC, v.pool_address ASC
+        LIMIT ?
+        "#,
+            if factory.is_some() {
+                "AND p.factory = ?"
+            } else {
+                ""
+            }
+        );
+
+        let mut ranked = self.client.query(&query);
+        if let Some(factory) = &factory {
+            ranked = ranked.bind(factory);
+        }
+        let pool_addresses: Vec<String> = self
+            .with_timeout(ranked.bind(limit).fetch_all::<String>())
+            .await?;
+
+        let mut pools = self
+            .get_pulse_data_for_pools(&pool_addresses, Some(24))
+            .await?;
+        let rank: HashMap<&str, usize> = pool_addresses
+            .iter()
+            .enumerate()
+            .map(|(i, address)| (address.as_str(), i))
+            .collect();
+        pools.sort_by_key(|pool| rank.get(pool.pair_address.as_str()).copied());
+
+        Ok(pools)
+    }
+
+    #[instrument(skip_all, fields(pool_address = %pool_address))]
+    pub async fn get_liquidity_lock_status(
+        &self,
+        pool_address: String,
+    ) -> Result<Option<LiquidityLockResponse>> {
+        let query = r#"
+        WITH pool_info AS (
+            SELECT
+                pool_address,
+                factory,
+                initial_token_quote_reserve,
+                JSONExtractString(metadata, 'lp_mint') AS lp_mint
+            FROM pools
+            WHERE pool_address = ?
+            LIMIT 1
+        ),
+        liquidity_events AS (
+            SELECT
+                countIf(swap_type = 'REMOVE') AS remove_count,
+                sumIf(abs(quote_amount), swap_type = 'REMOVE') AS removed_quote,
+                count() AS event_count,
+                argMax(quote_reserve, created_at) AS current_quote_reserve
+            FROM swaps
+            WHERE pool_address = ?
+        ),
+        burned_lp AS (
+            SELECT CAST(coalesce(sum(a.amount), 0) AS Float64) AS burned_lp_amount
+            FROM (SELECT * FROM accounts FINAL) a
+            JOIN pool_info pi ON a.mint = pi.lp_mint
+            WHERE pi.lp_mint != ''
+              AND a.owner IN ?
+              AND a.amount > 0
+        )
+        SELECT
+            pi.pool_address AS pool_address,
+            pi.factory AS factory,
+            pi.initial_token_quote_reserve AS initial_quote_reserve,
+            coalesce(le.remove_count, 0) AS remove_count,
+            coalesce(le.removed_quote, 0) AS removed_quote,
+            coalesce(le.event_count, 0) AS event_count,
+            coalesce(le.current_quote_reserve, 0) AS current_quote_reserve,
+            coalesce(bl.burned_lp_amount, 0) AS burned_lp_amount
+        FROM pool_info pi
+        LEFT JOIN liquidity_events le ON 1=1
+        LEFT JOIN burned_lp bl ON 1=1
+        "#;
+
+        #[derive(Debug, Deserialize, Row)]
+        struct LiquidityLockRow {
+            pool_address: String,
+            factory: String,
+            initial_quote_reserve: f64,
+            remove_count: u64,
+            removed_quote: f64,
+            event_count: u64,
+            current_quote_reserve: f64,
+            burned_lp_amount: f64,
+        }
+
+        let row = self
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(&pool_address)
+                    .bind(&pool_address)
+                    .bind(&LP_BURN_ADDRESSES[..])
+                    .fetch_optional::<LiquidityLockRow>(),
+            )
+            .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        // Trading alone says nothing about a lock; only a reserve that stayed close to where
+        // it started does
+        let reserves_stable = row.event_count > 0
+            && row.initial_quote_reserve > 0.0
+            && (row.current_quote_reserve - row.initial_quote_reserve).abs()
+                <= row.initial_quote_reserve * LP_RESERVE_TOLERANCE;
+        let status = if row.burned_lp_amount > 0.0 {
+            LiquidityLockStatus::Burned
+        } else if row.remove_count > 0 {
+            LiquidityLockStatus::Unlocked
+        } else if reserves_stable {
+            LiquidityLockStatus::Locked
+        } else {
+            LiquidityLockStatus::Unknown
+        };
+
+        let current_quote_reserve = if row.event_count > 0 {
+            row.current_quote_reserve
+        } else {
+            row.initial_quote_reserve
+        };
+
+        Ok(Some(LiquidityLockResponse {
+            pool_address: row.pool_address,
+            status,
+            factory: row.factory,
+            remove_count: row.remove_count,
+            removed_quote: row.removed_quote,
+            initial_quote_reserve: row.initial_quote_reserve,
+            current_quote_reserve,
+            burned_lp_amount: row.burned_lp_amount,
+        }))
+    }
+
+    #[instrument(skip_all, fields(pool_address = %pool_address))]
+    pub async fn get_dev_activity(&self, pool_address: String) -> Result<DevActivityResponse> {
+        let creator_query = r#"
+            SELECT creator
+            FROM pools
+            WHERE pool_address = ?
+            LIMIT 1
+        "#;
+
+        let creator = self
+            .with_timeout(
+                self.client
+                    .query(creator_query)
+                    .bind(&pool_address)
+                    .fetch_optional::<String>(),
+            )
+            .await?
+            .ok_or(ServiceError::NotFound)?;
+
+        let swaps_query = r#"
+            SELECT
+                creator,
+                pool_address,
+                hash,
+                base_amount,
+                quote_amount,
+                base_reserve,
+                quote_reserve,
+                price_sol,
+                swap_type,
+                slot,
+                created_at
+            FROM swaps
+            PREWHERE pool_address = ? AND creator = ?
+            WHERE swap_type IN ('BUY', 'SELL')
+            ORDER BY slot ASC, created_at ASC
+        "#;
+
+        let swaps: Vec<DBSwap> = self
+            .with_timeout(
+                self.client
+                    .query(swaps_query)
+                    .bind(&pool_address)
+                    .bind(&creator)
+                    .fetch_all(),
+            )
+            .await?;
+
+        let mut cumulative_holding = 0.0;
+        let activity = swaps
+            .into_iter()
+            .map(|swap| {
+                match swap.swap_type.as_str() {
+                    "BUY" => cumulative_holding += swap.base_amount.abs(),
+                    "SELL" => cumulative_holding -= swap.base_amount.abs(),
+                    _ => {}
+                }
+                DevActivityEntry {
+                    hash: swap.hash,
+                    swap_type: swap.swap_type,
+                    base_amount: swap.base_amount,
+                    quote_amount: swap.quote_amount,
+                    price_sol: swap.price_sol,
+                    slot: swap.slot,
+                    created_at: swap.created_at,
+                    cumulative_holding,
+                }
+            })
+            .collect();
+
+        Ok(DevActivityResponse {
+            pool_address,
+            creator,
+            activity,
+        })
+    }
+
+    pub async fn get_market_stats(&self) -> Result<MarketStats> {
+        // pool_report_24h is bucketed per calendar day, so a rolling 24h window is
+        // summed from the 5m buckets instead
+        let query = r#"
+        WITH vol AS (
+            SELECT
+                s.pool_address,
+                CAST(sum(s.buy_volume + s.sell_volume) AS Float64) AS volume_sol
+            FROM pool_report_5m s
+            WHERE s.bucket_start >= now() - INTERVAL 24 HOUR
+            GROUP BY s.pool_address
+        ),
+        totals AS (
+            SELECT CAST(coalesce(sum(volume_sol), 0) AS Float64) AS volume_sol_24h
+            FROM vol
+        ),
+        new_pools AS (
+            SELECT
+                count() AS new_pools_24h,
+                countIf(pre_factory = 'PumpFun' AND factory = 'PumpSwap') AS migrations_24h
+            FROM pools
+            WHERE created_at >= now() - INTERVAL 24 HOUR
+        ),
+        top_factory AS (
+            SELECT
+                p.factory AS factory,
+                CAST(sum(v.volume_sol) AS Float64) AS volume_sol
+            FROM vol v
+            JOIN pools p ON p.pool_address = v.pool_address
+            GROUP BY p.factory
+            ORDER BY volume_sol DESC, factory ASC
+            LIMIT 1
+        )
+        SELECT
+            t.volume_sol_24h AS volume_sol_24h,
+            np.new_pools_24h AS new_pools_24h,
+            np.migrations_24h AS migrations_24h,
+            coalesce(tf.factory, '') AS top_factory,
+            coalesce(tf.volume_sol, 0) AS top_factory_volume_sol
+        FROM totals t
+        LEFT JOIN new_pools np ON 1=1
+        LEFT JOIN top_factory tf ON 1=1
+        "#;
+
+        #[derive(Debug, Deserialize, Row)]
+        struct MarketStatsRow {
+            volume_sol_24h: f64,
+            new_pools_24h: u64,
+            migrations_24h: u64,
+            top_factory: String,
+            top_factory_volume_sol: f64,
+        }
+
+        let row = self
+            .with_timeout(self.client.query(query).fetch_one::<MarketStatsRow>())
+            .await?;
+
+        Ok(MarketStats {
+            volume_sol_24h: row.volume_sol_24h,
+            new_pools_24h: row.new_pools_24h,
+            migrations_24h: row.migrations_24h,
+            top_factory: (!row.top_factory.is_empty()).then_some(row.top_factory),
+            top_factory_volume_sol: row.top_factory_volume_sol,
+        })
+    }
+
+    // Pools created and volume traded per factory over the last `hours`, busiest first
+    pub async fn get_factory_stats(&self, hours: u32) -> Result<Vec<FactoryStat>> {
+        // Like get_market_stats, volume comes from the 5m buckets because pool_report_24h
+        // is bucketed per calendar day
+        let query = r#"
+        SELECT
+            factory,
+            sum(pools_created) AS pools_created,
+            CAST(sum(volume_sol) AS Float64) AS volume_sol
+        FROM (
+            SELECT
+                factory,
+                count() AS pools_created,
+                CAST(0 AS Float64) AS volume_sol
+            FROM pools
+            WHERE created_at >= now() - toIntervalHour(?)
+            GROUP BY factory
+
+            UNION ALL
+
+            SELECT
+                p.factory AS factory,
+                toUInt64(0) AS pools_created,
+                CAST(sum(s.buy_volume + s.sell_volume) AS Float64) AS volume_sol
+            FROM pool_report_5m s
+            JOIN pools p ON p.pool_address = s.pool_address
+            WHERE s.bucket_start >= now() - toIntervalHour(?)
+            GROUP BY p.factory
+        )
+        GROUP BY factory
+        ORDER BY volume_sol DESC, factory ASC
+        "#;
+
+        let stats = self
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(hours)
+                    .bind(hours)
+                    .fetch_all::<FactoryStat>(),
+            )
+            .await?;
+
+        Ok(stats)
+    }
+
+    // Pools created in the last `hours`, newest first
+    pub async fn get_recent_pools(&self, hours: u32, limit: u64) -> Result<Vec<DBPool>> {
+        let query = r#"
+            SELECT
+                creator,
+                pool_address,
+                pool_base_address,
+                pool_quote_address,
+                factory,
+                pre_factory,
+                token_base_address,
+                token_quote_address,
+                initial_token_base_reserve,
+                initial_token_quote_reserve,
+                slot,
+                reversed,
+                created_at,
+                hash,
+                metadata
+            FROM pools
+            WHERE created_at >= now() - toIntervalHour(?)
+            ORDER BY created_at DESC
+            LIMIT ?
+        "#;
+
+        let pools = self
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(hours)
+                    .bind(limit)
+                    .fetch_all::<DBPool>(),
+            )
+            .await?;
+
+        Ok(pools)
+    }
+
+    // Holder growth of a mint in `interval_secs` buckets, the most recent `limit` buckets
+    // oldest first. accounts only keeps the latest balance per account once merged, so a
+    // wallet counts from its first non-zero balance and isn't subtracted when it sells out.
+    #[instrument(skip_all, fields(mint = %mint))]
+    pub async fn get_holders_history(
+        &self,
+        mint: String,
+        interval_secs: i64,
+        limit: u64,
+    ) -> Result<Vec<HolderCountPoint>> {
+        let query = r#"
+            SELECT bucket, new_holders, holders
+            FROM (
+                SELECT
+                    bucket,
+                    new_holders,
+                    toUInt64(sum(new_holders) OVER (ORDER BY bucket ASC)) AS holders
+                FROM (
+                    SELECT
+                        toStartOfInterval(first_seen, toIntervalSecond(?)) AS bucket,
+                        count() AS new_holders
+                    FROM (
+                        SELECT owner, min(updated_at) AS first_seen
+                        FROM accounts
+                        WHERE mint = ?
+                          AND owner IS NOT NULL
+                          AND amount > 0
+                        GROUP BY owner
+                    )
+                    GROUP BY bucket
+                )
+            )
+            ORDER BY bucket DESC
+            LIMIT ?
+        "#;
+
+        #[derive(Debug, Deserialize, Row)]
+        struct HolderCountRow {
+            #[serde(with = "clickhouse::serde::chrono::datetime")]
+            bucket: DateTime<Utc>,
+            new_holders: u64,
+            holders: u64,
+        }
+
+        let rows = self
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(interval_secs)
+                    .bind(&mint)
+                    .bind(limit)
+                    .fetch_all::<HolderCountRow>(),
+            )
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .rev()
+            .map(|row| HolderCountPoint {
+                bucket: row.bucket,
+                new_holders: row.new_holders,
+                holders: row.holders,
+            })
+            .collect())
+    }
+
+    // BUY/SELL swaps of the last 24h moving more than `min_sol`, newest first, with the
+    // base token's metadata. Only SOL-quoted pools that aren't reversed are scanned, since
+    // elsewhere quote_amount isn't SOL.
+    pub async fn get_whale_trades(&self, min_sol: f64, limit: u64) -> Result<Vec<WhaleTrade>> {
+        let query = r#"
+            SELECT
+                s.hash AS hash,
+                s.pool_address AS pool_address,
+                s.creator AS trader,
+                s.swap_type AS swap_type,
+                s.base_amount AS base_amount,
+                s.quote_amount AS quote_amount,
+                s.price_sol AS price_sol,
+                s.slot AS slot,
+                s.created_at AS created_at,
+                p.token_base_address AS token_address,
+                coalesce(t.name, '') AS name,
+                coalesce(t.symbol, '') AS symbol,
+                t.image AS image
+            FROM (
+                SELECT hash, pool_address, creator, swap_type, base_amount, quote_amount,
+                    price_sol, slot, created_at
+                FROM swaps
+                PREWHERE created_at >= now() - INTERVAL 24 HOUR
+                WHERE swap_type IN ('BUY', 'SELL') AND abs(quote_amount) > ?
+                  AND pool_address IN (
+                    SELECT pool_address FROM pools
+                    WHERE token_quote_address = ? AND NOT reversed
+                  )
+                ORDER BY created_at DESC
+                LIMIT ?
+            ) s
+            JOIN pools p ON p.pool_address = s.pool_address
+            LEFT JOIN tokens t ON t.mint_address = p.token_base_address
+            ORDER BY s.created_at DESC
+        "#;
+
+        let rows = self
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(min_sol)
+                    .bind(SOL_TOKEN.address)
+                    .bind(limit)
+                    .fetch_all::<WhaleTradeRow>(),
+            )
+            .await?;
+
+        // The scan already applies the threshold; rechecking keeps a row at or below it
+        // from ever reaching the feed
+        Ok(rows
+            .into_iter()
+            .filter(|row| row.quote_amount.abs() > min_sol)
+            .map(|row| WhaleTrade {
+                hash: row.hash,
+                pool_address: row.pool_address,
+                trader: row.trader,
+                swap_type: row.swap_type,
+                base_amount: row.base_amount,
+                quote_amount: row.quote_amount,
+                price_sol: row.price_sol,
+                slot: row.slot,
+                created_at: row.created_at,
+                token_address: row.token_address,
+                name: row.name,
+                symbol: row.symbol,
+                image: row.image,
+            })
+            .collect())
+    }
+
+    // Pools created by `creator`, newest first, with their latest curve percentage
+    #[instrument(skip_all, fields(creator = %creator))]
+    pub async fn get_creator_pools(&self, creator: String, limit: u64) -> Result<Vec<CreatorPool>> {
+        let query = r#"
+            SELECT
+                p.pool_address AS pool_address,
+                p.token_base_address AS token_base_address,
+                p.token_quote_address AS token_quote_address,
+                p.factory AS factory,
+                p.pre_factory AS pre_factory,
+                p.created_at AS created_at,
+                CAST(coalesce(pcu.curve_percentage, 0) AS Float32) AS curve_percentage,
+                notEmpty(coalesce(m.token_base_address, '')) AS migrated
+            FROM pools p
+            LEFT JOIN (
+                SELECT pool_address, curve_percentage
+                FROM pool_curve_updates FINAL
+                WHERE pool_address IN (SELECT pool_address FROM pools WHERE creator = ?)
+            ) pcu ON pcu.pool_address = p.pool_address
+            LEFT JOIN (
+                SELECT DISTINCT token_base_address
+                FROM pools
+                WHERE creator = ? AND pre_factory = 'PumpFun' AND factory = 'PumpSwap'
+            ) m ON m.token_base_address = p.token_base_address
+            WHERE p.creator = ?
+            ORDER BY p.created_at DESC
+            LIMIT ?
+        "#;
+
+        #[derive(Debug, Deserialize, Row)]
+        struct CreatorPoolRow {
+            pool_address: String,
+            token_base_address: String,
+            token_quote_address: String,
+            factory: String,
+            pre_factory: Option<String>,
+            #[serde(with = "clickhouse::serde::chrono::datetime")]
+            created_at: DateTime<Utc>,
+            curve_percentage: f32,
+            migrated: bool,
+        }
+
+        let rows = self
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(&creator)
+                    .bind(&creator)
+                    .bind(&creator)
+                    .bind(limit)
+                    .fetch_all::<CreatorPoolRow>(),
+            )
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| CreatorPool {
+                pool_address: row.pool_address,
+                token_base_address: row.token_base_address,
+                token_quote_address: row.token_quote_address,
+                factory: row.factory,
+                pre_factory: row.pre_factory,
+                created_at: row.created_at,
+                curve_percentage: row.curve_percentage,
+                migrated: row.migrated,
+            })
+            .collect())
+    }
+
+    // Latest authorities from token_latest_authority_mv. A mint it has never seen is NotFound.
+    #[instrument(skip_all, fields(mint = %mint))]
+    pub async fn get_token_authority(&self, mint: String) -> Result<TokenAuthority> {
+        let query = r#"
+            SELECT mint_authority, freeze_authority
+            FROM token_latest_authority_mv
+            WHERE mint_address = ?
+            LIMIT 1
+        "#;
+
+        #[derive(Debug, Deserialize, Row)]
+        struct AuthorityRow {
+            mint_authority: Option<String>,
+            freeze_authority: Option<String>,
+        }
+
+        let row = self
+            .with_retry(|| {
+                self.client
+                    .query(query)
+                    .bind(&mint)
+                    .fetch_optional::<AuthorityRow>()
+            })
+            .await?
+            .ok_or(ServiceError::NotFound)?;
+
+        Ok(TokenAuthority::new(
+            row.mint_authority,
+            row.freeze_authority,
+        ))
+    }
+
+    // Whether a pool is indexed and has any swap, without touching anything heavier
+    #[instrument(skip_all, fields(pool_address = %pool_address))]
+    pub async fn get_pool_exists(&self, pool_address: String) -> Result<PoolExists> {
+        let query = r#"
+            SELECT
+                factory,
+                (SELECT count() > 0 FROM (
+                    SELECT 1 FROM swaps WHERE pool_address = ? LIMIT 1
+                )) AS has_trades
+            FROM pools
+            WHERE pool_address = ?
+            LIMIT 1
+        "#;
+
+        let row = self
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(&pool_address) // swaps subquery
+                    .bind(&pool_address) // pools
+                    .fetch_optional::<ExistsRow>(),
+            )
+            .await?;
+
+        Ok(match row {
+            Some(row) => PoolExists {
+                exists: true,
+                has_trades: row.has_trades,
+                factory: Some(row.factory),
+            },
+            None => PoolExists {
+                exists: false,
+                has_trades: false,
+                factory: None,
+            },
+        })
+    }
+
+    // PumpFun -> PumpSwap migrations of the creator's pools, counted as in the pulse
+    // `migration` CTE but over all time
+    #[instrument(skip_all, fields(creator = %creator))]
+    pub async fn get_creator_migrations(&self, creator: String) -> Result<CreatorMigrations> {
+        let query = r#"
+            SELECT
+                countIf(pre_factory = 'PumpFun' AND factory = 'PumpSwap') AS migration_count,
+                arrayMap(
+                    x -> x.1,
+                    arrayReverseSort(
+                        x -> x.2,
+                        groupArrayIf(
+                            (pool_address, created_at),
+                            pre_factory = 'PumpFun' AND factory = 'PumpSwap'
+                        )
+                    )
+                ) AS pool_addresses
+            FROM pools
+            WHERE creator = ?
+        "#;
+
+        #[derive(Debug, Deserialize, Row)]
+        struct MigrationRow {
+            migration_count: u64,
+            pool_addresses: Vec<String>,
+        }
+
+        let row = self
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(&creator)
+                    .fetch_one::<MigrationRow>(),
+            )
+            .await?;
+
+        Ok(CreatorMigrations {
+            creator,
+            migration_count: row.migration_count,
+            pool_addresses: row.pool_addresses,
+        })
+    }
+
+    // Bonding-curve progress of a pool, oldest update first
+    #[instrument(skip_all, fields(pool_address = %pool_address))]
+    pub async fn get_curve_history(
+        &self,
+        pool_address: String,
+        limit: u64,
+    ) -> Result<Vec<CurvePoint>> {
+        let query = r#"
+            SELECT
+                CAST(curve_percentage AS Float32) AS curve_percentage,
+                updated_at
+            FROM pool_curve_updates FINAL
+            WHERE pool_address = ?
+            ORDER BY updated_at ASC
+            LIMIT ?
+        "#;
+
+        #[derive(Debug, Deserialize, Row)]
+        struct CurveRow {
+            curve_percentage: f32,
+            #[serde(with = "clickhouse::serde::chrono::datetime")]
+            updated_at: DateTime<Utc>,
+        }
+
+        let rows = self
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(&pool_address)
+                    .bind(limit)
+                    .fetch_all::<CurveRow>(),
+            )
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| CurvePoint {
+                curve_percentage: row.curve_percentage,
+                updated_at: row.updated_at,
+            })
+            .collect())
+    }
+
+    #[instrument(skip_all, fields(user = %wallet))]
+    pub async fn get_incoming_transfers(
+        &self,
+        wallet: String,
+        limit: u64,
+    ) -> Result<Vec<FundingTransfer>> {
+        let query = r#"
+            SELECT
+                source,
+                CAST(amount AS Float64) AS amount,
+                hash,
+                created_at
+            FROM transfer_sol
+            WHERE destination = ?
+            ORDER BY created_at ASC, hash ASC
+            LIMIT ?
+        "#;
+
+        #[derive(Debug, Deserialize, Row)]
+        struct IncomingTransferRow {
+            source: String,
+            amount: f64,
+            hash: String,
+            #[serde(with = "clickhouse::serde::chrono::datetime")]
+            created_at: DateTime<Utc>,
+        }
+
+        let rows = self
+            .with_timeout(
+                self.client
+                    .query(query)
+                    .bind(&wallet)
+                    .bind(limit)
+                    .fetch_all::<IncomingTransferRow>(),
+            )
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| FundingTransfer {
+                source: row.source,
+                amount: row.amount,
+                hash: row.hash,
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+
+    // `from` / `to` (unix seconds) bound the swaps counted; all-time when absent
+    #[instrument(skip_all, fields(user = %wallet))]
+    pub async fn get_wallet_pnl(
+        &self,
+        wallet: String,
+        from: Option<i64>,
+        to: Option<i64>,
+    ) -> Result<WalletPnl> {
+        let mut window = String::new();
+        if from.is_some() {
+            window.push_str(" AND created_at >= toDateTime(?)");
+        }
+        if to.is_some() {
+            window.push_str(" AND created_at <= toDateTime(?)");
+        }
+
+        let query = format!(
+            r#"
+        WITH traded AS (
+            SELECT
+                pool_address,
+                CAST(sumIf(abs(quote_amount), swap_type = 'BUY') AS Float64) AS native_spent,
+                CAST(sumIf(abs(quote_amount), swap_type = 'SELL') AS Float64) AS native_received,
+                CAST(sumIf(abs(base_amount), swap_type = 'BUY') AS Float64) AS tokens_bought,
+                CAST(sumIf(abs(base_amount), swap_type = 'SELL') AS Float64) AS tokens_sold,
+                max(created_at) AS last_trade_at
+            FROM swaps
+            PREWHERE creator = ?
+            WHERE swap_type IN ('BUY', 'SELL'){window}
+            GROUP BY pool_address
+        ),
+        latest_price AS (
+            SELECT
+                pool_address,
+                CAST(argMax(price_sol, (slot, created_at)) AS Float64) AS price_native
+            FROM swaps
+            WHERE pool_address IN (SELECT pool_address FROM traded)
+            GROUP BY pool_address
+        )
+        SELECT
+            tr.pool_address AS pool_address,
+            coalesce(p.token_base_address, '') AS token_base_address,
+            tr.native_spent AS native_spent,
+            tr.native_received AS native_received,
+            tr.tokens_bought AS tokens_bought,
+            tr.tokens_sold AS tokens_sold,
+            CAST({HELD_BASE_TOKENS} AS Nullable(Float64)) AS remaining_tokens,
+            coalesce(lp.price_native, 0) AS price_native
+        FROM traded tr
+        LEFT JOIN pools p ON p.pool_address = tr.pool_address
+        LEFT JOIN latest_price lp ON lp.pool_address = tr.pool_address
+        LEFT JOIN (
+            SELECT mint, amount
+            FROM accounts FINAL
+            WHERE owner = ? AND amount > 0
+        ) a ON a.mint = p.token_base_address
+        LEFT JOIN tokens t ON t.mint_address = p.token_base_address
+        LEFT JOIN token_initialize_events ti ON ti.mint_address = p.token_base_address
+        ORDER BY tr.last_trade_at DESC, tr.pool_address ASC
+        "#
+        );
+
+        #[derive(Debug, Deserialize, Row)]
+        struct PnlRow {
+            pool_address: String,
+            token_base_address: String,
+            native_spent: f64,
+            native_received: f64,
+            tokens_bought: f64,
+            tokens_sold: f64,
+            remaining_tokens: Option<f64>,
+            price_native: f64,
+        }
+
+        let mut pnl_query = self.client.query(&query).bind(&wallet);
+        if let Some(from) = from {
+            pnl_query = pnl_query.bind(from);
+        }
+        if let Some(to) = to {
+            pnl_query = pnl_query.bind(to);
+        }
+        let rows = self
+            .with_timeout(pnl_query.bind(&wallet).fetch_all::<PnlRow>())
+            .await?;
+
+        // A mint traded in several pools (e.g. PumpFun then PumpSwap after migration)
+        // shares one token account; value the balance once, in the latest-traded pool
+        let mut valued_mints = HashSet::new();
+        let pools: Vec<PoolPnl> = rows
+            .into_iter()
+            .map(|row| {
+                let remaining_tokens = if valued_mints.insert(row.token_base_address.clone()) {
+                    row.remaining_tokens
+                } else {
+                    Some(0.0)
+                };
+                PoolPnl {
+                    realized: row.native_received - row.native_spent,
+                    unrealized: remaining_tokens.map(|tokens| tokens * row.price_native),
+                    pool_address: row.pool_address,
+                    token_base_address: row.token_base_address,
+                    native_spent: row.native_spent,
+                    native_received: row.native_received,
+                    avg_buy_price: average_price(row.native_spent, row.tokens_bought),
+                    avg_sell_price: average_price(row.native_received, row.tokens_sold),
+                    tokens_bought: row.tokens_bought,
+                    tokens_sold: row.tokens_sold,
+                    remaining_tokens,
+                    price_native: row.price_native,
+                }
+            })
+            .collect();
+
+        let total_realized: f64 = pools.iter().map(|p| p.realized).sum();
+        // Pools whose holding can't be valued (unknown decimals) are left out
+        let total_unrealized: f64 = pools.iter().filter_map(|p| p.unrealized).sum();
+
+        Ok(WalletPnl {
+            wallet,
+            pools,
+            total_realized,
+            total_unrealized,
+            total_pnl: total_realized + total_unrealized,
+        })
+    }
+}
+
+#[cfg(test)]
+impl ClickhouseService {
+    // A service around `client` with the settings `init` uses by default
+    pub fn with_client(client: Client) -> Self {
+        Self {
+            client,
+            query_timeout: Duration::from_secs(5),
+            candle_intervals: Arc::new(OnceCell::from(Interval::ALL.to_vec())),
+            candles_swap_fallback: true,
+            quote_tokens: Arc::new(QuoteTokenRegistry::default()),
+            read_retries: 2,
+            pulse_cache: PulseCache::new(Duration::from_secs(1)),
+            holder_counts_mv: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(test)]
+pub mod testing;
@@ -0,0 +1,105 @@
+// Helpers for tests of the queries: rows to mock them with, and the SQL a handler sends.
+// `clickhouse::test::Mock` only records request bodies, and short reads go out as GET with
+// the SQL in the URL.
+use std::future::Future;
+
+use clickhouse::Client;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+    sync::oneshot,
+};
+
+use super::ClickhouseService;
+// Rows tests can hand to `clickhouse::test::Mock` in place of a query's result
+pub(crate) use super::{ExistsRow, SwapDecimalsRow};
+
+// Runs `run` against a server that answers the first query with an empty body and
+// returns that query's SQL. Whatever `run` does with the empty result is ignored.
+pub async fn recorded_query<F, Fut>(run: F) -> String
+where
+    F: FnOnce(ClickhouseService) -> Fut,
+    Fut: Future,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = oneshot::channel();
+
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 4096];
+        let header_end = loop {
+            if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                break end + 4;
+            }
+            let n = stream.read(&mut buf).await.unwrap();
+            if n == 0 {
+                break request.len();
+            }
+            request.extend_from_slice(&buf[..n]);
+        };
+        let head = String::from_utf8_lossy(&request[..header_end]).into_owned();
+        let content_length = head
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse().ok())?
+            })
+            .unwrap_or(0);
+        while request.len() < header_end + content_length {
+            let n = stream.read(&mut buf).await.unwrap();
+            if n == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..n]);
+        }
+        let body = String::from_utf8_lossy(&request[header_end..]).into_owned();
+        let _ = tx.send((head, body));
+        let _ = stream
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+            .await;
+    });
+
+    run(ClickhouseService::with_client(
+        Client::default().with_url(url),
+    ))
+    .await;
+    let (head, body) = rx.await.unwrap();
+    // Long queries are POSTed; short ones travel in the `query` URL parameter
+    if !body.is_empty() {
+        return body;
+    }
+    let target = head.split_whitespace().nth(1).unwrap_or_default();
+    let params = target.split_once('?').map(|(_, q)| q).unwrap_or_default();
+    params
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("query="))
+        .map(percent_decode)
+        .unwrap_or_default()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(b) => {
+                        out.push(b);
+                        i += 2;
+                    }
+                    Err(_) => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
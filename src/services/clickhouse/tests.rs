@@ -0,0 +1,1806 @@
+use axum::http::StatusCode;
+use clickhouse::test::{Mock, handlers};
+use serde_json::json;
+
+use super::*;
+use crate::defaults::USDC_TOKEN;
+use crate::routes::pulse::PulseRow;
+
+fn service(mock: &Mock) -> ClickhouseService {
+    ClickhouseService::with_client(Client::default().with_url(mock.url()))
+}
+
+fn token_info_row() -> TokenInfoRow {
+    TokenInfoRow {
+        top10_amount_raw: 0.0,
+        top_n: 10,
+        top_n_amount_raw: 0.0,
+        dev_amount_raw: 0.0,
+        snipers_amount_raw: 0.0,
+        num_holders: 10,
+        token_supply: 1_000_000_000.0,
+        decimals: 6,
+        bundlers_amount_raw: 0.0,
+        has_decimals: true,
+    }
+}
+
+#[tokio::test]
+async fn slow_query_returns_timeout() {
+    let mock = Mock::new();
+    let service = ClickhouseService {
+        query_timeout: Duration::from_millis(10),
+        ..service(&mock)
+    };
+    let result: Result<()> = service.with_timeout(std::future::pending()).await;
+    assert!(matches!(result, Err(ServiceError::Timeout)));
+    assert_eq!(
+        result.unwrap_err().status_code(),
+        StatusCode::GATEWAY_TIMEOUT
+    );
+}
+
+#[tokio::test]
+async fn token_info_clamps_and_flags_missing_decimals() {
+    let mock = Mock::new();
+    // No decimals metadata: raw amounts are still 10^6 too large for the supply
+    mock.add(handlers::provide(vec![TokenInfoRow {
+        top10_amount_raw: 900_000_000.0 * 1e6,
+        dev_amount_raw: 5e20,
+        decimals: 0,
+        has_decimals: false,
+        ..token_info_row()
+    }]));
+
+    let info = service(&mock)
+        .get_token_info("pool".to_string(), 10)
+        .await
+        .unwrap();
+    assert!(info.decimals_defaulted);
+    assert_eq!(info.top10_holders_percent, 90.0);
+    assert_eq!(info.dev_holds_percent, 100.0);
+    assert_eq!(info.snipers_hold_percent, 0.0);
+}
+
+#[tokio::test]
+async fn token_info_keeps_known_decimals() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![TokenInfoRow {
+        top10_amount_raw: 100_000_000.0 * 1e6,
+        ..token_info_row()
+    }]));
+
+    let info = service(&mock)
+        .get_token_info("pool".to_string(), 10)
+        .await
+        .unwrap();
+    assert!(!info.decimals_defaulted);
+    assert_eq!(info.top10_holders_percent, 10.0);
+}
+
+#[tokio::test]
+async fn token_info_top_n_widens_the_holder_window() {
+    let sql =
+        testing::recorded_query(
+            |db| async move { db.get_token_info("pool".to_string(), 20).await },
+        )
+        .await;
+    assert!(sql.contains("sumIf(x.amount, x.rn <= 20)"));
+    assert!(sql.contains("sumIf(x.amount, x.rn <= 10)"));
+    assert!(sql.contains("WHERE rn <= greatest(10, 20)"));
+
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![TokenInfoRow {
+        top10_amount_raw: 100_000_000.0 * 1e6,
+        top_n: 20,
+        top_n_amount_raw: 150_000_000.0 * 1e6,
+        ..token_info_row()
+    }]));
+    let info = service(&mock)
+        .get_token_info("pool".to_string(), 20)
+        .await
+        .unwrap();
+    assert_eq!(info.top10_holders_percent, 10.0);
+    assert_eq!(info.top_n, 20);
+    assert_eq!(info.top_n_holders_percent, 15.0);
+}
+
+// Mirror of the row `get_token_info_batch` reads: the pool address, then a TokenInfoRow
+#[derive(Serialize, Row)]
+struct TokenInfoBatchRow {
+    pool_address: String,
+    top10_amount_raw: f64,
+    dev_amount_raw: f64,
+    snipers_amount_raw: f64,
+    num_holders: u64,
+    token_supply: f64,
+    decimals: i8,
+    bundlers_amount_raw: f64,
+    has_decimals: bool,
+}
+
+#[tokio::test]
+async fn token_info_batch_matches_single_lookups() {
+    let rows = [
+        TokenInfoRow {
+            top10_amount_raw: 100_000_000.0 * 1e6,
+            top_n_amount_raw: 100_000_000.0 * 1e6,
+            dev_amount_raw: 20_000_000.0 * 1e6,
+            snipers_amount_raw: 5_000_000.0 * 1e6,
+            bundlers_amount_raw: 1_000_000.0 * 1e6,
+            ..token_info_row()
+        },
+        TokenInfoRow {
+            top10_amount_raw: 900_000_000.0 * 1e6,
+            top_n_amount_raw: 900_000_000.0 * 1e6,
+            decimals: 0,
+            has_decimals: false,
+            ..token_info_row()
+        },
+    ];
+
+    let mock = Mock::new();
+    mock.add(handlers::provide(
+        rows.iter()
+            .zip(["PoolA", "PoolB"])
+            .map(|(row, pool)| TokenInfoBatchRow {
+                pool_address: pool.to_string(),
+                top10_amount_raw: row.top10_amount_raw,
+                dev_amount_raw: row.dev_amount_raw,
+                snipers_amount_raw: row.snipers_amount_raw,
+                num_holders: row.num_holders,
+                token_supply: row.token_supply,
+                decimals: row.decimals,
+                bundlers_amount_raw: row.bundlers_amount_raw,
+                has_decimals: row.has_decimals,
+            })
+            .collect::<Vec<_>>(),
+    ));
+    let pools = ["PoolA".to_string(), "PoolB".to_string()];
+    let batch = service(&mock).get_token_info_batch(&pools).await.unwrap();
+
+    for (row, pool) in rows.into_iter().zip(pools) {
+        let mock = Mock::new();
+        mock.add(handlers::provide(vec![row]));
+        let single = service(&mock)
+            .get_token_info(pool.clone(), 10)
+            .await
+            .unwrap();
+        assert_eq!(json!(batch[&pool]), json!(single), "{pool}");
+    }
+}
+
+#[tokio::test]
+async fn batch_pulse_data_takes_one_round_trip() {
+    let mock = Mock::new();
+    let pools: Vec<String> = (0..50).map(|i| format!("pool{i}")).collect();
+    // Only one response is queued, so a per-pool query would fail on the second pool
+    mock.add(handlers::provide(pools.iter().map(|pool| PulseRow {
+        pool_address: pool.clone(),
+        scale_factor: 1e6,
+        ..Default::default()
+    })));
+
+    let pulses = service(&mock).get_batch_pulse_data(&pools).await.unwrap();
+    let returned: Vec<String> = pulses.into_iter().map(|p| p.pair_address).collect();
+    assert_eq!(returned, pools);
+}
+
+#[tokio::test]
+async fn pulse_keeps_pools_whose_token_is_not_indexed() {
+    let sql =
+        testing::recorded_query(
+            |db| async move { db.get_pool_pulse_data("NewPool".to_string()).await },
+        )
+        .await;
+    assert!(sql.contains("LEFT JOIN tok t ON t.mint_address = r.token_base_address"));
+
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![PulseRow {
+        pool_address: "NewPool".to_string(),
+        mint_address: "NewPool-mint".to_string(),
+        top10_amount_raw: 100,
+        scale_factor: 1.0,
+        metadata_pending: true,
+        ..Default::default()
+    }]));
+    let pulse = service(&mock)
+        .get_pool_pulse_data("NewPool".to_string())
+        .await
+        .unwrap();
+    assert!(pulse.metadata_pending);
+    assert_eq!(pulse.token_address, "NewPool-mint");
+    assert_eq!(pulse.token_decimals, 0);
+    // No supply to divide by, so the percentages stay finite
+    assert_eq!(pulse.top10_holders_percent, 0.0);
+}
+
+#[tokio::test]
+async fn pool_pulse_ignores_pool_age() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![PulseRow {
+        pool_address: "OldPool".to_string(),
+        scale_factor: 1e6,
+        ..Default::default()
+    }]));
+    let pulse = service(&mock)
+        .get_pool_pulse_data("OldPool".to_string())
+        .await
+        .unwrap();
+    assert_eq!(pulse.pair_address, "OldPool");
+
+    let sql =
+        testing::recorded_query(
+            |db| async move { db.get_pool_pulse_data("OldPool".to_string()).await },
+        )
+        .await;
+    assert!(!sql.contains("p.created_at >="));
+    let new_pair = testing::recorded_query(|db| async move {
+        db.get_batch_pulse_data(&["OldPool".to_string()]).await
+    })
+    .await;
+    assert!(new_pair.contains("p.created_at >= now() - INTERVAL 1 HOUR"));
+}
+
+#[tokio::test]
+async fn pool_pulse_reads_the_whole_history() {
+    let sql =
+        testing::recorded_query(
+            |db| async move { db.get_pool_pulse_data("OldPool".to_string()).await },
+        )
+        .await;
+    // Swaps, holders and funding older than an hour still count, and migrated pools or
+    // pools without recent volume still get a row
+    assert!(!sql.contains("INTERVAL 1 HOUR"));
+    assert!(sql.contains("a.updated_at >= toDateTime(0)"));
+    assert!(!sql.contains("curve_percentage, 0) < 50"));
+    assert!(sql.contains("LEFT JOIN vol_24h v"));
+
+    let feed = testing::recorded_query(|db| async move {
+        db.get_batch_pulse_data(&["OldPool".to_string()]).await
+    })
+    .await;
+    assert!(feed.contains("a.updated_at >= now() - INTERVAL 1 HOUR"));
+    assert!(feed.contains("WHERE coalesce(pcu.curve_percentage, 0) < 50"));
+}
+
+#[tokio::test]
+async fn pool_pulse_is_not_found_without_a_row() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(Vec::<PulseRow>::new()));
+    let result = service(&mock)
+        .get_pool_pulse_data("Missing".to_string())
+        .await;
+    assert!(matches!(result, Err(ServiceError::NotFound)));
+}
+
+#[tokio::test]
+async fn empty_batch_skips_the_query() {
+    let mock = Mock::new();
+    assert!(
+        service(&mock)
+            .get_batch_pulse_data(&[])
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+// Column layout of the liquidity-lock query
+#[derive(Serialize, Row)]
+struct LiquidityLockRow {
+    pool_address: String,
+    factory: String,
+    initial_quote_reserve: f64,
+    remove_count: u64,
+    removed_quote: f64,
+    event_count: u64,
+    current_quote_reserve: f64,
+    burned_lp_amount: f64,
+}
+
+fn lock_row() -> LiquidityLockRow {
+    LiquidityLockRow {
+        pool_address: "pool".to_string(),
+        factory: "PumpSwap".to_string(),
+        initial_quote_reserve: 85.0,
+        remove_count: 0,
+        removed_quote: 0.0,
+        event_count: 12,
+        current_quote_reserve: 90.0,
+        burned_lp_amount: 0.0,
+    }
+}
+
+async fn lock_status(row: LiquidityLockRow) -> LiquidityLockStatus {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![row]));
+    let status = service(&mock)
+        .get_liquidity_lock_status("pool".to_string())
+        .await;
+    status.unwrap().unwrap().status
+}
+
+#[tokio::test]
+async fn liquidity_lock_status_follows_the_heuristic() {
+    assert_eq!(lock_status(lock_row()).await, LiquidityLockStatus::Locked);
+    assert_eq!(
+        lock_status(LiquidityLockRow {
+            burned_lp_amount: 1e9,
+            remove_count: 1,
+            ..lock_row()
+        })
+        .await,
+        LiquidityLockStatus::Burned
+    );
+    assert_eq!(
+        lock_status(LiquidityLockRow {
+            remove_count: 1,
+            removed_quote: 40.0,
+            ..lock_row()
+        })
+        .await,
+        LiquidityLockStatus::Unlocked
+    );
+    assert_eq!(
+        lock_status(LiquidityLockRow {
+            event_count: 0,
+            ..lock_row()
+        })
+        .await,
+        LiquidityLockStatus::Unknown
+    );
+}
+
+#[tokio::test]
+async fn traded_pools_are_not_locked_without_evidence() {
+    // Plenty of swaps and no REMOVE, but the reserve has grown well past its start
+    assert_eq!(
+        lock_status(LiquidityLockRow {
+            current_quote_reserve: 140.0,
+            ..lock_row()
+        })
+        .await,
+        LiquidityLockStatus::Unknown
+    );
+    // A bonding curve is not locked just for being one
+    assert_eq!(
+        lock_status(LiquidityLockRow {
+            factory: "PumpFun".to_string(),
+            event_count: 0,
+            ..lock_row()
+        })
+        .await,
+        LiquidityLockStatus::Unknown
+    );
+}
+
+#[tokio::test]
+async fn liquidity_lock_is_none_for_unknown_pools() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(Vec::<LiquidityLockRow>::new()));
+    let status = service(&mock)
+        .get_liquidity_lock_status("pool".to_string())
+        .await;
+    assert!(status.unwrap().is_none());
+}
+
+fn dev_swap(swap_type: &str, base_amount: f64) -> DBSwap {
+    DBSwap {
+        swap_type: swap_type.to_string(),
+        base_amount,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn dev_activity_tracks_the_running_holding() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec!["creator".to_string()]));
+    mock.add(handlers::provide(vec![
+        dev_swap("BUY", 1000.0),
+        dev_swap("BUY", 500.0),
+        dev_swap("SELL", -300.0),
+        dev_swap("SELL", -1200.0),
+    ]));
+
+    let response = service(&mock)
+        .get_dev_activity("pool".to_string())
+        .await
+        .unwrap();
+    assert_eq!(response.creator, "creator");
+    let holdings: Vec<f64> = response
+        .activity
+        .iter()
+        .map(|e| e.cumulative_holding)
+        .collect();
+    assert_eq!(holdings, [1000.0, 1500.0, 1200.0, 0.0]);
+}
+
+#[tokio::test]
+async fn dev_activity_is_not_found_for_unknown_pools() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(Vec::<String>::new()));
+    let result = service(&mock).get_dev_activity("pool".to_string()).await;
+    assert!(matches!(result, Err(ServiceError::NotFound)));
+}
+
+// Mirror of the row `get_market_stats` reads
+#[derive(Serialize, Row)]
+struct MarketStatsRow {
+    volume_sol_24h: f64,
+    new_pools_24h: u64,
+    migrations_24h: u64,
+    top_factory: String,
+    top_factory_volume_sol: f64,
+}
+
+#[tokio::test]
+async fn market_stats_aggregate_a_rolling_24h() {
+    let sql = testing::recorded_query(|db| async move { db.get_market_stats().await }).await;
+    // Volume is summed per pool from the 5m buckets of the last 24h, then totalled
+    assert!(sql.contains("FROM pool_report_5m s"), "{sql}");
+    assert!(sql.contains("WHERE s.bucket_start >= now() - INTERVAL 24 HOUR"));
+    assert!(sql.contains("sum(s.buy_volume + s.sell_volume)"));
+    assert!(sql.contains("coalesce(sum(volume_sol), 0) AS Float64) AS volume_sol_24h"));
+    // Pools and migrations are counted by creation time over the same window
+    assert!(sql.contains("WHERE created_at >= now() - INTERVAL 24 HOUR"));
+    assert!(sql.contains("countIf(pre_factory = 'PumpFun' AND factory = 'PumpSwap')"));
+    // The top factory is the one with the most of that volume, ties broken by name
+    assert!(sql.contains("GROUP BY p.factory"));
+    assert!(sql.contains("ORDER BY volume_sol DESC, factory ASC"));
+    assert!(sql.contains("LIMIT 1"));
+}
+
+#[tokio::test]
+async fn market_stats_have_no_top_factory_without_volume() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![MarketStatsRow {
+        volume_sol_24h: 0.0,
+        new_pools_24h: 0,
+        migrations_24h: 0,
+        top_factory: String::new(),
+        top_factory_volume_sol: 0.0,
+    }]));
+
+    let stats = service(&mock).get_market_stats().await.unwrap();
+    assert!(stats.top_factory.is_none());
+}
+
+// Mirror of the row `get_incoming_transfers` reads
+#[derive(Serialize, Row)]
+struct IncomingTransferRow {
+    source: String,
+    amount: f64,
+    hash: String,
+    #[serde(with = "clickhouse::serde::chrono::datetime")]
+    created_at: DateTime<Utc>,
+}
+
+fn transfer(source: &str, amount: f64, secs: i64) -> IncomingTransferRow {
+    IncomingTransferRow {
+        source: source.to_string(),
+        amount,
+        hash: format!("hash-{secs}"),
+        created_at: DateTime::from_timestamp(secs, 0).unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn incoming_transfers_keep_every_funder_in_order() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![
+        transfer("FunderA", 1.5, 1_700_000_000),
+        transfer("FunderB", 0.25, 1_700_000_060),
+        transfer("FunderA", 2.0, 1_700_000_120),
+    ]));
+
+    let transfers = service(&mock)
+        .get_incoming_transfers("wallet".to_string(), 100)
+        .await
+        .unwrap();
+    let funders: Vec<(&str, f64)> = transfers
+        .iter()
+        .map(|t| (t.source.as_str(), t.amount))
+        .collect();
+    assert_eq!(
+        funders,
+        [("FunderA", 1.5), ("FunderB", 0.25), ("FunderA", 2.0)]
+    );
+    assert_eq!(transfers[1].hash, "hash-1700000060");
+    assert_eq!(transfers[2].created_at.timestamp(), 1_700_000_120);
+}
+
+#[tokio::test]
+async fn holder_distribution_buckets_on_lower_bounds() {
+    let mock = Mock::new();
+    // 1%, just under 1%, exactly 0.1% and just under 0.1% of a 10_000 supply
+    mock.add(handlers::provide(vec![100.0f64, 99.0, 10.0, 9.0]));
+    mock.add(handlers::provide(vec![10_000.0f64]));
+
+    let distribution = service(&mock)
+        .get_holder_distribution("mint".to_string())
+        .await
+        .unwrap();
+    assert_eq!(distribution.total_holders, 4);
+    let counts: Vec<(&str, u64)> = distribution
+        .buckets
+        .iter()
+        .map(|b| (b.label.as_str(), b.holder_count))
+        .collect();
+    assert_eq!(counts, [(">1%", 1), ("0.1-1%", 2), ("<0.1%", 1)]);
+    assert_eq!(distribution.buckets[0].holders_percent, 25.0);
+    assert_eq!(distribution.buckets[1].supply_percent, 1.09);
+    assert!(distribution.gini > 0.0 && distribution.gini < 1.0);
+}
+
+#[tokio::test]
+async fn holder_distribution_is_even_for_equal_balances() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![50.0f64, 50.0, 50.0, 50.0]));
+    mock.add(handlers::provide(vec![0.0f64]));
+
+    let distribution = service(&mock)
+        .get_holder_distribution("mint".to_string())
+        .await
+        .unwrap();
+    // Without mint events the supply is what the holders have, 25% each
+    assert_eq!(distribution.buckets[0].holder_count, 4);
+    assert!(distribution.gini.abs() < 1e-9);
+}
+
+// Mirror of `FirstBuyer`, whose fields are private to the models
+#[derive(Serialize, Row)]
+struct FirstBuyerRow {
+    creator: String,
+    slot: i64,
+    base_amount: f64,
+    remaining: Option<f64>,
+    is_sniper: bool,
+}
+
+#[tokio::test]
+async fn first_buyers_flag_snipers_in_the_first_slot() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![
+        FirstBuyerRow {
+            creator: "Sniper".to_string(),
+            slot: 100,
+            base_amount: 5000.0,
+            remaining: Some(0.0),
+            is_sniper: true,
+        },
+        FirstBuyerRow {
+            creator: "Early".to_string(),
+            slot: 101,
+            base_amount: 1200.0,
+            remaining: None,
+            is_sniper: false,
+        },
+    ]));
+
+    let buyers = service(&mock)
+        .get_first_buyers("pool".to_string(), 20)
+        .await
+        .unwrap();
+    assert_eq!(
+        serde_json::to_value(&buyers).unwrap(),
+        serde_json::json!([
+            { "creator": "Sniper", "slot": 100, "base_amount": 5000.0, "remaining": 0.0, "is_sniper": true },
+            { "creator": "Early", "slot": 101, "base_amount": 1200.0, "remaining": null, "is_sniper": false },
+        ])
+    );
+}
+
+#[tokio::test]
+async fn first_buyers_order_by_slot_then_time() {
+    let sql =
+        testing::recorded_query(
+            |db| async move { db.get_first_buyers("pool".to_string(), 20).await },
+        )
+        .await;
+    assert!(sql.contains("ORDER BY b.slot ASC, b.first_buy_at ASC"));
+    assert!(sql.contains("b.slot = f.slot AS is_sniper"));
+}
+
+// Mirror of the row `get_wallet_pnl` reads
+#[derive(Serialize, Row)]
+struct PnlRow {
+    pool_address: String,
+    token_base_address: String,
+    native_spent: f64,
+    native_received: f64,
+    tokens_bought: f64,
+    tokens_sold: f64,
+    remaining_tokens: Option<f64>,
+    price_native: f64,
+}
+
+fn pnl_row(pool: &str, mint: &str, spent: f64, received: f64, remaining: Option<f64>) -> PnlRow {
+    PnlRow {
+        pool_address: pool.to_string(),
+        token_base_address: mint.to_string(),
+        native_spent: spent,
+        native_received: received,
+        tokens_bought: 0.0,
+        tokens_sold: 0.0,
+        remaining_tokens: remaining,
+        price_native: 0.5,
+    }
+}
+
+#[tokio::test]
+async fn wallet_pnl_for_a_fully_exited_wallet_is_realized() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![
+        pnl_row("PoolA", "MintA", 2.0, 3.5, Some(0.0)),
+        pnl_row("PoolB", "MintB", 1.0, 0.25, Some(0.0)),
+    ]));
+
+    let pnl = service(&mock)
+        .get_wallet_pnl("wallet".to_string(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(pnl.pools[0].realized, 1.5);
+    assert_eq!(pnl.pools[1].realized, -0.75);
+    assert_eq!(pnl.total_realized, 0.75);
+    assert_eq!(pnl.total_unrealized, 0.0);
+    assert_eq!(pnl.total_pnl, 0.75);
+}
+
+#[tokio::test]
+async fn wallet_pnl_values_a_held_mint_once() {
+    let mock = Mock::new();
+    // Same mint bought on PumpFun and again after migration; latest pool first
+    mock.add(handlers::provide(vec![
+        pnl_row("PumpSwapPool", "MintA", 1.0, 0.0, Some(10.0)),
+        pnl_row("PumpFunPool", "MintA", 2.0, 1.0, Some(10.0)),
+    ]));
+
+    let pnl = service(&mock)
+        .get_wallet_pnl("wallet".to_string(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(pnl.pools[0].unrealized, Some(5.0));
+    assert_eq!(pnl.pools[1].remaining_tokens, Some(0.0));
+    assert_eq!(pnl.total_realized, -2.0);
+    assert_eq!(pnl.total_unrealized, 5.0);
+    assert_eq!(pnl.total_pnl, 3.0);
+}
+
+#[tokio::test]
+async fn wallet_pnl_token_amounts_share_the_remaining_scale() {
+    let mock = Mock::new();
+    // 6-decimal token: 1000 bought for 2 SOL, 600 sold for 1.5 SOL, 400 still held
+    mock.add(handlers::provide(vec![PnlRow {
+        tokens_bought: 1000.0,
+        tokens_sold: 600.0,
+        ..pnl_row("PoolA", "MintA", 2.0, 1.5, Some(400.0))
+    }]));
+
+    let pnl = service(&mock)
+        .get_wallet_pnl("wallet".to_string(), None, None)
+        .await
+        .unwrap();
+    let pool = &pnl.pools[0];
+    assert_eq!(
+        Some(pool.tokens_bought - pool.tokens_sold),
+        pool.remaining_tokens
+    );
+    assert_eq!(pool.avg_buy_price, Some(0.002));
+    assert_eq!(pool.avg_sell_price, Some(0.0025));
+
+    let sql = testing::recorded_query(|db| async move {
+        db.get_wallet_pnl("wallet".to_string(), None, None).await
+    })
+    .await;
+    assert!(sql.contains("sumIf(abs(base_amount), swap_type = 'BUY')"));
+    assert!(sql.contains("a.amount / pow(10, coalesce(t.decimals, ti.decimals))"));
+}
+
+#[tokio::test]
+async fn holdings_with_unknown_decimals_are_not_valued() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![
+        pnl_row("PoolA", "MintA", 1.0, 0.0, Some(10.0)),
+        pnl_row("PoolB", "MintB", 1.0, 0.0, None),
+    ]));
+    let pnl = service(&mock)
+        .get_wallet_pnl("wallet".to_string(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(pnl.pools[1].unrealized, None);
+    assert_eq!(pnl.total_unrealized, 5.0);
+
+    // Every holder amount takes decimals from tokens, then the mint's initialization,
+    // and is NULL without either
+    let sqls = [
+        testing::recorded_query(|db| async move {
+            db.get_wallet_pnl("wallet".to_string(), None, None).await
+        })
+        .await,
+        testing::recorded_query(|db| async move { db.get_top_traders("pool".to_string()).await })
+            .await,
+        testing::recorded_query(
+            |db| async move { db.get_first_buyers("pool".to_string(), 20).await },
+        )
+        .await,
+    ];
+    for sql in sqls {
+        assert!(
+            sql.contains("LEFT JOIN tokens t ON t.mint_address"),
+            "{sql}"
+        );
+        assert!(
+            sql.contains("if(isNotNull(t.decimals) OR notEmpty(ti.mint_address)"),
+            "{sql}"
+        );
+        assert!(!sql.contains("coalesce(ti.decimals, 0)"), "{sql}");
+    }
+}
+
+#[tokio::test]
+async fn wallet_pnl_average_prices_are_null_without_trades_on_that_side() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![PnlRow {
+        tokens_bought: 1000.0,
+        ..pnl_row("PoolA", "MintA", 2.0, 0.0, 1000.0)
+    }]));
+
+    let pnl = service(&mock)
+        .get_wallet_pnl("wallet".to_string(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(pnl.pools[0].avg_sell_price, None);
+}
+
+#[tokio::test]
+async fn wallet_pnl_window_bounds_the_counted_swaps() {
+    let sql = testing::recorded_query(|db| async move {
+        db.get_wallet_pnl(
+            "wallet".to_string(),
+            Some(1_700_000_000),
+            Some(1_700_600_000),
+        )
+        .await
+    })
+    .await;
+    assert!(sql.contains(
+        "WHERE swap_type IN ('BUY', 'SELL') AND created_at >= toDateTime(1700000000) \
+         AND created_at <= toDateTime(1700600000)"
+    ));
+
+    let sql = testing::recorded_query(|db| async move {
+        db.get_wallet_pnl("wallet".to_string(), None, None).await
+    })
+    .await;
+    assert!(!sql.contains("toDateTime("));
+}
+
+#[tokio::test]
+async fn candles_fall_back_to_swaps_without_an_interval_table() {
+    let mock = Mock::new();
+    // JSONEachRow lines as the swaps aggregation returns them, 15s apart
+    let lines = concat!(
+        r#"{"timestamp":"2024-01-01 00:00:00","open":1,"high":2,"low":1,"close":2,"volume_base":10,"volume_quote":0.5,"trades":2}"#,
+        "\n",
+        r#"{"timestamp":"2024-01-01 00:00:15","open":2,"high":2,"low":1.5,"close":1.5,"volume_base":4,"volume_quote":0.2,"trades":1}"#,
+        "\n",
+    );
+    mock.add(handlers::provide(lines.as_bytes().to_vec()));
+
+    let db = ClickhouseService {
+        candle_intervals: Arc::new(OnceCell::from(vec![Interval::OneMinute])),
+        ..service(&mock)
+    };
+    let candles = db
+        .get_candlestick(
+            "pool".to_string(),
+            &Interval::FifteenSeconds,
+            0,
+            i64::MAX,
+            100,
+        )
+        .await
+        .unwrap();
+    let buckets: Vec<&str> = candles
+        .iter()
+        .map(|c| c["timestamp"].as_str().unwrap())
+        .collect();
+    assert_eq!(buckets, ["2024-01-01 00:00:00", "2024-01-01 00:00:15"]);
+    assert_eq!(candles[1]["close"], serde_json::json!(1.5));
+}
+
+#[tokio::test]
+async fn failed_candle_discovery_is_retried() {
+    let mock = Mock::new();
+    let db = ClickhouseService {
+        candle_intervals: Arc::new(OnceCell::new()),
+        ..service(&mock)
+    };
+    let candle = concat!(
+        r#"{"timestamp":"2024-01-01 00:00:00","open":1,"high":2,"low":1,"close":2,"volume_base":10,"volume_quote":0.5,"trades":2}"#,
+        "\n",
+    );
+
+    // Discovery fails, so the candles are built from swaps rather than guessed from a table
+    mock.add(handlers::exception(159));
+    mock.add(handlers::provide(candle.as_bytes().to_vec()));
+    let candles = db
+        .get_candlestick("pool".to_string(), &Interval::OneMinute, 0, i64::MAX, 100)
+        .await
+        .unwrap();
+    assert_eq!(candles.len(), 1);
+    assert!(db.candle_intervals.get().is_none());
+
+    // The next request discovers the tables and reads candles_1m
+    mock.add(handlers::provide(vec!["candles_1m".to_string()]));
+    mock.add(handlers::provide(candle.as_bytes().to_vec()));
+    let candles = db
+        .get_candlestick("pool".to_string(), &Interval::OneMinute, 0, i64::MAX, 100)
+        .await
+        .unwrap();
+    assert_eq!(candles.len(), 1);
+    assert_eq!(
+        db.candle_intervals.get().map(Vec::as_slice),
+        Some(&[Interval::OneMinute][..])
+    );
+}
+
+#[tokio::test]
+async fn candle_query_errors_are_returned_not_panicked() {
+    let mock = Mock::new();
+    // UNKNOWN_TABLE, e.g. swaps not created yet
+    mock.add(handlers::exception(60));
+    let db = ClickhouseService {
+        candle_intervals: Arc::new(OnceCell::from(Vec::new())),
+        ..service(&mock)
+    };
+    let result = db
+        .get_candlestick("pool".to_string(), &Interval::FifteenSeconds, 0, 1, 100)
+        .await;
+    assert!(matches!(result, Err(ServiceError::Db(_))), "{result:?}");
+
+    mock.add(handlers::provide(b"not json\n".to_vec()));
+    let result = db
+        .get_candlestick("pool".to_string(), &Interval::FifteenSeconds, 0, 1, 100)
+        .await;
+    assert!(matches!(result, Err(ServiceError::Db(_))), "{result:?}");
+}
+
+#[tokio::test]
+async fn candle_table_errors_are_returned_not_panicked() {
+    let mock = Mock::new();
+    mock.add(handlers::exception(60));
+    let db = ClickhouseService {
+        candle_intervals: Arc::new(OnceCell::from(vec![Interval::OneMinute])),
+        ..service(&mock)
+    };
+    let result = db
+        .get_candlestick("pool".to_string(), &Interval::OneMinute, 0, 1, 100)
+        .await;
+    assert!(matches!(result, Err(ServiceError::Db(_))), "{result:?}");
+}
+
+fn pair_info_row(pool_address: &str) -> PairInfoRow {
+    PairInfoRow {
+        pool_address: pool_address.to_string(),
+        pool_base_address: String::new(),
+        pool_quote_address: String::new(),
+        token_base_address: format!("{pool_address}-mint"),
+        token_quote_address: String::new(),
+        creator: String::new(),
+        hash: String::new(),
+        factory: "PumpFun".to_string(),
+        pre_factory: None,
+        reversed: false,
+        curve_percentage: 0.0,
+        initial_token_base_reserve: 0.0,
+        initial_token_quote_reserve: 0.0,
+        slot: 0,
+        metadata: String::new(),
+        created_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        token_hash: String::new(),
+        mint_address: format!("{pool_address}-mint"),
+        name: String::new(),
+        symbol: String::new(),
+        decimals: 6,
+        uri: String::new(),
+        supply: 0.0,
+        token_slot: 0,
+        mint_authority: None,
+        freeze_authority: None,
+        image: None,
+        twitter: None,
+        telegram: None,
+        website: None,
+        discord: None,
+        program_id: String::new(),
+        metadata_pending: false,
+    }
+}
+
+// Mirror of the row `get_open_trading` reads
+#[derive(Serialize, Row)]
+struct OpenTradingRow {
+    pool_address: String,
+    #[serde(with = "clickhouse::serde::chrono::datetime")]
+    open_trading: DateTime<Utc>,
+}
+
+// Mirror of the row `get_price_window_24h` reads
+#[derive(Serialize, Row)]
+struct PriceWindowRow {
+    pool_address: String,
+    open_price: f64,
+    close_price: f64,
+}
+
+#[tokio::test]
+async fn pair_info_batch_leaves_out_unknown_pools() {
+    let mock = Mock::new();
+    // ClickHouse only returns rows for the pools that exist
+    mock.add(handlers::provide(vec![
+        pair_info_row("PoolA"),
+        pair_info_row("PoolC"),
+    ]));
+    mock.add(handlers::provide(Vec::<OpenTradingRow>::new()));
+    mock.add(handlers::provide(Vec::<PriceWindowRow>::new()));
+
+    let addresses = ["PoolA", "PoolB", "PoolC"].map(String::from);
+    let pairs = service(&mock)
+        .get_pair_info_batch(&addresses)
+        .await
+        .unwrap();
+    let mut found: Vec<&str> = pairs.keys().map(String::as_str).collect();
+    found.sort();
+    assert_eq!(found, ["PoolA", "PoolC"]);
+    assert_eq!(pairs["PoolC"].base_token.mint_address, "PoolC-mint");
+}
+
+#[tokio::test]
+async fn pair_info_returns_pools_whose_token_is_not_indexed() {
+    let sql = testing::recorded_query(|db| async move {
+        db.get_pair_info_batch(&["NewPool".to_string()]).await
+    })
+    .await;
+    assert!(sql.contains("LEFT JOIN tokens t"));
+    assert!(!sql.contains("INNER JOIN tokens"));
+
+    let mock = Mock::new();
+    // What the LEFT JOIN yields without a tokens row: default token columns
+    mock.add(handlers::provide(vec![PairInfoRow {
+        name: String::new(),
+        decimals: 0,
+        metadata_pending: true,
+        ..pair_info_row("NewPool")
+    }]));
+    mock.add(handlers::provide(Vec::<OpenTradingRow>::new()));
+    mock.add(handlers::provide(Vec::<PriceWindowRow>::new()));
+
+    let pair = service(&mock)
+        .get_pair_info("NewPool".to_string())
+        .await
+        .unwrap();
+    assert!(pair.metadata_pending);
+    assert_eq!(pair.base_token.mint_address, "NewPool-mint");
+    assert_eq!(pair.base_token.decimals, 0);
+}
+
+fn usdc_pool(pool_address: &str) -> DBPool {
+    DBPool {
+        creator: String::new(),
+        pool_address: pool_address.to_string(),
+        pool_base_address: String::new(),
+        pool_quote_address: String::new(),
+        factory: "PumpSwap".to_string(),
+        pre_factory: None,
+        token_base_address: format!("{pool_address}-mint"),
+        token_quote_address: USDC_TOKEN.address.to_string(),
+        initial_token_base_reserve: 0.0,
+        initial_token_quote_reserve: 0.0,
+        slot: 0,
+        reversed: false,
+        created_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        hash: String::new(),
+        metadata: String::new(),
+    }
+}
+
+#[tokio::test]
+async fn pair_info_and_search_return_the_same_quote_token() {
+    use axum::{
+        Json,
+        extract::{Query, State},
+        http::Uri,
+    };
+
+    use crate::routes::{
+        extractors::{ApiQuery, PoolAddress},
+        get_pair_info::get_pair_info,
+        search::search_pools,
+    };
+
+    const POOL: &str = "So11111111111111111111111111111111111111112";
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![PairInfoRow {
+        token_quote_address: USDC_TOKEN.address.to_string(),
+        ..pair_info_row(POOL)
+    }]));
+    mock.add(handlers::provide(Vec::<OpenTradingRow>::new()));
+    mock.add(handlers::provide(Vec::<PriceWindowRow>::new()));
+    let Json(pair_info) = get_pair_info(PoolAddress(POOL.to_string()), State(service(&mock)))
+        .await
+        .unwrap();
+
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![usdc_pool(POOL)]));
+    let pair = PairInfo::from(pair_info_row(POOL));
+    mock.add(handlers::provide(vec![pair.base_token]));
+    mock.add(handlers::provide(vec![DBSwap::default()]));
+    mock.add(handlers::provide(vec![report_bucket(0.0, 0.0)]));
+    let uri: Uri = format!("/pools?search={POOL}").parse().unwrap();
+    let search = ApiQuery(Query::try_from_uri(&uri).unwrap().0);
+    let Json(found) = search_pools(State(service(&mock)), search).await.unwrap();
+
+    let expected = json!(QuoteTokenData::from(&USDC_TOKEN));
+    assert_eq!(pair_info["quote_token"], expected);
+    assert_eq!(found["data"]["quote_token"], expected);
+    assert_eq!(expected["logo"], USDC_TOKEN.logo);
+    assert_eq!(expected["decimals"], USDC_TOKEN.decimals);
+}
+
+#[tokio::test]
+async fn empty_pair_info_batch_skips_the_query() {
+    let mock = Mock::new();
+    let pairs = service(&mock).get_pair_info_batch(&[]).await.unwrap();
+    assert!(pairs.is_empty());
+}
+
+#[tokio::test]
+async fn open_trading_is_the_first_swap_time_when_the_pool_traded() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![
+        pair_info_row("PoolA"),
+        pair_info_row("PoolB"),
+    ]));
+    mock.add(handlers::provide(vec![OpenTradingRow {
+        pool_address: "PoolA".to_string(),
+        open_trading: DateTime::from_timestamp(1_700_000_060, 0).unwrap(),
+    }]));
+    mock.add(handlers::provide(Vec::<PriceWindowRow>::new()));
+
+    let addresses = ["PoolA", "PoolB"].map(String::from);
+    let pairs = service(&mock)
+        .get_pair_info_batch(&addresses)
+        .await
+        .unwrap();
+    assert_eq!(
+        pairs["PoolA"].open_trading.map(|t| t.timestamp()),
+        Some(1_700_000_060)
+    );
+    assert_eq!(pairs["PoolB"].open_trading, None);
+}
+
+#[tokio::test]
+async fn price_change_24h_is_relative_to_the_first_swap() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![
+        pair_info_row("Doubled"),
+        PairInfoRow {
+            reversed: true,
+            ..pair_info_row("Reversed")
+        },
+        pair_info_row("Quiet"),
+    ]));
+    mock.add(handlers::provide(Vec::<OpenTradingRow>::new()));
+    mock.add(handlers::provide(vec![
+        PriceWindowRow {
+            pool_address: "Doubled".to_string(),
+            open_price: 0.000_01,
+            close_price: 0.000_02,
+        },
+        // Reversed pools index the inverse price, so halving here means the token doubled
+        PriceWindowRow {
+            pool_address: "Reversed".to_string(),
+            open_price: 0.5,
+            close_price: 0.25,
+        },
+    ]));
+
+    let addresses = ["Doubled", "Reversed", "Quiet"].map(String::from);
+    let pairs = service(&mock)
+        .get_pair_info_batch(&addresses)
+        .await
+        .unwrap();
+    for pool in ["Doubled", "Reversed"] {
+        let change = pairs[pool].price_change_24h_percent.unwrap();
+        assert!((change - 100.0).abs() < 1e-9, "{pool}: {change}");
+    }
+    assert_eq!(pairs["Quiet"].price_change_24h_percent, None);
+}
+
+// Mirror of the row `get_current_price` reads
+#[derive(Serialize, Row)]
+struct CurrentPriceRow {
+    price_sol: f64,
+    reversed: bool,
+}
+
+#[tokio::test]
+async fn current_price_is_quote_per_base_token() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![CurrentPriceRow {
+        price_sol: 0.000_02,
+        reversed: false,
+    }]));
+    // Reversed pools index the inverse price
+    mock.add(handlers::provide(vec![CurrentPriceRow {
+        price_sol: 4.0,
+        reversed: true,
+    }]));
+
+    let db = service(&mock);
+    assert_eq!(db.get_current_price("Straight").await.unwrap(), 0.000_02);
+    assert_eq!(db.get_current_price("Reversed").await.unwrap(), 0.25);
+}
+
+#[tokio::test]
+async fn trending_pools_keep_the_momentum_order() {
+    let mock = Mock::new();
+    // The ranking query puts the pool with a volume spike ahead of the flat one...
+    mock.add(handlers::provide(vec![
+        "Spike".to_string(),
+        "Flat".to_string(),
+    ]));
+    // ...while the pulse rows come back in whatever order ClickHouse joins them
+    mock.add(handlers::provide(["Flat", "Spike"].map(|pool| PulseRow {
+        pool_address: pool.to_string(),
+        scale_factor: 1e6,
+        ..Default::default()
+    })));
+
+    let pools = service(&mock).get_trending_pools(20, None).await.unwrap();
+    let ranked: Vec<&str> = pools.iter().map(|p| p.pair_address.as_str()).collect();
+    assert_eq!(ranked, ["Spike", "Flat"]);
+}
+
+#[tokio::test]
+async fn trending_pools_rank_by_recent_share_of_volume() {
+    let sql = testing::recorded_query(|db| async move {
+        db.get_trending_pools(20, Some("PumpFun".to_string())).await
+    })
+    .await;
+    assert!(sql.contains("ORDER BY v.volume_5m / v.volume_1h DESC"));
+    assert!(sql.contains("AND p.factory = 'PumpFun'"));
+}
+
+fn report_bucket(open_price: f64, close_price: f64) -> PoolReport {
+    PoolReport {
+        pool_address: "UsdcPool".to_string(),
+        bucket_start: Utc::now(),
+        buy_volume: 2e9,
+        buy_count: 1,
+        sell_volume: 0.0,
+        sell_count: 0,
+        unique_traders: 1,
+        unique_buyers: 1,
+        unique_sellers: 0,
+        open_price,
+        close_price,
+    }
+}
+
+#[tokio::test]
+async fn pool_and_token_data_rescale_usdc_quote_values() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![usdc_pool("UsdcPool")]));
+    mock.add(handlers::provide(vec![
+        PairInfo::from(pair_info_row("UsdcPool")).base_token,
+    ]));
+    mock.add(handlers::provide(vec![DBSwap {
+        quote_reserve: 2.0,
+        ..Default::default()
+    }]));
+    mock.add(handlers::provide(vec![report_bucket(0.0, 0.0)]));
+
+    let data = service(&mock)
+        .get_pool_and_token_data("UsdcPool".to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(data.quote_liquidity, 2000.0);
+    // 2 SOL-scaled units of buy volume, in USDC
+    assert_eq!(data.volume_quote, 2e12);
+}
+
+#[tokio::test]
+async fn pool_and_token_data_orient_reversed_pools() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![DBPool {
+        reversed: true,
+        ..usdc_pool("UsdcPool")
+    }]));
+    let token = PairInfo::from(pair_info_row("UsdcPool")).base_token;
+    let supply = token.supply;
+    mock.add(handlers::provide(vec![token]));
+    mock.add(handlers::provide(vec![DBSwap {
+        base_reserve: 3.0,
+        quote_reserve: 500.0,
+        price_sol: 4.0,
+        ..Default::default()
+    }]));
+    mock.add(handlers::provide(vec![report_bucket(0.0, 0.0)]));
+
+    let data = service(&mock)
+        .get_pool_and_token_data("UsdcPool".to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(data.base_liquidity, 500.0);
+    assert_eq!(data.quote_liquidity, 3000.0);
+    assert!((data.marketcap_sol - 0.25 * 1000.0 * supply).abs() < 1e-6);
+}
+
+fn usdc_quote(reversed: bool) -> PoolQuoteRow {
+    PoolQuoteRow {
+        token_quote_address: USDC_TOKEN.address.to_string(),
+        reversed,
+    }
+}
+
+#[tokio::test]
+async fn pool_report_carries_the_quote_denomination() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![report_bucket(1.0, 1.0)]));
+    mock.add(handlers::provide(vec![usdc_quote(false)]));
+
+    let report = service(&mock)
+        .get_pool_report("UsdcPool".to_string(), ReportType::OneMinute)
+        .await
+        .unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].quote_mint, USDC_TOKEN.address);
+    assert_eq!(report[0].quote_decimals, USDC_TOKEN.decimals);
+}
+
+#[tokio::test]
+async fn pool_report_price_change_is_relative_to_the_open() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![
+        report_bucket(2.0, 3.0),
+        report_bucket(0.0, 3.0),
+    ]));
+    mock.add(handlers::provide(vec![usdc_quote(false)]));
+
+    let report = service(&mock)
+        .get_pool_report("UsdcPool".to_string(), ReportType::OneMinute)
+        .await
+        .unwrap();
+    assert_eq!(report[0].price_change_percent, Some(50.0));
+    assert_eq!(report[1].price_change_percent, None);
+}
+
+#[tokio::test]
+async fn pool_report_price_change_follows_reversed_pools() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![report_bucket(2.0, 4.0)]));
+    mock.add(handlers::provide(vec![usdc_quote(true)]));
+
+    let report = service(&mock)
+        .get_pool_report("UsdcPool".to_string(), ReportType::OneMinute)
+        .await
+        .unwrap();
+    // The indexed price doubled, so the token's price in the quote halved
+    assert_eq!(report[0].price_change_percent, Some(-50.0));
+}
+
+// Mirror of the row `get_curve_history` reads
+#[derive(Serialize, Row)]
+struct CurveRow {
+    curve_percentage: f32,
+    #[serde(with = "clickhouse::serde::chrono::datetime")]
+    updated_at: DateTime<Utc>,
+}
+
+#[tokio::test]
+async fn curve_history_is_ordered_and_monotonic() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(
+        [
+            (12.5, 1_700_000_000),
+            (40.0, 1_700_000_060),
+            (100.0, 1_700_000_120),
+        ]
+        .map(|(curve_percentage, secs)| CurveRow {
+            curve_percentage,
+            updated_at: DateTime::from_timestamp(secs, 0).unwrap(),
+        }),
+    ));
+
+    let points = service(&mock)
+        .get_curve_history("pool".to_string(), 5_000)
+        .await
+        .unwrap();
+    assert_eq!(points.len(), 3);
+    assert!(points.windows(2).all(|w| {
+        w[0].updated_at < w[1].updated_at && w[0].curve_percentage <= w[1].curve_percentage
+    }));
+
+    let sql = testing::recorded_query(|db| async move {
+        db.get_curve_history("pool".to_string(), 5_000).await
+    })
+    .await;
+    assert!(sql.contains("ORDER BY updated_at ASC"));
+    assert!(sql.contains("LIMIT 5000"));
+}
+
+#[tokio::test]
+async fn token_search_ranks_an_exact_symbol_first() {
+    let sql =
+        testing::recorded_query(|db| async move { db.search_tokens("sol".to_string()).await })
+            .await;
+    // "sol" matching symbol SOL exactly is tier 0, ahead of SOLANA (prefix) or xSOLx
+    let order_by = &sql[sql.find("ORDER BY").unwrap()..];
+    let first_tier = order_by
+        .lines()
+        .map(str::trim)
+        .find(|line| line.ends_with(", 0,"))
+        .unwrap();
+    assert_eq!(
+        first_tier,
+        "lower(COALESCE(t.symbol, '')) = lower({term:String}), 0,"
+    );
+    assert!(order_by.contains("length(COALESCE(t.symbol, '')) ASC"));
+}
+
+fn holder(address: &str, amount: f64) -> HolderResponse {
+    HolderResponse {
+        address: address.to_string(),
+        account: format!("{address}-ata"),
+        mint: "mint".to_string(),
+        decimals: 6,
+        amount,
+        delegated_amount: 0,
+        is_sniper: false,
+        is_bundler: false,
+    }
+}
+
+#[tokio::test]
+async fn holders_total_counts_past_the_limit() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![
+        holder("WalletA", 500.0),
+        holder("WalletB", 300.0),
+    ]));
+    mock.add(handlers::provide(vec![12_340_u64]));
+
+    let list = service(&mock)
+        .get_holders("mint".to_string(), 2)
+        .await
+        .unwrap();
+    assert_eq!(list.holders.len(), 2);
+    assert!(list.total >= list.holders.len() as u64);
+
+    let sql =
+        testing::recorded_query(|db| async move { db.get_holders("mint".to_string(), 2).await })
+            .await;
+    assert!(sql.contains("LIMIT 2"));
+}
+
+#[tokio::test]
+async fn holders_flag_early_buyers_of_the_mints_pools() {
+    let sql =
+        testing::recorded_query(|db| async move { db.get_holders("mint".to_string(), 50).await })
+            .await;
+    // Early buyers are taken from the mint's own pools only...
+    assert!(sql.contains("WHERE token_base_address = 'mint'"), "{sql}");
+    assert!(sql.contains("INNER JOIN pool_slots ps ON s.pool_address = ps.pool_address"));
+    // ...and only from buys in the creation slot or the one after
+    assert!(sql.contains("WHERE s.swap_type = 'BUY'"));
+    assert!(sql.contains("AND (s.slot = ps.slot OR s.slot = ps.slot + 1)"));
+    assert!(sql.contains("max(s.slot = ps.slot + 1) AS is_sniper"));
+    assert!(sql.contains("max(s.slot = ps.slot) AS is_bundler"));
+    assert!(sql.contains("GROUP BY s.creator"));
+    // Every holder is kept; only those among the early buyers are flagged
+    assert!(sql.contains("LEFT JOIN early_buyers eb ON eb.owner = accounts.owner"));
+    assert!(sql.contains("COALESCE(eb.is_sniper, false) as is_sniper"));
+    assert!(sql.contains("COALESCE(eb.is_bundler, false) as is_bundler"));
+}
+
+#[tokio::test]
+async fn recent_pools_bind_the_window_and_limit() {
+    let sql = testing::recorded_query(|db| async move { db.get_recent_pools(6, 5).await }).await;
+    assert!(
+        sql.contains("created_at >= now() - toIntervalHour(6)"),
+        "{sql}"
+    );
+    assert!(sql.contains("ORDER BY created_at DESC"));
+    assert!(sql.contains("LIMIT 5"));
+}
+
+#[tokio::test]
+async fn factory_stats_are_one_row_per_factory() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![
+        FactoryStat {
+            factory: "PumpSwap".to_string(),
+            pools_created: 3,
+            volume_sol: 250.5,
+        },
+        FactoryStat {
+            factory: "PumpFun".to_string(),
+            pools_created: 40,
+            volume_sol: 120.0,
+        },
+    ]));
+    let stats = service(&mock).get_factory_stats(24).await.unwrap();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[1].factory, "PumpFun");
+    assert_eq!(stats[1].pools_created, 40);
+
+    let sql = testing::recorded_query(|db| async move { db.get_factory_stats(6).await }).await;
+    // Pool counts and volume are each windowed, then summed per factory
+    assert_eq!(sql.matches("toIntervalHour(6)").count(), 2, "{sql}");
+    assert!(sql.contains("UNION ALL"));
+    assert!(
+        sql.trim_end()
+            .ends_with("ORDER BY volume_sol DESC, factory ASC")
+    );
+}
+
+// Mirror of the row `get_holders_history` reads
+#[derive(Serialize, Row)]
+struct HolderCountRow {
+    #[serde(with = "clickhouse::serde::chrono::datetime")]
+    bucket: DateTime<Utc>,
+    new_holders: u64,
+    holders: u64,
+}
+
+#[tokio::test]
+async fn holders_history_is_oldest_first_with_a_running_total() {
+    let mock = Mock::new();
+    // The query takes the most recent buckets, so they arrive newest first
+    mock.add(handlers::provide(
+        [
+            (1_700_007_200, 2, 10),
+            (1_700_003_600, 5, 8),
+            (1_700_000_000, 3, 3),
+        ]
+        .map(|(secs, new_holders, holders)| HolderCountRow {
+            bucket: DateTime::from_timestamp(secs, 0).unwrap(),
+            new_holders,
+            holders,
+        }),
+    ));
+
+    let points = service(&mock)
+        .get_holders_history("mint".to_string(), 3600, 168)
+        .await
+        .unwrap();
+    assert_eq!(points.len(), 3);
+    assert!(
+        points.windows(2).all(|w| {
+            w[0].bucket < w[1].bucket && w[1].holders == w[0].holders + w[1].new_holders
+        })
+    );
+
+    let sql = testing::recorded_query(|db| async move {
+        db.get_holders_history("mint".to_string(), 3600, 168).await
+    })
+    .await;
+    assert!(
+        sql.contains("toStartOfInterval(first_seen, toIntervalSecond(3600))"),
+        "{sql}"
+    );
+    assert!(sql.contains("LIMIT 168"));
+}
+
+#[tokio::test]
+async fn whale_trades_only_scan_swaps_above_the_threshold() {
+    let sql =
+        testing::recorded_query(|db| async move { db.get_whale_trades(25.5, 50).await }).await;
+    assert!(sql.contains("abs(quote_amount) > 25.5"), "{sql}");
+    assert!(
+        sql.contains(&format!(
+            "WHERE token_quote_address = '{}' AND NOT reversed",
+            SOL_TOKEN.address
+        )),
+        "{sql}"
+    );
+    assert!(sql.contains("created_at >= now() - INTERVAL 24 HOUR"));
+    assert!(sql.contains("LIMIT 50"));
+}
+
+#[tokio::test]
+async fn whale_trades_below_the_threshold_are_excluded() {
+    let trade = |hash: &str, quote_amount: f64| WhaleTradeRow {
+        hash: hash.to_string(),
+        pool_address: "pool".to_string(),
+        trader: "trader".to_string(),
+        swap_type: "SELL".to_string(),
+        base_amount: 1.0,
+        quote_amount,
+        price_sol: 1.0,
+        slot: 1,
+        created_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        token_address: "mint".to_string(),
+        name: String::new(),
+        symbol: String::new(),
+        image: None,
+    };
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![
+        trade("whale", -30.0),
+        trade("at", 25.0),
+        trade("minnow", 10.0),
+    ]));
+
+    let trades = service(&mock).get_whale_trades(25.0, 50).await.unwrap();
+    let hashes: Vec<&str> = trades.iter().map(|t| t.hash.as_str()).collect();
+    assert_eq!(hashes, ["whale"]);
+}
+
+// Mirror of the row `get_creator_pools` reads
+#[derive(Serialize, Row)]
+struct CreatorPoolRow {
+    pool_address: String,
+    token_base_address: String,
+    token_quote_address: String,
+    factory: String,
+    pre_factory: Option<String>,
+    #[serde(with = "clickhouse::serde::chrono::datetime")]
+    created_at: DateTime<Utc>,
+    curve_percentage: f32,
+    migrated: bool,
+}
+
+fn creator_pool(pool_address: &str, factory: &str, migrated: bool) -> CreatorPoolRow {
+    CreatorPoolRow {
+        pool_address: pool_address.to_string(),
+        token_base_address: format!("{pool_address}-mint"),
+        token_quote_address: SOL_TOKEN.address.to_string(),
+        factory: factory.to_string(),
+        pre_factory: (factory == "PumpSwap").then(|| "PumpFun".to_string()),
+        created_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        curve_percentage: 100.0,
+        migrated,
+    }
+}
+
+#[tokio::test]
+async fn creator_pools_list_every_pool_of_a_prolific_creator() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![
+        creator_pool("PoolC", "PumpSwap", true),
+        creator_pool("PoolB", "PumpFun", true),
+        creator_pool("PoolA", "PumpFun", false),
+    ]));
+    let pools = service(&mock)
+        .get_creator_pools("Creator".to_string(), 100)
+        .await
+        .unwrap();
+    let addresses: Vec<&str> = pools.iter().map(|p| p.pool_address.as_str()).collect();
+    assert_eq!(addresses, ["PoolC", "PoolB", "PoolA"]);
+    assert!(pools[1].migrated && !pools[2].migrated);
+
+    let mock = Mock::new();
+    mock.add(handlers::provide(Vec::<CreatorPoolRow>::new()));
+    let pools = service(&mock)
+        .get_creator_pools("Unknown".to_string(), 100)
+        .await
+        .unwrap();
+    assert!(pools.is_empty());
+
+    let sql = testing::recorded_query(|db| async move {
+        db.get_creator_pools("Creator".to_string(), 100).await
+    })
+    .await;
+    assert_eq!(sql.matches("creator = 'Creator'").count(), 3, "{sql}");
+    assert!(sql.contains("LIMIT 100"));
+}
+
+// Mirror of the row `get_creator_migrations` reads
+#[derive(Serialize, Row)]
+struct MigrationRow {
+    migration_count: u64,
+    pool_addresses: Vec<String>,
+}
+
+#[tokio::test]
+async fn pool_exists_reports_the_factory_and_trades() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![ExistsRow {
+        factory: "PumpFun".to_string(),
+        has_trades: true,
+    }]));
+    let exists = service(&mock)
+        .get_pool_exists("Pool".to_string())
+        .await
+        .unwrap();
+    assert!(exists.exists);
+    assert!(exists.has_trades);
+    assert_eq!(exists.factory.as_deref(), Some("PumpFun"));
+
+    let sql =
+        testing::recorded_query(|db| async move { db.get_pool_exists("Pool".to_string()).await })
+            .await;
+    // Both lookups stop at the first row
+    assert!(sql.contains("SELECT 1 FROM swaps WHERE pool_address = 'Pool' LIMIT 1"));
+    assert!(sql.trim_end().ends_with("LIMIT 1"));
+}
+
+#[tokio::test]
+async fn pool_exists_is_false_for_a_random_address() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(Vec::<ExistsRow>::new()));
+    let exists = service(&mock)
+        .get_pool_exists("Random".to_string())
+        .await
+        .unwrap();
+    assert!(!exists.exists);
+    assert!(!exists.has_trades);
+    assert_eq!(exists.factory, None);
+}
+
+// Mirror of the row `get_token_authority` reads
+#[derive(Serialize, Row)]
+struct AuthorityRow {
+    mint_authority: Option<String>,
+    freeze_authority: Option<String>,
+}
+
+#[tokio::test]
+async fn renounced_authorities_are_null() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![AuthorityRow {
+        mint_authority: None,
+        freeze_authority: None,
+    }]));
+    let authority = service(&mock)
+        .get_token_authority("Mint".to_string())
+        .await
+        .unwrap();
+    assert_eq!(authority.mint_authority, None);
+    assert_eq!(authority.freeze_authority, None);
+    assert!(authority.is_mint_renounced);
+    assert!(authority.is_freeze_renounced);
+}
+
+#[tokio::test]
+async fn authorities_still_held_are_not_renounced() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![AuthorityRow {
+        mint_authority: Some("MintAuthority".to_string()),
+        freeze_authority: Some(String::new()),
+    }]));
+    let authority = service(&mock)
+        .get_token_authority("Mint".to_string())
+        .await
+        .unwrap();
+    assert_eq!(authority.mint_authority.as_deref(), Some("MintAuthority"));
+    assert!(!authority.is_mint_renounced);
+    // An empty freeze authority counts as renounced
+    assert_eq!(authority.freeze_authority, None);
+    assert!(authority.is_freeze_renounced);
+
+    let mock = Mock::new();
+    mock.add(handlers::provide(Vec::<AuthorityRow>::new()));
+    let missing = service(&mock).get_token_authority("Mint".to_string()).await;
+    assert!(matches!(missing, Err(ServiceError::NotFound)));
+}
+
+#[tokio::test]
+async fn creator_migrations_count_pumpfun_to_pumpswap() {
+    let mock = Mock::new();
+    mock.add(handlers::provide(vec![MigrationRow {
+        migration_count: 2,
+        pool_addresses: vec!["SwapB".to_string(), "SwapA".to_string()],
+    }]));
+    let migrations = service(&mock)
+        .get_creator_migrations("Creator".to_string())
+        .await
+        .unwrap();
+    assert_eq!(migrations.creator, "Creator");
+    assert_eq!(migrations.migration_count, 2);
+    assert_eq!(migrations.pool_addresses, ["SwapB", "SwapA"]);
+
+    let sql = testing::recorded_query(|db| async move {
+        db.get_creator_migrations("Creator".to_string()).await
+    })
+    .await;
+    assert!(
+        sql.contains("countIf(pre_factory = 'PumpFun' AND factory = 'PumpSwap')"),
+        "{sql}"
+    );
+    assert!(sql.contains("WHERE creator = 'Creator'"));
+}
+
+fn settings(vars: &[(&str, &str)]) -> ClientSettings {
+    ClientSettings::from_vars(|name| {
+        vars.iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.to_string())
+    })
+}
+
+#[test]
+fn client_settings_default_to_the_previous_hardcoded_values() {
+    let settings = settings(&[]);
+    assert_eq!(settings.compression, Compression::Lz4);
+    assert_eq!(settings.async_insert_max_data_size, 104_857_600);
+    assert_eq!(settings.async_insert_busy_timeout_ms, 400);
+    assert_eq!(settings.min_insert_block_size_bytes, 268_435_456);
+}
+
+#[test]
+fn client_settings_read_overrides() {
+    assert_eq!(
+        settings(&[("CLICKHOUSE_COMPRESSION", "none")]).compression,
+        Compression::None
+    );
+    assert_eq!(
+        settings(&[("CLICKHOUSE_COMPRESSION", "zstd")]).compression,
+        Compression::Lz4
+    );
+    let tuned = settings(&[
+        ("CLICKHOUSE_ASYNC_INSERT_BUSY_TIMEOUT_MS", "2000"),
+        ("CLICKHOUSE_MAX_INSERT_BLOCK_SIZE", "not a number"),
+    ]);
+    assert_eq!(tuned.async_insert_busy_timeout_ms, 2000);
+    assert_eq!(tuned.max_insert_block_size, 1_048_576);
+}
+
+fn network_error() -> clickhouse::error::Error {
+    clickhouse::error::Error::Network(Box::new(std::io::Error::from(
+        std::io::ErrorKind::ConnectionReset,
+    )))
+}
+
+#[tokio::test(start_paused = true)]
+async fn reads_are_retried_after_transient_errors() {
+    let mock = Mock::new();
+    let attempts = std::cell::Cell::new(0);
+    let result = service(&mock)
+        .with_retry(|| {
+            attempts.set(attempts.get() + 1);
+            let attempt = attempts.get();
+            async move {
+                if attempt < 3 {
+                    Err(network_error())
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+    assert_eq!(result.unwrap(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn query_errors_and_exhausted_retries_are_returned() {
+    let mock = Mock::new();
+    let db = service(&mock);
+
+    let attempts = std::cell::Cell::new(0);
+    let result: Result<()> = db
+        .with_retry(|| {
+            attempts.set(attempts.get() + 1);
+            async { Err(clickhouse::error::Error::BadResponse("syntax error".into())) }
+        })
+        .await;
+    assert!(matches!(result, Err(ServiceError::Db(_))));
+    assert_eq!(attempts.get(), 1);
+
+    let attempts = std::cell::Cell::new(0);
+    let result: Result<()> = db
+        .with_retry(|| {
+            attempts.set(attempts.get() + 1);
+            async { Err(network_error()) }
+        })
+        .await;
+    assert!(result.is_err());
+    assert_eq!(attempts.get(), 1 + db.read_retries);
+}
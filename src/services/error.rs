@@ -0,0 +1,41 @@
+use std::fmt;
+
+use axum::http::StatusCode;
+
+pub type Result<T, E = ServiceError> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+pub enum ServiceError {
+    // The query did not finish within the configured query timeout
+    Timeout,
+    Db(clickhouse::error::Error),
+    NotFound,
+}
+
+impl ServiceError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ServiceError::Timeout => StatusCode::GATEWAY_TIMEOUT,
+            ServiceError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ServiceError::NotFound => StatusCode::NOT_FOUND,
+        }
+    }
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceError::Timeout => write!(f, "query timed out"),
+            ServiceError::Db(e) => write!(f, "clickhouse error: {}", e),
+            ServiceError::NotFound => write!(f, "not found"),
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+impl From<clickhouse::error::Error> for ServiceError {
+    fn from(e: clickhouse::error::Error) -> Self {
+        ServiceError::Db(e)
+    }
+}
@@ -1,4 +1,9 @@
 pub mod clickhouse;
 pub mod db;
+pub mod error;
+pub mod pulse_cache;
+pub mod pulse_feed;
+pub mod quote_tokens;
 pub mod redis;
 pub mod subscription;
+pub mod webhooks;
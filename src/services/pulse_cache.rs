@@ -0,0 +1,131 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tokio::sync::OnceCell;
+
+use crate::types::pulse::PulseDataResponse;
+
+type Rows = Arc<Vec<PulseDataResponse>>;
+// Filled once by whichever request misses first; the others wait on it
+type Slot = Arc<OnceCell<(Instant, Rows)>>;
+
+// Recent `POST /pulse` results, keyed by the generated SQL. The SQL already encodes the
+// table, every filter, the lookback and the limit, so two requests that would read the same
+// rows share an entry. Entries only expire; nothing invalidates them early.
+#[derive(Clone)]
+pub struct PulseCache {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<String, Slot>>>,
+}
+
+impl PulseCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    // PULSE_CACHE_TTL_MS, default 1000; `0` turns the cache off
+    pub fn from_env() -> Self {
+        let ttl_ms = std::env::var("PULSE_CACHE_TTL_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1000);
+        Self::new(Duration::from_millis(ttl_ms))
+    }
+
+    // A slot is kept while its rows are fresh, or while a load into it is still running
+    fn is_live(&self, slot: &Slot) -> bool {
+        match slot.get() {
+            Some((stored, _)) => stored.elapsed() < self.ttl,
+            None => Arc::strong_count(slot) > 1,
+        }
+    }
+
+    // The cached rows for `query`, else the result of `load`. Concurrent misses for the
+    // same query run `load` once. A failed load isn't cached, so the next caller retries.
+    pub async fn get_or_load<E, F, Fut>(&self, query: &str, load: F) -> Result<Rows, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Rows, E>>,
+    {
+        if self.ttl.is_zero() {
+            return load().await;
+        }
+        let slot = {
+            let mut entries = self.entries.lock().unwrap();
+            // Expired entries are dropped here rather than by a background sweep
+            entries.retain(|_, slot| self.is_live(slot));
+            entries.entry(query.to_string()).or_default().clone()
+        };
+        let (_, rows) = slot
+            .get_or_try_init(|| async { load().await.map(|rows| (Instant::now(), rows)) })
+            .await?;
+        Ok(rows.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+
+    async fn load(cache: &PulseCache, query: &str, loads: &AtomicUsize) -> Rows {
+        cache
+            .get_or_load(query, || async {
+                loads.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                Ok::<_, Infallible>(Arc::new(Vec::new()))
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn entries_expire_after_the_ttl() {
+        let cache = PulseCache::new(Duration::from_millis(50));
+        let loads = AtomicUsize::new(0);
+        load(&cache, "SELECT 1", &loads).await;
+        load(&cache, "SELECT 1", &loads).await;
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        load(&cache, "SELECT 2", &loads).await;
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        load(&cache, "SELECT 1", &loads).await;
+        assert_eq!(loads.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn concurrent_misses_share_one_load() {
+        let cache = PulseCache::new(Duration::from_secs(1));
+        let loads = AtomicUsize::new(0);
+        tokio::join!(
+            load(&cache, "SELECT 1", &loads),
+            load(&cache, "SELECT 1", &loads),
+            load(&cache, "SELECT 1", &loads),
+        );
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_loads_are_retried() {
+        let cache = PulseCache::new(Duration::from_secs(1));
+        let failed = cache
+            .get_or_load("SELECT 1", || async { Err("timeout") })
+            .await;
+        assert_eq!(failed.unwrap_err(), "timeout");
+
+        let loads = AtomicUsize::new(0);
+        load(&cache, "SELECT 1", &loads).await;
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+    }
+}
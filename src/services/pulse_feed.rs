@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use tokio::sync::broadcast;
+
+use crate::types::pulse::PulseDataResponse;
+
+// Pulse rows for newly created pools. Computed once, then fanned out to every
+// transport (socket.io, SSE) through its own receiver.
+#[derive(Clone)]
+pub struct PulseFeed {
+    sender: broadcast::Sender<Arc<PulseDataResponse>>,
+}
+
+impl PulseFeed {
+    // PULSE_FEED_CAPACITY is how many rows a slow receiver can fall behind before it
+    // starts skipping them
+    pub fn from_env() -> Self {
+        let capacity = std::env::var("PULSE_FEED_CAPACITY")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|capacity| *capacity > 0)
+            .unwrap_or(256);
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    pub fn publish(&self, pulse: PulseDataResponse) {
+        // Err only means nobody is listening right now
+        let _ = self.sender.send(Arc::new(pulse));
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<PulseDataResponse>> {
+        self.sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{routes::pulse::PulseRow, services::quote_tokens::QuoteTokenRegistry};
+
+    #[tokio::test]
+    async fn every_subscriber_gets_each_row() {
+        let feed = PulseFeed::from_env();
+        let mut socket_io = feed.subscribe();
+        let mut sse = feed.subscribe();
+
+        let row = PulseRow {
+            pool_address: "pool".to_string(),
+            ..Default::default()
+        };
+        feed.publish(row.into_pulse_data(&QuoteTokenRegistry::default()));
+
+        let (a, b) = (socket_io.recv().await.unwrap(), sse.recv().await.unwrap());
+        assert_eq!(a.pair_address, "pool");
+        // Computed once and shared, not cloned per subscriber
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use tracing::{info, warn};
+
+use crate::{
+    defaults::{QuoteToken, QuoteTokenData, SOL_TOKEN, USDC_TOKEN},
+    utils::quote_decimals_factor,
+};
+
+// Quote tokens by mint address. SOL and USDC are always present; more can be added
+// with a JSON array of `{address, name, symbol, decimals, logo}` in QUOTE_TOKENS_FILE
+// or inline in QUOTE_TOKENS.
+#[derive(Debug, Clone)]
+pub struct QuoteTokenRegistry {
+    tokens: HashMap<String, QuoteTokenData>,
+}
+
+impl Default for QuoteTokenRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            tokens: HashMap::new(),
+        };
+        registry.insert(QuoteTokenData::from(&SOL_TOKEN));
+        registry.insert(QuoteTokenData::from(&USDC_TOKEN));
+        registry
+    }
+}
+
+impl QuoteTokenRegistry {
+    pub fn from_env() -> Self {
+        let mut registry = Self::default();
+
+        let config = match std::env::var("QUOTE_TOKENS_FILE") {
+            Ok(path) => match std::fs::read_to_string(&path) {
+                Ok(contents) => Some(contents),
+                Err(e) => {
+                    warn!("Could not read QUOTE_TOKENS_FILE {}: {}", path, e);
+                    None
+                }
+            },
+            Err(_) => std::env::var("QUOTE_TOKENS").ok(),
+        };
+
+        if let Some(config) = config {
+            match registry.extend_from_json(&config) {
+                Ok(count) => info!("Loaded {} extra quote tokens", count),
+                Err(e) => warn!("Ignoring invalid quote token config: {}", e),
+            }
+        }
+
+        registry
+    }
+
+    pub fn extend_from_json(&mut self, json: &str) -> Result<usize, serde_json::Error> {
+        let tokens: Vec<QuoteTokenData> = serde_json::from_str(json)?;
+        let count = tokens.len();
+        for token in tokens {
+            self.insert(token);
+        }
+        Ok(count)
+    }
+
+    pub fn insert(&mut self, token: QuoteTokenData) {
+        self.tokens.insert(token.address.clone(), token);
+    }
+
+    pub fn get(&self, address: &str) -> Option<&QuoteTokenData> {
+        self.tokens.get(address)
+    }
+
+    // Unregistered quote mints get a bare entry instead of failing the whole response
+    pub fn resolve(&self, address: &str) -> QuoteTokenData {
+        match self.get(address) {
+            Some(token) => token.clone(),
+            None => {
+                warn!("Unknown quote token {}", address);
+                // Assume SOL scaling so prices are left as indexed
+                QuoteTokenData {
+                    address: address.to_string(),
+                    name: String::new(),
+                    symbol: String::new(),
+                    decimals: SOL_TOKEN.decimals,
+                    logo: String::new(),
+                }
+            }
+        }
+    }
+
+    // `quote_decimals_factor` as SQL over the quote mint in `column`, so SQL filters see the
+    // same units as the rows. Mints not listed here scale by 1, as `resolve` assumes; the
+    // arms are sorted so one registry always yields the same SQL.
+    pub fn decimals_factor_sql(&self, column: &str) -> String {
+        let mut arms: Vec<String> = self
+            .tokens
+            .values()
+            .filter(|token| token.decimals != SOL_TOKEN.decimals)
+            .map(|token| {
+                format!(
+                    "{column} = '{}', {}",
+                    token.address.replace('\\', r"\\").replace('\'', r"\'"),
+                    quote_decimals_factor(token.decimals)
+                )
+            })
+            .collect();
+        if arms.is_empty() {
+            return "1".to_string();
+        }
+        arms.sort();
+        format!("multiIf({}, 1)", arms.join(", "))
+    }
+}
+
+impl From<&QuoteToken> for QuoteTokenData {
+    fn from(token: &QuoteToken) -> Self {
+        QuoteTokenData {
+            address: token.address.to_string(),
+            name: token.name.to_string(),
+            symbol: token.symbol.to_string(),
+            decimals: token.decimals,
+            logo: token.logo.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JUP: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+    #[test]
+    fn resolves_a_configured_third_quote_token() {
+        let mut registry = QuoteTokenRegistry::default();
+        let count = registry
+            .extend_from_json(&format!(
+                r#"[{{"address":"{JUP}","name":"Jupiter","symbol":"JUP","decimals":6,"logo":"https://example.com/jup.png"}}]"#
+            ))
+            .unwrap();
+        assert_eq!(count, 1);
+
+        let jup = registry.resolve(JUP);
+        assert_eq!(jup.symbol, "JUP");
+        assert_eq!(jup.decimals, 6);
+        assert_eq!(registry.resolve(SOL_TOKEN.address).symbol, SOL_TOKEN.symbol);
+        assert_eq!(
+            registry.resolve(USDC_TOKEN.address).symbol,
+            USDC_TOKEN.symbol
+        );
+    }
+
+    #[test]
+    fn unknown_quote_tokens_get_a_bare_entry() {
+        let token = QuoteTokenRegistry::default().resolve(JUP);
+        assert_eq!(token.address, JUP);
+        assert!(token.symbol.is_empty());
+        assert_eq!(token.decimals, SOL_TOKEN.decimals);
+    }
+
+    #[test]
+    fn factor_sql_lists_tokens_off_sol_decimals() {
+        let mut registry = QuoteTokenRegistry::default();
+        assert_eq!(
+            registry.decimals_factor_sql("q"),
+            format!("multiIf(q = '{}', 1000, 1)", USDC_TOKEN.address)
+        );
+
+        registry
+            .extend_from_json(&format!(
+                r#"[{{"address":"{JUP}","name":"Jupiter","symbol":"JUP","decimals":6,"logo":""}}]"#
+            ))
+            .unwrap();
+        let sql = registry.decimals_factor_sql("q");
+        assert!(sql.contains(&format!("q = '{JUP}', 1000")));
+        assert!(!sql.contains(SOL_TOKEN.address));
+    }
+
+    #[test]
+    fn invalid_config_is_an_error() {
+        let mut registry = QuoteTokenRegistry::default();
+        assert!(registry.extend_from_json(r#"{"address":"x"}"#).is_err());
+        assert!(registry.get(JUP).is_none());
+    }
+}
@@ -1,25 +1,281 @@
-use futures_util::StreamExt;
-use serde::Deserialize;
-pub async fn subscribe_and_process<T, F, Fut>(channel: &str, handler: F) -> redis::RedisResult<()>
+use std::{fmt::Display, future::Future, time::Duration};
+
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::metrics::METRICS;
+
+// Channels relayed to socket.io clients
+pub const PUBSUB_CHANNELS: [&str; 2] = ["swap_created", "pool_created"];
+
+// Longest slice of a bad payload that gets logged
+const PAYLOAD_SAMPLE_CHARS: usize = 200;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+// Counts and logs a payload on `channel` that failed to decode; the caller drops it and
+// moves on to the next message
+pub fn report_malformed(channel: &str, payload: &str, error: impl Display) {
+    METRICS.pubsub_malformed(channel);
+    let sample: String = payload.chars().take(PAYLOAD_SAMPLE_CHARS).collect();
+    warn!(
+        "Malformed payload on {} ({} bytes): {}; sample: {:?}",
+        channel,
+        payload.len(),
+        error,
+        sample
+    );
+}
+
+// Opens a pubsub connection already subscribed to `channels`
+pub async fn connect_pubsub(
+    redis_url: &str,
+    channels: &[&str],
+) -> redis::RedisResult<redis::aio::PubSub> {
+    let client = redis::Client::open(redis_url)?;
+    let mut pubsub = client.get_async_pubsub().await?;
+    for channel in channels {
+        pubsub.subscribe(*channel).await?;
+    }
+    Ok(pubsub)
+}
+
+// How long a pool stays marked as announced
+const NEW_PAIR_SEEN_TTL_SECS: u64 = 24 * 60 * 60;
+
+// Pools already announced as `new-pair`, kept in Redis so a restart that replays
+// `pool_created` doesn't announce them twice
+#[derive(Clone)]
+pub struct NewPairDedup {
+    conn: redis::aio::MultiplexedConnection,
+}
+
+impl NewPairDedup {
+    pub async fn connect(redis_url: &str) -> redis::RedisResult<Self> {
+        let client = redis::Client::open(redis_url)?;
+        let conn = client.get_multiplexed_async_connection().await?;
+        Ok(Self { conn })
+    }
+
+    // True only for the first claim of `pool_address` within the TTL
+    pub async fn claim(&mut self, pool_address: &str) -> redis::RedisResult<bool> {
+        let set: Option<String> = redis::cmd("SET")
+            .arg(format!("new_pair_emitted:{}", pool_address))
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(NEW_PAIR_SEEN_TTL_SECS)
+            .query_async(&mut self.conn)
+            .await?;
+        Ok(set.is_some())
+    }
+}
+
+// Calls `connect` until it succeeds, doubling the delay between attempts up to
+// MAX_BACKOFF. Returns `None` if shutdown is signalled while waiting.
+pub async fn connect_with_backoff<T, F, Fut>(
+    mut connect: F,
+    shutdown_rx: &mut watch::Receiver<bool>,
+) -> Option<T>
 where
-    T: for<'de> Deserialize<'de>,
-    F: Fn(T) -> Fut + Send + 'static,
-    Fut: std::future::Future<Output = ()> + Send,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = redis::RedisResult<T>>,
 {
-    let client = redis::Client::open("redis://127.0.0.1/")?;
-    let mut pubsub = client.get_async_pubsub().await?;
+    let mut delay = INITIAL_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        if *shutdown_rx.borrow() {
+            return None;
+        }
+        match connect().await {
+            Ok(connection) => {
+                if attempt > 1 {
+                    info!("Connected to redis after {} attempts", attempt);
+                }
+                return Some(connection);
+            }
+            Err(e) => warn!(
+                "Redis connection attempt {} failed: {}; retrying in {:?}",
+                attempt, e, delay
+            ),
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = shutdown_rx.changed() => return None,
+        }
+        delay = (delay * 2).min(MAX_BACKOFF);
+        attempt += 1;
+    }
+}
 
-    pubsub.subscribe(channel).await?;
-    let mut stream = pubsub.on_message();
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::Cell,
+        fmt,
+        sync::{Arc, Mutex},
+    };
 
-    while let Some(msg) = stream.next().await {
-        let payload: String = msg.get_payload()?;
+    use redis::{ErrorKind, RedisError};
+    use tracing::{
+        Event, Level, Subscriber,
+        field::{Field, Visit},
+    };
+    use tracing_subscriber::layer::{Context, SubscriberExt};
 
-        match serde_json::from_str::<T>(&payload) {
-            Ok(data) => handler(data).await,
-            Err(e) => eprintln!("Failed to deserialize: {}", e),
+    use super::*;
+
+    // Collects the message of every warn! event
+    #[derive(Clone, Default)]
+    struct Warnings(Arc<Mutex<Vec<String>>>);
+
+    struct Message(String);
+
+    impl Visit for Message {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "message" {
+                self.0 = format!("{value:?}");
+            }
         }
     }
 
-    Ok(())
+    impl<S: Subscriber> tracing_subscriber::Layer<S> for Warnings {
+        fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+            if *event.metadata().level() == Level::WARN {
+                let mut message = Message(String::new());
+                event.record(&mut message);
+                self.0.lock().unwrap().push(message.0);
+            }
+        }
+    }
+
+    fn malformed_total(channel: &str) -> u64 {
+        let prefix = format!("pubsub_malformed_payloads_total{{channel=\"{channel}\"}} ");
+        METRICS
+            .render()
+            .lines()
+            .find_map(|line| line.strip_prefix(&prefix)?.parse().ok())
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn malformed_payloads_are_counted_and_logged_with_a_sample() {
+        let warnings = Warnings::default();
+        let subscriber = tracing_subscriber::registry().with(warnings.clone());
+        let payload = format!("{{\"pool_address\": {}", "x".repeat(1_000));
+        let error = serde_json::from_str::<serde_json::Value>(&payload).unwrap_err();
+
+        tracing::subscriber::with_default(subscriber, || {
+            report_malformed("malformed_test", &payload, &error);
+            report_malformed("malformed_test", "not json", &error);
+        });
+
+        assert_eq!(malformed_total("malformed_test"), 2);
+        let warnings = warnings.0.lock().unwrap();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("(1017 bytes)"));
+        assert!(warnings[0].contains(&"x".repeat(PAYLOAD_SAMPLE_CHARS - 17)));
+        assert!(!warnings[0].contains(&"x".repeat(PAYLOAD_SAMPLE_CHARS)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn transient_failures_are_retried_with_backoff() {
+        let (_shutdown_tx, mut shutdown_rx) = watch::channel(false);
+        let attempts = Cell::new(0);
+        let started = tokio::time::Instant::now();
+
+        let connection = connect_with_backoff(
+            || {
+                attempts.set(attempts.get() + 1);
+                let attempt = attempts.get();
+                async move {
+                    if attempt < 3 {
+                        Err(RedisError::from((ErrorKind::IoError, "connection refused")))
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            },
+            &mut shutdown_rx,
+        )
+        .await;
+
+        assert_eq!(connection, Some(3));
+        // 500ms after the first failure, then 1s after the second
+        assert_eq!(started.elapsed(), Duration::from_millis(1500));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn shutdown_stops_the_retries() {
+        let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+        let retry = connect_with_backoff(
+            || async { Err::<(), _>(RedisError::from((ErrorKind::IoError, "down"))) },
+            &mut shutdown_rx,
+        );
+        let shutdown = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            shutdown_tx.send(true).unwrap();
+        };
+
+        let (connection, ()) = tokio::join!(retry, shutdown);
+        assert_eq!(connection, None);
+    }
+
+    // Minimal RESP server keeping SET NX keys in memory, enough for NewPairDedup
+    async fn fake_redis() -> String {
+        use std::collections::HashSet;
+
+        use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("redis://{}", listener.local_addr().unwrap());
+        let keys = Arc::new(Mutex::new(HashSet::new()));
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let keys = keys.clone();
+                tokio::spawn(async move {
+                    let (read, mut write) = socket.into_split();
+                    let mut read = BufReader::new(read);
+                    let mut line = String::new();
+                    loop {
+                        // *<argc> then $<len> + bytes per argument
+                        line.clear();
+                        if read.read_line(&mut line).await.unwrap_or(0) == 0 {
+                            return;
+                        }
+                        let argc: usize = line.trim()[1..].parse().unwrap();
+                        let mut args = Vec::with_capacity(argc);
+                        for _ in 0..argc {
+                            line.clear();
+                            read.read_line(&mut line).await.unwrap();
+                            let mut arg = vec![0; line.trim()[1..].parse::<usize>().unwrap() + 2];
+                            read.read_exact(&mut arg).await.unwrap();
+                            arg.truncate(arg.len() - 2);
+                            args.push(String::from_utf8(arg).unwrap());
+                        }
+                        let reply: &[u8] = match args[0].to_uppercase().as_str() {
+                            "SET" if !keys.lock().unwrap().insert(args[1].clone()) => b"$-1\r\n",
+                            _ => b"+OK\r\n",
+                        };
+                        write.write_all(reply).await.unwrap();
+                    }
+                });
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn a_replayed_pool_is_claimed_only_once() {
+        let url = fake_redis().await;
+        let mut dedup = NewPairDedup::connect(&url).await.unwrap();
+        assert!(dedup.claim("PoolA").await.unwrap());
+        assert!(!dedup.claim("PoolA").await.unwrap());
+        assert!(dedup.claim("PoolB").await.unwrap());
+
+        // A restarted instance sees the claims made before it
+        let mut restarted = NewPairDedup::connect(&url).await.unwrap();
+        assert!(!restarted.claim("PoolA").await.unwrap());
+    }
 }
@@ -0,0 +1,376 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use sha2::Sha256;
+use tokio::sync::broadcast::{Receiver, error::RecvError};
+use tracing::{info, warn};
+
+use crate::types::{filter::Filters, pulse::PulseDataResponse};
+
+pub const SIGNATURE_HEADER: &str = "x-signature-256";
+
+const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+// A URL that receives every new pool matching `filters`
+#[derive(Debug)]
+pub struct Webhook {
+    pub id: String,
+    // API key that registered the hook; only it can delete the hook
+    pub owner: String,
+    pub url: String,
+    pub filters: Filters,
+    // Key for the HMAC-SHA256 signature sent with each delivery
+    secret: String,
+}
+
+// Webhooks registered since startup; they are not persisted across restarts
+#[derive(Clone)]
+pub struct WebhookRegistry {
+    hooks: Arc<RwLock<Vec<Arc<Webhook>>>>,
+    next_id: Arc<AtomicU64>,
+    client: reqwest::Client,
+}
+
+impl Default for WebhookRegistry {
+    fn default() -> Self {
+        // A redirect could send a delivery to a host the registration check never saw, and
+        // a host's DNS can change after registration, so each connection is checked again
+        let client = reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(Arc::new(PublicResolver))
+            .build()
+            .expect("failed to build webhook HTTP client");
+        Self {
+            hooks: Arc::default(),
+            next_id: Arc::default(),
+            client,
+        }
+    }
+}
+
+impl WebhookRegistry {
+    pub fn count_for(&self, owner: &str) -> usize {
+        let hooks = self.hooks.read().unwrap();
+        hooks.iter().filter(|hook| hook.owner == owner).count()
+    }
+
+    pub fn register(&self, owner: &str, url: String, secret: String, filters: Filters) -> String {
+        let id = format!("wh_{:08x}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        let hook = Webhook {
+            id: id.clone(),
+            owner: owner.to_string(),
+            url,
+            filters,
+            secret,
+        };
+        self.hooks.write().unwrap().push(Arc::new(hook));
+        id
+    }
+
+    // False when no hook with that id belongs to `owner`
+    pub fn remove(&self, owner: &str, id: &str) -> bool {
+        let mut hooks = self.hooks.write().unwrap();
+        let before = hooks.len();
+        hooks.retain(|hook| hook.id != id || hook.owner != owner);
+        hooks.len() < before
+    }
+
+    // POSTs each pulse row from the feed to every webhook whose filters it matches.
+    // Deliveries run on their own tasks so a slow endpoint doesn't hold up the rest.
+    pub async fn deliver(self, mut rx: Receiver<Arc<PulseDataResponse>>) {
+        loop {
+            let pulse = match rx.recv().await {
+                Ok(pulse) => pulse,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(
+                        "Webhook delivery fell behind, skipped {} pulse rows",
+                        skipped
+                    );
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let now = Utc::now();
+            let matching: Vec<Arc<Webhook>> = self
+                .hooks
+                .read()
+                .unwrap()
+                .iter()
+                .filter(|hook| hook.filters.matches(&pulse, now))
+                .cloned()
+                .collect();
+            if matching.is_empty() {
+                continue;
+            }
+            let body = match serde_json::to_vec(&*pulse) {
+                Ok(body) => body,
+                Err(e) => {
+                    warn!("Failed to encode pulse for webhooks: {}", e);
+                    continue;
+                }
+            };
+            for hook in matching {
+                tokio::spawn(post_with_retry(self.client.clone(), hook, body.clone()));
+            }
+        }
+    }
+}
+
+// Retries connection errors, 429s and 5xx responses with exponential backoff
+async fn post_with_retry(client: reqwest::Client, hook: Arc<Webhook>, body: Vec<u8>) {
+    let signature = format!("sha256={}", sign(&hook.secret, &body));
+    let mut delay = INITIAL_BACKOFF;
+    for attempt in 1..=MAX_ATTEMPTS {
+        let result = client
+            .post(&hook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, &signature)
+            .body(body.clone())
+            .send()
+            .await;
+        let error = match result {
+            Ok(response) if response.status().is_success() => {
+                info!("Delivered webhook {} on attempt {}", hook.id, attempt);
+                return;
+            }
+            Ok(response) => {
+                let status = response.status();
+                if !status.is_server_error() && status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    warn!("Webhook {} rejected delivery with {}", hook.id, status);
+                    return;
+                }
+                status.to_string()
+            }
+            Err(e) => e.to_string(),
+        };
+        warn!(
+            "Webhook {} delivery attempt {}/{} failed: {}",
+            hook.id, attempt, MAX_ATTEMPTS, error
+        );
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+}
+
+// Whether every address `url`'s host resolves to is publicly routable. Deliveries are made
+// from inside our network, so loopback, private, link-local (cloud metadata) and similar
+// ranges are refused.
+pub async fn is_public_host(url: &reqwest::Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let port = url.port_or_known_default().unwrap_or(80);
+    // IPv6 literals keep their brackets in the URL
+    let addrs: Vec<IpAddr> = match host.trim_matches(['[', ']']).parse::<IpAddr>() {
+        Ok(ip) => vec![ip],
+        Err(_) => match tokio::net::lookup_host((host, port)).await {
+            Ok(addrs) => addrs.map(|addr| addr.ip()).collect(),
+            Err(_) => Vec::new(),
+        },
+    };
+    !addrs.is_empty() && addrs.into_iter().all(is_public_ip)
+}
+
+// Resolver for deliveries that fails when a host resolves to any non-public address, so a
+// hostname can't pass registration and then be rebound to an internal one. IP-literal URLs
+// never reach a resolver; they can't change after the registration check.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            // The connector fills in the URL's port
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            if addrs.is_empty() || !addrs.iter().all(|addr| is_public_ip(addr.ip())) {
+                return Err(
+                    format!("{} does not resolve to a public address", name.as_str()).into(),
+                );
+            }
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+// `IpAddr::is_global` is still unstable, so the non-public ranges are listed here
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // 0.0.0.0/8, carrier-grade NAT 100.64.0.0/10 and reserved 240.0.0.0/4
+                || a == 0
+                || (a == 100 && (64..128).contains(&b))
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return is_public_ip(v4.into());
+            }
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // unique local fc00::/7 and link-local fe80::/10
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+// Hex-encoded HMAC-SHA256 of `body`, so receivers can check a delivery came from us
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, body::Bytes, http::HeaderMap, routing::post};
+    use serde_json::json;
+    use tokio::{
+        net::TcpListener,
+        sync::{broadcast, mpsc},
+    };
+
+    use super::*;
+
+    fn filters(extra: serde_json::Value) -> Filters {
+        let mut body = json!({
+            "factories": { "pumpFun": false, "pumpSwap": false },
+            "searchKeywords": [],
+            "excludeKeywords": [],
+            "twitter": false,
+            "website": false,
+            "telegram": false,
+            "atLeastOneSocial": false,
+        });
+        body.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn matching_pools_are_posted_signed() {
+        let (tx, mut received) = mpsc::unbounded_channel();
+        let receiver = Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: Bytes| async move {
+                let signature = headers[SIGNATURE_HEADER].to_str().unwrap().to_string();
+                tx.send((signature, body)).unwrap();
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, receiver).await.unwrap() });
+
+        let registry = WebhookRegistry::default();
+        let cats = filters(json!({ "searchKeywords": ["moon"] }));
+        let dogs = filters(json!({ "searchKeywords": ["dog"] }));
+        registry.register("key", url.clone(), "secret".to_string(), cats);
+        registry.register("key", url, "secret".to_string(), dogs);
+        let (feed, rx) = broadcast::channel(4);
+        tokio::spawn(registry.deliver(rx));
+        feed.send(Arc::new(PulseDataResponse::sample(Utc::now())))
+            .unwrap();
+
+        let (signature, body) = tokio::time::timeout(Duration::from_secs(5), received.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(signature, format!("sha256={}", sign("secret", &body)));
+        let pulse: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(pulse["pairAddress"], "pool");
+        // The `dog` hook doesn't match the pool, so nothing else arrives
+        let second = tokio::time::timeout(Duration::from_millis(200), received.recv()).await;
+        assert!(second.is_err());
+    }
+
+    #[tokio::test]
+    async fn deliveries_refuse_hostnames_that_resolve_privately() {
+        let resolved = PublicResolver.resolve("localhost".parse().unwrap()).await;
+        assert!(resolved.is_err());
+
+        // A hook on a hostname is rechecked when the delivery connects, so a receiver
+        // listening on loopback gets nothing
+        let (tx, mut received) = mpsc::unbounded_channel();
+        let receiver = Router::new().route(
+            "/hook",
+            post(move || async move {
+                tx.send(()).unwrap();
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move { axum::serve(listener, receiver).await.unwrap() });
+
+        let registry = WebhookRegistry::default();
+        registry.register(
+            "key",
+            format!("http://localhost:{port}/hook"),
+            "secret".to_string(),
+            filters(json!({ "searchKeywords": ["moon"] })),
+        );
+        let (feed, rx) = broadcast::channel(4);
+        tokio::spawn(registry.deliver(rx));
+        feed.send(Arc::new(PulseDataResponse::sample(Utc::now())))
+            .unwrap();
+
+        let delivered = tokio::time::timeout(Duration::from_millis(300), received.recv()).await;
+        assert!(delivered.is_err());
+    }
+
+    #[test]
+    fn private_and_local_addresses_are_not_public() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in ["1.1.1.1", "8.8.8.8", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn sign_matches_rfc_4231_vector() {
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -0,0 +1,32 @@
+use axum::extract::FromRef;
+
+use crate::services::{
+    clickhouse::ClickhouseService, pulse_feed::PulseFeed, webhooks::WebhookRegistry,
+};
+
+// Shared by every route; handlers extract only the part they need, e.g.
+// `State<ClickhouseService>` or `State<PulseFeed>`
+#[derive(Clone)]
+pub struct AppState {
+    pub clickhouse: ClickhouseService,
+    pub pulse_feed: PulseFeed,
+    pub webhooks: WebhookRegistry,
+}
+
+impl FromRef<AppState> for ClickhouseService {
+    fn from_ref(state: &AppState) -> Self {
+        state.clickhouse.clone()
+    }
+}
+
+impl FromRef<AppState> for PulseFeed {
+    fn from_ref(state: &AppState) -> Self {
+        state.pulse_feed.clone()
+    }
+}
+
+impl FromRef<AppState> for WebhookRegistry {
+    fn from_ref(state: &AppState) -> Self {
+        state.webhooks.clone()
+    }
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenAuthority {
+    pub mint_authority: Option<String>,
+    pub freeze_authority: Option<String>,
+    // No one can mint more supply
+    pub is_mint_renounced: bool,
+    // No one can freeze holders' token accounts
+    pub is_freeze_renounced: bool,
+}
+
+impl TokenAuthority {
+    // An empty authority is renounced too, the same as a null one
+    pub fn new(mint_authority: Option<String>, freeze_authority: Option<String>) -> Self {
+        let mint_authority = mint_authority.filter(|a| !a.is_empty());
+        let freeze_authority = freeze_authority.filter(|a| !a.is_empty());
+        Self {
+            is_mint_renounced: mint_authority.is_none(),
+            is_freeze_renounced: freeze_authority.is_none(),
+            mint_authority,
+            freeze_authority,
+        }
+    }
+}
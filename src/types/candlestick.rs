@@ -18,21 +18,79 @@ pub enum Interval {
 }
 
 impl Interval {
+    pub const ALL: [Interval; 13] = [
+        Interval::OneSecond,
+        Interval::FiveSeconds,
+        Interval::FifteenSeconds,
+        Interval::ThirtySeconds,
+        Interval::OneMinute,
+        Interval::FiveMinutes,
+        Interval::FifteenMinutes,
+        Interval::ThirtyMinutes,
+        Interval::OneHour,
+        Interval::FourHours,
+        Interval::SixHours,
+        Interval::TwelveHours,
+        Interval::OneDay,
+    ];
+
     pub fn to_string(&self) -> String {
+        self.table_suffix().to_string()
+    }
+
+    // Fixed suffix of the interval's candle table. Table names are only ever built from
+    // this, never from the string a client sent.
+    pub fn table_suffix(&self) -> &'static str {
+        match self {
+            Interval::OneSecond => "1s",
+            Interval::FiveSeconds => "5s",
+            Interval::FifteenSeconds => "15s",
+            Interval::ThirtySeconds => "30s",
+            Interval::OneMinute => "1m",
+            Interval::FiveMinutes => "5m",
+            Interval::FifteenMinutes => "15m",
+            Interval::ThirtyMinutes => "30m",
+            Interval::OneHour => "1h",
+            Interval::FourHours => "4h",
+            Interval::SixHours => "6h",
+            Interval::TwelveHours => "12h",
+            Interval::OneDay => "1d",
+        }
+    }
+
+    pub fn candle_table(&self) -> &'static str {
+        match self {
+            Interval::OneSecond => "candles_1s",
+            Interval::FiveSeconds => "candles_5s",
+            Interval::FifteenSeconds => "candles_15s",
+            Interval::ThirtySeconds => "candles_30s",
+            Interval::OneMinute => "candles_1m",
+            Interval::FiveMinutes => "candles_5m",
+            Interval::FifteenMinutes => "candles_15m",
+            Interval::ThirtyMinutes => "candles_30m",
+            Interval::OneHour => "candles_1h",
+            Interval::FourHours => "candles_4h",
+            Interval::SixHours => "candles_6h",
+            Interval::TwelveHours => "candles_12h",
+            Interval::OneDay => "candles_1d",
+        }
+    }
+
+    pub fn seconds(&self) -> i64 {
         match self {
-            Interval::OneSecond => "1s".to_string(),
-            Interval::FiveSeconds => "5s".to_string(),
-            Interval::FifteenSeconds => "15s".to_string(),
-            Interval::ThirtySeconds => "30s".to_string(),
-            Interval::OneMinute => "1m".to_string(),
-            Interval::FiveMinutes => "5m".to_string(),
-            Interval::FifteenMinutes => "15m".to_string(),
-            Interval::ThirtyMinutes => "30m".to_string(),
-            Interval::OneHour => "1h".to_string(),
-            Interval::FourHours => "4h".to_string(),
-            Interval::SixHours => "6h".to_string(),
-            Interval::TwelveHours => "12h".to_string(),
-            Interval::OneDay => "1d".to_string(),
+            Interval::OneSecond => 1,
+            Interval::FiveSeconds => 5,
+            Interval::FifteenSeconds => 15,
+            Interval::ThirtySeconds => 30,
+            Interval::OneMinute => 60,
+            Interval::FiveMinutes => 300,
+            Interval::FifteenMinutes => 900,
+            Interval::ThirtyMinutes => 1800,
+            Interval::OneHour => 3600,
+            Interval::FourHours => 14400,
+            Interval::SixHours => 21600,
+            Interval::TwelveHours => 43200,
+            Interval::OneDay => 86400,
         }
     }
 
@@ -76,4 +134,37 @@ pub struct CandlestickQuery {
     pub start_time: Option<i64>, // Unix timestamp (seconds since epoch)
     pub end_time: Option<i64>,   // Unix timestamp (seconds since epoch)
     pub limit: i32,
+    // Forward-fill buckets with no trades so the series has no holes
+    #[serde(default)]
+    pub fill_gaps: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_interval_maps_to_its_own_table() {
+        for interval in Interval::ALL {
+            let suffix = interval.table_suffix();
+            assert_eq!(Interval::from_string(suffix), Ok(interval.clone()));
+            assert_eq!(interval.candle_table(), format!("candles_{}", suffix));
+        }
+    }
+
+    #[test]
+    fn intervals_are_ordered_by_length() {
+        let seconds: Vec<i64> = Interval::ALL.iter().map(Interval::seconds).collect();
+        assert!(seconds.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn rejects_unknown_intervals() {
+        for interval in ["", "2m", "1H", "candles_1m", "1m; DROP TABLE swaps"] {
+            assert!(
+                Interval::from_string(interval).is_err(),
+                "{interval:?} should be rejected"
+            );
+        }
+    }
 }
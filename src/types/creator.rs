@@ -0,0 +1,23 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatorPool {
+    pub pool_address: String,
+    pub token_base_address: String,
+    pub token_quote_address: String,
+    pub factory: String,
+    pub pre_factory: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub curve_percentage: f32,
+    // The base token has a PumpSwap pool migrated from PumpFun
+    pub migrated: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatorMigrations {
+    pub creator: String,
+    pub migration_count: u64,
+    // PumpSwap pools the creator's PumpFun tokens migrated to, newest first
+    pub pool_addresses: Vec<String>,
+}
@@ -0,0 +1,8 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CurvePoint {
+    pub curve_percentage: f32,
+    pub updated_at: DateTime<Utc>,
+}
@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DevActivityEntry {
+    pub hash: String,
+    pub swap_type: String,
+    pub base_amount: f64,
+    pub quote_amount: f64,
+    pub price_sol: f64,
+    pub slot: i64,
+    pub created_at: DateTime<Utc>,
+    // dev's base-token balance in this pool after this swap
+    pub cumulative_holding: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DevActivityResponse {
+    pub pool_address: String,
+    pub creator: String,
+    pub activity: Vec<DevActivityEntry>,
+}
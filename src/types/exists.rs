@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PoolExists {
+    // The pool is in `pools`
+    pub exists: bool,
+    // At least one swap has been indexed for it
+    pub has_trades: bool,
+    pub factory: Option<String>,
+}
@@ -1,12 +1,39 @@
 use chrono::{DateTime, Utc};
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, prelude::ToPrimitive};
 use serde::{Deserialize, Deserializer, Serialize};
 
+use crate::types::pulse::PulseDataResponse;
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PulseFilter {
     pub filters: Filters,
     pub table: PulseTable,
+    // Read holder counts from the pool_holder_counts MV instead of scanning accounts
+    #[serde(default)]
+    pub approximate_holders: bool,
+    // Seconds of recent 5m buckets to leave out of vol_24h; 0 counts everything
+    #[serde(default)]
+    pub settling_period: u32,
+    // How far back pools (and their swaps, holders and funding) are considered
+    #[serde(default = "default_lookback_hours")]
+    pub lookback_hours: u32,
+}
+
+const DEFAULT_LOOKBACK_HOURS: u32 = 24;
+const DEFAULT_MAX_LOOKBACK_HOURS: u32 = 168;
+
+fn default_lookback_hours() -> u32 {
+    DEFAULT_LOOKBACK_HOURS
+}
+
+// Upper bound for `lookback_hours` and the age filter, from PULSE_MAX_LOOKBACK_HOURS
+pub fn max_lookback_hours() -> u32 {
+    std::env::var("PULSE_MAX_LOOKBACK_HOURS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|hours| *hours > 0)
+        .unwrap_or(DEFAULT_MAX_LOOKBACK_HOURS)
 }
 
 #[derive(Debug, Deserialize)]
@@ -21,7 +48,9 @@ pub enum PulseTable {
 #[serde(rename_all = "camelCase")]
 pub struct Filters {
     pub factories: FactoryFilters,
+    #[serde(deserialize_with = "validate_keywords")]
     pub search_keywords: Vec<String>,
+    #[serde(deserialize_with = "validate_keywords")]
     pub exclude_keywords: Vec<String>,
     // pub dex_paid: bool,
     #[serde(
@@ -96,7 +125,62 @@ pub struct Filters {
     pub twitter: bool,
     pub website: bool,
     pub telegram: bool,
+    #[serde(default)]
+    pub discord: bool,
     pub at_least_one_social: bool,
+    // Minimum number of twitter / website / telegram / discord links set
+    #[serde(default)]
+    pub min_socials: Option<u8>,
+}
+
+impl Filters {
+    // In-memory equivalent of the pulse WHERE clauses, for rows that are pushed rather
+    // than queried (e.g. webhooks). Liquidity and market cap are compared in quote units.
+    pub fn matches(&self, pulse: &PulseDataResponse, now: DateTime<Utc>) -> bool {
+        let name = pulse.token_name.as_deref().unwrap_or("").to_lowercase();
+        let symbol = pulse.token_symbol.as_deref().unwrap_or("").to_lowercase();
+        let contains = |keyword: &String| {
+            let keyword = keyword.to_lowercase();
+            name.contains(&keyword) || symbol.contains(&keyword)
+        };
+        let has = |social: &Option<String>| social.as_deref().is_some_and(|s| !s.is_empty());
+        let socials = [
+            &pulse.twitter,
+            &pulse.website,
+            &pulse.telegram,
+            &pulse.discord,
+        ]
+        .into_iter()
+        .filter(|social| has(social))
+        .count();
+
+        self.factories.matches(&pulse.protocol)
+            && (self.search_keywords.is_empty() || self.search_keywords.iter().any(contains))
+            && !self.exclude_keywords.iter().any(contains)
+            && self.age.contains((now - pulse.created_at).num_minutes())
+            && self.top10_holders.contains_f64(pulse.top10_holders_percent)
+            && self.dev_holding.contains_f64(pulse.dev_holds_percent)
+            && self
+                .snipers_holding
+                .contains_f64(pulse.snipers_holds_percent)
+            && self.holders.contains(pulse.num_holders)
+            && self
+                .bonding_curve
+                .contains_f64(pulse.bonding_curve_percent as f64)
+            && self.liquidity.contains_f64(pulse.liquidity_sol)
+            && self.volume.contains_f64(pulse.volume_sol)
+            && self.market_cap.contains_f64(pulse.market_cap_sol)
+            && self.txns.contains(pulse.num_txns)
+            && self.num_buys.contains(pulse.num_buys)
+            && self.num_sells.contains(pulse.num_sells)
+            && self.num_migrations.contains(pulse.migration_count)
+            && (!self.twitter || has(&pulse.twitter))
+            && (!self.website || has(&pulse.website))
+            && (!self.telegram || has(&pulse.telegram))
+            && (!self.discord || has(&pulse.discord))
+            && (!self.at_least_one_social || socials > 0)
+            && self.min_socials.is_none_or(|min| socials >= min as usize)
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -114,6 +198,16 @@ pub struct FactoryFilters {
     // pub moonshot: bool,
 }
 
+impl FactoryFilters {
+    // No factory selected means every factory
+    fn matches(&self, factory: &str) -> bool {
+        if !self.pump_fun && !self.pump_swap {
+            return true;
+        }
+        (self.pump_fun && factory == "PumpFun") || (self.pump_swap && factory == "PumpSwap")
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RangeFilter<T> {
@@ -127,6 +221,21 @@ impl<T> RangeFilter<T> {
     }
 }
 
+impl<T: PartialOrd> RangeFilter<T> {
+    pub fn contains(&self, value: T) -> bool {
+        self.min.as_ref().is_none_or(|min| value >= *min)
+            && self.max.as_ref().is_none_or(|max| value <= *max)
+    }
+}
+
+impl RangeFilter<Decimal> {
+    pub fn contains_f64(&self, value: f64) -> bool {
+        let bound = |bound: &Option<Decimal>| bound.and_then(|b| b.to_f64());
+        bound(&self.min).is_none_or(|min| value >= min)
+            && bound(&self.max).is_none_or(|max| value <= max)
+    }
+}
+
 fn default_range_filter<T>(min: T, max: T) -> RangeFilter<T> {
     RangeFilter {
         min: Some(min),
@@ -134,9 +243,14 @@ fn default_range_filter<T>(min: T, max: T) -> RangeFilter<T> {
     }
 }
 
+// Largest `filters.age.max` in minutes; also the max filled in when one is left out
+fn max_age_minutes() -> i64 {
+    max_lookback_hours() as i64 * 60
+}
+
 // For usage compatibility, you can define type-specific wrappers if needed:
 fn default_age_filter() -> RangeFilter<i64> {
-    default_range_filter(0, 1440)
+    default_range_filter(0, max_age_minutes())
 }
 fn default_hundred_filter() -> RangeFilter<Decimal> {
     default_range_filter(Decimal::from(0), Decimal::from(100))
@@ -148,14 +262,40 @@ where
     default_range_filter(T::from(0), T::from(1000000000))
 }
 
+// Each keyword becomes its own LIKE clause in the pulse SQL
+const MAX_KEYWORDS: usize = 50;
+const MAX_KEYWORD_CHARS: usize = 64;
+
+fn validate_keywords<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let keywords = Vec::<String>::deserialize(deserializer)?;
+    if keywords.len() > MAX_KEYWORDS {
+        return Err(serde::de::Error::custom(format!(
+            "at most {} keywords are allowed",
+            MAX_KEYWORDS
+        )));
+    }
+    if keywords
+        .iter()
+        .any(|k| k.chars().count() > MAX_KEYWORD_CHARS)
+    {
+        return Err(serde::de::Error::custom(format!(
+            "keywords can be at most {} characters",
+            MAX_KEYWORD_CHARS
+        )));
+    }
+    Ok(keywords)
+}
+
 fn validate_range_filter<'de, D, T>(
     deserializer: D,
     max_limit: T,
-    err_msg: &str,
 ) -> Result<RangeFilter<T>, D::Error>
 where
     D: Deserializer<'de>,
-    T: serde::de::Deserialize<'de> + PartialOrd + Clone,
+    T: serde::de::Deserialize<'de> + PartialOrd + Clone + std::fmt::Display,
 {
     let mut filter = RangeFilter::<T>::deserialize(deserializer)?;
 
@@ -167,7 +307,21 @@ where
     // Validate that max doesn't exceed limit
     if let Some(ref max) = filter.max {
         if *max > max_limit {
-            return Err(serde::de::Error::custom(err_msg));
+            // The field name is added by the caller (see `FilterJson`)
+            return Err(serde::de::Error::custom(format!(
+                "max cannot exceed {}",
+                max_limit
+            )));
+        }
+    }
+
+    // An inverted range matches nothing, which is never what the caller meant
+    if let (Some(min), Some(max)) = (&filter.min, &filter.max) {
+        if min > max {
+            return Err(serde::de::Error::custom(format!(
+                "min {} is greater than max {}",
+                min, max
+            )));
         }
     }
 
@@ -178,28 +332,110 @@ fn validate_age_filter<'de, D>(deserializer: D) -> Result<RangeFilter<i64>, D::E
 where
     D: Deserializer<'de>,
 {
-    validate_range_filter(deserializer, 1440, "Age max cannot exceed 1440")
+    validate_range_filter(deserializer, max_age_minutes())
 }
 
 fn validate_hundred_filter<'de, D>(deserializer: D) -> Result<RangeFilter<Decimal>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    validate_range_filter(
-        deserializer,
-        Decimal::from(100),
-        "Bonding curve max cannot exceed 100",
-    )
+    validate_range_filter(deserializer, Decimal::from(100))
 }
 
 fn validate_max_amount_filter<'de, D, T>(deserializer: D) -> Result<RangeFilter<T>, D::Error>
 where
     D: Deserializer<'de>,
-    T: serde::de::Deserialize<'de> + PartialOrd + Clone + From<i64>,
+    T: serde::de::Deserialize<'de> + PartialOrd + Clone + From<i64> + std::fmt::Display,
 {
-    validate_range_filter(
-        deserializer,
-        T::from(1000000000),
-        "Max amount cannot exceed 1000000000",
-    )
+    validate_range_filter(deserializer, T::from(1000000000))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn parse(extra: serde_json::Value) -> Result<Filters, serde_json::Error> {
+        let mut body = json!({
+            "factories": { "pumpFun": false, "pumpSwap": false },
+            "searchKeywords": [],
+            "excludeKeywords": [],
+            "twitter": false,
+            "website": false,
+            "telegram": false,
+            "atLeastOneSocial": false,
+        });
+        body.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(body)
+    }
+
+    fn filters(extra: serde_json::Value) -> Filters {
+        parse(extra).unwrap()
+    }
+
+    #[test]
+    fn missing_age_max_defaults_the_same_as_a_missing_age() {
+        let omitted = filters(json!({})).age;
+        let min_only = filters(json!({ "age": { "min": 0 } })).age;
+        assert_eq!(omitted.max, min_only.max);
+        assert_eq!(omitted.max, Some(max_age_minutes()));
+    }
+
+    #[test]
+    fn age_max_past_the_lookback_limit_is_rejected() {
+        let max = max_age_minutes();
+        assert_eq!(filters(json!({ "age": { "max": max } })).age.max, Some(max));
+        assert!(parse(json!({ "age": { "max": max + 1 } })).is_err());
+    }
+
+    #[test]
+    fn inverted_ranges_are_rejected() {
+        let error = parse(json!({ "liquidity": { "min": 100, "max": 10 } })).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("min 100 is greater than max 10")
+        );
+        // Also against the max filled in for a missing one
+        assert!(parse(json!({ "bondingCurve": { "min": 101 } })).is_err());
+        assert!(parse(json!({ "holders": { "min": 10, "max": 10 } })).is_ok());
+    }
+
+    #[test]
+    fn matches_a_new_pool_inside_every_range() {
+        let now = Utc::now();
+        let pulse = PulseDataResponse::sample(now);
+        assert!(filters(json!({})).matches(&pulse, now));
+        assert!(
+            filters(json!({
+                "factories": { "pumpFun": true, "pumpSwap": false },
+                "searchKeywords": ["moon"],
+                "twitter": true,
+                "liquidity": { "min": 20, "max": 40 },
+            }))
+            .matches(&pulse, now)
+        );
+    }
+
+    #[test]
+    fn rejects_a_pool_outside_any_filter() {
+        let now = Utc::now();
+        let pulse = PulseDataResponse::sample(now);
+        for extra in [
+            json!({ "factories": { "pumpFun": false, "pumpSwap": true } }),
+            json!({ "searchKeywords": ["dog"] }),
+            json!({ "excludeKeywords": ["cat"] }),
+            json!({ "liquidity": { "min": 31 } }),
+            json!({ "top10Holders": { "max": 10 } }),
+            json!({ "website": true }),
+            // An empty link doesn't count
+            json!({ "telegram": true }),
+            json!({ "minSocials": 2 }),
+        ] {
+            assert!(!filters(extra.clone()).matches(&pulse, now), "{extra}");
+        }
+    }
 }
@@ -0,0 +1,10 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FundingTransfer {
+    pub source: String,
+    pub amount: f64,
+    pub hash: String,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::extra::HolderResponse;
+
+// Top holders of a mint plus the number of distinct owners holding any of it
+#[derive(Debug, Serialize)]
+pub struct HolderList {
+    pub holders: Vec<HolderResponse>,
+    pub total: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HolderExportEntry {
+    pub owner: String,
+    pub amount: f64,
+    pub percent_of_supply: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HolderBucket {
+    pub label: String,
+    // share of supply per holder, lower bound inclusive, upper bound exclusive
+    pub min_percent: f64,
+    pub max_percent: Option<f64>,
+    pub holder_count: u64,
+    pub holders_percent: f64,
+    pub supply_percent: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HolderDistribution {
+    pub mint: String,
+    pub total_holders: u64,
+    pub buckets: Vec<HolderBucket>,
+    // 0 = perfectly even, 1 = one wallet holds everything
+    pub gini: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HolderCountPoint {
+    pub bucket: DateTime<Utc>,
+    // Owners whose first non-zero balance landed in this bucket
+    pub new_holders: u64,
+    // Running total of `new_holders` up to and including this bucket
+    pub holders: u64,
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiquidityLockStatus {
+    // LP tokens sit in a known burn address and can never be withdrawn
+    Burned,
+    // No removals, and the quote reserve is still within LP_RESERVE_TOLERANCE of its start
+    Locked,
+    // At least one REMOVE event drained liquidity from the pool
+    Unlocked,
+    // No evidence either way: nothing indexed yet, or reserves moved without a REMOVE
+    Unknown,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LiquidityLockResponse {
+    pub pool_address: String,
+    pub status: LiquidityLockStatus,
+    pub factory: String,
+    pub remove_count: u64,
+    pub removed_quote: f64,
+    pub initial_quote_reserve: f64,
+    pub current_quote_reserve: f64,
+    pub burned_lp_amount: f64,
+}
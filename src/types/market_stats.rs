@@ -0,0 +1,19 @@
+use clickhouse::Row;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MarketStats {
+    pub volume_sol_24h: f64,
+    pub new_pools_24h: u64,
+    pub migrations_24h: u64,
+    // None when nothing traded in the last 24h
+    pub top_factory: Option<String>,
+    pub top_factory_volume_sol: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Row)]
+pub struct FactoryStat {
+    pub factory: String,
+    pub pools_created: u64,
+    pub volume_sol: f64,
+}
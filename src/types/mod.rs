@@ -1,4 +1,15 @@
+pub mod authority;
 pub mod candlestick;
+pub mod creator;
+pub mod curve;
+pub mod dev_activity;
+pub mod exists;
 pub mod filter;
+pub mod funding;
+pub mod holders;
+pub mod liquidity;
+pub mod market_stats;
+pub mod pnl;
 pub mod pulse;
 pub mod token_info;
+pub mod whale_trades;
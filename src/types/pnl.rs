@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PoolPnl {
+    pub pool_address: String,
+    pub token_base_address: String,
+    pub native_spent: f64,
+    pub native_received: f64,
+    // Token amounts are decimal-adjusted, like swaps.base_amount and remaining_tokens
+    pub tokens_bought: f64,
+    pub tokens_sold: f64,
+    // None when the token's decimals aren't indexed yet
+    pub remaining_tokens: Option<f64>,
+    pub price_native: f64,
+    // native_spent / tokens_bought and native_received / tokens_sold
+    pub avg_buy_price: Option<f64>,
+    pub avg_sell_price: Option<f64>,
+    // native_received - native_spent
+    pub realized: f64,
+    // remaining_tokens * price_native
+    pub unrealized: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletPnl {
+    pub wallet: String,
+    pub pools: Vec<PoolPnl>,
+    pub total_realized: f64,
+    pub total_unrealized: f64,
+    pub total_pnl: f64,
+}
@@ -69,7 +69,9 @@ pub struct PulseDataResponse {
     pub website: Option<String>,
     pub twitter: Option<String>,
     pub telegram: Option<String>,
-    // pub discord: Option<String>,
+    pub discord: Option<String>,
+    // The token isn't indexed yet: name and symbol are empty and decimals are 0
+    pub metadata_pending: bool,
     pub top10_holders_percent: f64,
     pub dev_holds_percent: f64,
     pub snipers_holds_percent: f64,
@@ -77,6 +79,8 @@ pub struct PulseDataResponse {
     // pub bundlers_hold_percent: f64,
     pub volume_sol: f64,
     pub market_cap_sol: f64,
+    // False for a pool with no swaps yet, whose price, volume and liquidity are defaults
+    pub has_trades: bool,
 
     // pub fees_sol: f64,
     pub liquidity_sol: f64,
@@ -89,6 +93,9 @@ pub struct PulseDataResponse {
     pub num_holders: i64,
     // pub num_trading_bot_users: i64,
     pub created_at: DateTime<Utc>,
+    // Seconds since created_at when the row was built, so clients don't each derive it
+    pub age_seconds: i64,
+    pub lifecycle: Lifecycle,
     // pub extra: String,
     // pub dex_paid: bool,
     pub migration_count: i64,
@@ -98,6 +105,78 @@ pub struct PulseDataResponse {
     // pub kol_count: i64,
 }
 
+// Stage label for UIs. A PumpFun pool is `new` for its first PULSE_NEW_POOL_SECONDS
+// (default 900), then `bonding` until it migrates to PumpSwap.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Lifecycle {
+    New,
+    Bonding,
+    Migrated,
+}
+
+const DEFAULT_NEW_POOL_SECONDS: i64 = 900;
+
+fn new_pool_seconds() -> i64 {
+    std::env::var("PULSE_NEW_POOL_SECONDS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_NEW_POOL_SECONDS)
+}
+
+impl Lifecycle {
+    pub fn classify(protocol: &str, age_seconds: i64) -> Self {
+        if protocol == "PumpSwap" {
+            Lifecycle::Migrated
+        } else if age_seconds < new_pool_seconds() {
+            Lifecycle::New
+        } else {
+            Lifecycle::Bonding
+        }
+    }
+}
+
+// A PumpFun pool five minutes old with one social, for tests
+#[cfg(test)]
+impl PulseDataResponse {
+    pub fn sample(now: DateTime<Utc>) -> Self {
+        Self {
+            pair_address: "pool".to_string(),
+            token_address: "mint".to_string(),
+            creator: "creator".to_string(),
+            token_name: Some("Moon Cat".to_string()),
+            token_symbol: Some("MCAT".to_string()),
+            token_image: None,
+            token_decimals: 6,
+            protocol: "PumpFun".to_string(),
+            website: None,
+            twitter: Some("https://x.com/mooncat".to_string()),
+            telegram: Some(String::new()),
+            discord: None,
+            metadata_pending: false,
+            top10_holders_percent: 20.0,
+            dev_holds_percent: 5.0,
+            snipers_holds_percent: 1.0,
+            volume_sol: 12.0,
+            market_cap_sol: 40.0,
+            has_trades: true,
+            liquidity_sol: 30.0,
+            liquidity_token: 1_000_000.0,
+            bonding_curve_percent: 10.0,
+            supply: 1_000_000_000.0,
+            num_txns: 40,
+            num_buys: 30,
+            num_sells: 10,
+            num_holders: 25,
+            created_at: now - chrono::Duration::minutes(5),
+            age_seconds: 300,
+            lifecycle: Lifecycle::New,
+            migration_count: 0,
+            dev_wallet_funding: None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct DevWalletFunding {
     pub funding_wallet_address: String,
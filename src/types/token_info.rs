@@ -2,6 +2,11 @@ use clickhouse::Row;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    defaults::DEFAULT_TOKEN_DECIMALS,
+    utils::{calculate_percentage, clamp_percentage},
+};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TokenInfo {
     pub bundlers_hold_percent: f64,
@@ -11,15 +16,71 @@ pub struct TokenInfo {
     pub num_holders: i64,
     pub snipers_hold_percent: f64,
     pub top10_holders_percent: f64,
+    // Share of supply held by the `top_n` largest holders
+    pub top_n: u32,
+    pub top_n_holders_percent: f64,
+    // true when the mint had no decimals metadata and a fallback was used
+    pub decimals_defaulted: bool,
 }
 
 #[derive(Row, Deserialize, Serialize, Debug)]
 pub struct TokenInfoRow {
     pub top10_amount_raw: f64,
+    pub top_n: u32,
+    pub top_n_amount_raw: f64,
     pub dev_amount_raw: f64,
     pub snipers_amount_raw: f64,
     pub num_holders: u64,
     pub token_supply: f64,
     pub decimals: i8,
     pub bundlers_amount_raw: f64,
+    pub has_decimals: bool,
+}
+
+impl From<TokenInfoRow> for TokenInfo {
+    fn from(row: TokenInfoRow) -> Self {
+        // Missing token_initialize_events leaves decimals at 0, which would skip the
+        // decimal adjustment entirely, so fall back to the pump.fun default instead
+        let decimals_defaulted = !row.has_decimals;
+        let decimals = if decimals_defaulted {
+            DEFAULT_TOKEN_DECIMALS
+        } else {
+            row.decimals as u8
+        };
+
+        // Calculate scale factor in Rust: 10^decimals
+        let scale_factor = 10.0_f64.powi(decimals as i32);
+
+        let bundlers_decimal_adjusted = row.bundlers_amount_raw / scale_factor;
+        let dev_decimal_adjusted = row.dev_amount_raw / scale_factor;
+        let snipers_decimal_adjusted = row.snipers_amount_raw / scale_factor;
+        let top10_decimal_adjusted = row.top10_amount_raw / scale_factor;
+        let top_n_decimal_adjusted = row.top_n_amount_raw / scale_factor;
+
+        TokenInfo {
+            bundlers_hold_percent: clamp_percentage(calculate_percentage(
+                bundlers_decimal_adjusted,
+                row.token_supply,
+            )),
+            dev_holds_percent: clamp_percentage(calculate_percentage(
+                dev_decimal_adjusted,
+                row.token_supply,
+            )),
+            num_holders: row.num_holders as i64,
+            snipers_hold_percent: clamp_percentage(calculate_percentage(
+                snipers_decimal_adjusted,
+                row.token_supply,
+            )),
+            top10_holders_percent: clamp_percentage(calculate_percentage(
+                top10_decimal_adjusted,
+                row.token_supply,
+            )),
+            top_n: row.top_n,
+            top_n_holders_percent: clamp_percentage(calculate_percentage(
+                top_n_decimal_adjusted,
+                row.token_supply,
+            )),
+            decimals_defaulted,
+        }
+    }
 }
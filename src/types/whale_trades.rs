@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WhaleTrade {
+    pub hash: String,
+    pub pool_address: String,
+    pub trader: String,
+    pub swap_type: String,
+    pub base_amount: f64,
+    pub quote_amount: f64,
+    pub price_sol: f64,
+    pub slot: i64,
+    pub created_at: DateTime<Utc>,
+    pub token_address: String,
+    pub name: String,
+    pub symbol: String,
+    pub image: Option<String>,
+}
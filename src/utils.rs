@@ -1,11 +1,14 @@
 use fixnum::{
     FixedPoint,
+    ops::{CheckedAdd, CheckedSub},
     typenum::{U4, U8, U12, U18},
 };
 use rust_decimal::Decimal;
 use std::ops::Mul;
 use std::str::FromStr; // Add this import
 
+use crate::defaults::SOL_TOKEN;
+
 pub type Decimal32 = FixedPoint<i32, U4>; // Decimal(9, 4) = Decimal32(4)
 pub type Decimal64 = FixedPoint<i64, U8>; // Decimal(18, 8) = Decimal64(8)
 pub type Decimal128 = FixedPoint<i128, U12>; // Decimal(38, 12) = Decimal128(12)
@@ -20,8 +23,87 @@ pub type Decimal18 = FixedPoint<i128, U18>; // Decimal(38, 18) = Decimal18(18)
 //         Decimal18::from_bits(s)
 //     }
 // }
-pub fn calculate_market_cap(price_sol: f64, token_supply: f64) -> f64 {
-    price_sol * token_supply
+// Serializes a Decimal18 as its base-10 string in every format, so JSON clients get
+// all 18 digits rather than a rounded float. Deserialization keeps fixnum's default,
+// which reads the raw repr from ClickHouse's RowBinary.
+pub mod decimal18_as_string {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::Decimal18;
+
+    pub fn serialize<S: Serializer>(value: &Decimal18, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal18, D::Error> {
+        Decimal18::deserialize(deserializer)
+    }
+
+    pub mod option {
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        use super::Decimal18;
+
+        pub fn serialize<S: Serializer>(
+            value: &Option<Decimal18>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match value {
+                Some(value) => serializer.collect_str(value),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Decimal18>, D::Error> {
+            Option::<Decimal18>::deserialize(deserializer)
+        }
+    }
+}
+
+// Checked Decimal18 arithmetic; `None` on overflow
+pub fn add_dec18(a: Decimal18, b: Decimal18) -> Option<Decimal18> {
+    a.cadd(b).ok()
+}
+
+pub fn sub_dec18(a: Decimal18, b: Decimal18) -> Option<Decimal18> {
+    a.csub(b).ok()
+}
+
+// Swap prices and reserves are indexed with SOL's 9 decimals regardless of the quote
+// token; this rescales them to the pool's real quote token (1000x for 6-decimal USDC)
+pub fn quote_decimals_factor(quote_decimals: u8) -> f64 {
+    10f64.powi(SOL_TOKEN.decimals as i32 - quote_decimals as i32)
+}
+
+// Reserves and price of a pool as base token vs quote token
+#[derive(Debug, Clone, Copy)]
+pub struct PoolReserves {
+    pub base: f64,
+    pub quote: f64,
+    // quote per base
+    pub price: f64,
+}
+
+impl PoolReserves {
+    // Takes reserves and price as indexed. A `reversed` pool holds the quote token on its
+    // base side, so its reserves are swapped and its price inverted.
+    pub fn oriented(reversed: bool, base: f64, quote: f64, price: f64) -> Self {
+        if !reversed {
+            return Self { base, quote, price };
+        }
+        Self {
+            base: quote,
+            quote: base,
+            price: if price == 0.0 { 0.0 } else { 1.0 / price },
+        }
+    }
+}
+
+// Market cap in units of the pool's quote token
+pub fn calculate_market_cap(price_sol: f64, token_supply: f64, quote_decimals: u8) -> f64 {
+    price_sol * quote_decimals_factor(quote_decimals) * token_supply
 }
 
 // f64 variant for places that still operate on floating-point values
@@ -31,3 +113,124 @@ pub fn calculate_percentage(amount: f64, token_supply: f64) -> f64 {
     }
     ((amount) / token_supply) * 100.0
 }
+
+// Quote paid or received per token; `None` when no tokens moved
+pub fn average_price(quote_amount: f64, token_amount: f64) -> Option<f64> {
+    if token_amount == 0.0 {
+        return None;
+    }
+    Some(quote_amount / token_amount)
+}
+
+// Percent move from `open` to `close`; undefined when `open` is zero
+pub fn price_change_percent(open: f64, close: f64) -> Option<f64> {
+    if open == 0.0 {
+        return None;
+    }
+    Some((close - open) / open * 100.0)
+}
+
+// Share of traded volume that was buying, in 0..=1; undefined when nothing traded
+pub fn buy_pressure(buy_volume: f64, sell_volume: f64) -> Option<f64> {
+    let total = buy_volume + sell_volume;
+    if total == 0.0 {
+        return None;
+    }
+    Some(buy_volume / total)
+}
+
+// Keeps holder percentages inside 0..=100 when supply or decimals are off
+pub fn clamp_percentage(percentage: f64) -> f64 {
+    if percentage.is_nan() {
+        return 0.0;
+    }
+    percentage.clamp(0.0, 100.0)
+}
+
+// Gini coefficient of a set of balances; sorts `amounts` in place
+pub fn gini_coefficient(amounts: &mut [f64]) -> f64 {
+    let n = amounts.len();
+    let total: f64 = amounts.iter().sum();
+    if n == 0 || total <= 0.0 {
+        return 0.0;
+    }
+    amounts.sort_by(|a, b| a.total_cmp(b));
+    let weighted: f64 = amounts
+        .iter()
+        .enumerate()
+        .map(|(i, amount)| (i as f64 + 1.0) * amount)
+        .sum();
+    let n = n as f64;
+    (2.0 * weighted) / (n * total) - (n + 1.0) / n
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::defaults::USDC_TOKEN;
+
+    // USD per SOL used to compare caps across quote tokens
+    const SOL_USD: f64 = 150.0;
+
+    #[test]
+    fn sol_and_usdc_quoted_pools_have_the_same_usd_cap() {
+        let supply = 1_000_000_000.0;
+        // 0.001 SOL per token, i.e. $0.15
+        let sol_cap = calculate_market_cap(0.001, supply, SOL_TOKEN.decimals);
+        // $0.15 per token, indexed with SOL's 9 decimals instead of USDC's 6
+        let usdc_cap = calculate_market_cap(0.15 / 1000.0, supply, USDC_TOKEN.decimals);
+
+        assert!((sol_cap * SOL_USD - usdc_cap).abs() < 1e-3);
+        assert!((usdc_cap - 150_000_000.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn sol_quoted_prices_are_not_rescaled() {
+        assert_eq!(quote_decimals_factor(SOL_TOKEN.decimals), 1.0);
+        assert_eq!(quote_decimals_factor(USDC_TOKEN.decimals), 1000.0);
+    }
+
+    #[test]
+    fn buy_pressure_is_the_buying_share_of_volume() {
+        assert_eq!(buy_pressure(70.0, 30.0), Some(0.7));
+        assert_eq!(buy_pressure(0.0, 0.0), None);
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Price {
+        #[serde(with = "decimal18_as_string")]
+        price: Decimal18,
+    }
+
+    #[test]
+    fn decimal18_round_trips_all_18_digits() {
+        for digits in ["123456789.123456789012345678", "-0.000000000000000001"] {
+            let price = Price {
+                price: Decimal18::from_str(digits).unwrap(),
+            };
+            let json = serde_json::to_value(&price).unwrap();
+            assert_eq!(json["price"], digits);
+            assert_eq!(serde_json::from_value::<Price>(json).unwrap(), price);
+        }
+
+        let zero = serde_json::to_value(Price {
+            price: Decimal18::from_bits(0),
+        })
+        .unwrap();
+        assert_eq!(zero["price"], "0.0");
+    }
+
+    #[test]
+    fn checked_dec18_arithmetic() {
+        let one = Decimal18::from_str("1").unwrap();
+        let half = Decimal18::from_str("0.5").unwrap();
+        assert_eq!(add_dec18(one, half), Decimal18::from_str("1.5").ok());
+        assert_eq!(sub_dec18(half, one), Decimal18::from_str("-0.5").ok());
+        assert_eq!(add_dec18(Decimal18::from_bits(i128::MAX), one), None);
+        assert_eq!(sub_dec18(Decimal18::from_bits(i128::MIN), one), None);
+    }
+}
@@ -1,12 +1,28 @@
 pub mod new_pool_event;
 pub mod new_swap_event;
 pub mod store;
+pub mod swap_stream;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use socketioxide::extract::{Data, SocketRef};
+use socketioxide::{
+    SendError, SocketError, SocketIo,
+    extract::{Data, SocketRef, State, TryData},
+};
 
-use tracing::info;
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use spl_token::solana_program::pubkey::Pubkey;
+use tokio::sync::broadcast::{Receiver, error::RecvError};
+use tracing::{info, warn};
+
+use crate::{
+    metrics::METRICS,
+    middleware::auth::{ApiKeyAuth, header_key},
+    types::pulse::PulseDataResponse,
+};
+
+use swap_stream::SWAP_STREAM_NS;
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(untagged)]
@@ -17,6 +33,182 @@ enum Room {
     UpdatePulse,
 }
 
+// Per-topic subscriptions, sent as `subscribe` / `unsubscribe`
+// e.g. `{"type": "pool_swaps", "pool_address": "..."}`
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Subscription {
+    PoolSwaps { pool_address: String },
+    WalletTrades { wallet: String },
+}
+
+impl Subscription {
+    fn room(&self) -> Option<String> {
+        match self {
+            Subscription::PoolSwaps { pool_address } => Pubkey::from_str(pool_address)
+                .ok()
+                .map(|pool| pool_swaps_room(&pool.to_string())),
+            Subscription::WalletTrades { wallet } => Pubkey::from_str(wallet)
+                .ok()
+                .map(|wallet| wallet_trades_room(&wallet.to_string())),
+        }
+    }
+}
+
+// Event streams a client can subscribe to, announced in `hello` on connect. Adding a
+// variant (and listing it in ALL) is all a new stream needs to be advertised.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    // Pulse rows as `new-pair` events, after joining the `new-pair` room
+    UpdatePulseV2,
+    // `{"type": "pool_swaps", ...}` subscriptions
+    PoolSwaps,
+    // `{"type": "wallet_trades", ...}` subscriptions
+    WalletTrades,
+    // Snapshot plus live swaps for one pool, on the `/swaps/stream` namespace
+    SwapStream,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 4] = [
+        Capability::UpdatePulseV2,
+        Capability::PoolSwaps,
+        Capability::WalletTrades,
+        Capability::SwapStream,
+    ];
+}
+
+// First event on every socket, so clients can tell which event formats this server speaks
+#[derive(Debug, Serialize)]
+struct Hello {
+    version: &'static str,
+    capabilities: &'static [Capability],
+}
+
+impl Hello {
+    const CURRENT: Hello = Hello {
+        version: env!("CARGO_PKG_VERSION"),
+        capabilities: &Capability::ALL,
+    };
+}
+
+// Room that receives `swap_created` events for one pool
+pub fn pool_swaps_room(pool_address: &str) -> String {
+    format!("s:{}", pool_address)
+}
+
+// Room that receives `swap_created` events made by one wallet
+pub fn wallet_trades_room(wallet: &str) -> String {
+    format!("w:{}", wallet)
+}
+
+// Packets a socket may have queued before it counts as a slow consumer
+pub fn max_buffer_size_from_env() -> usize {
+    std::env::var("WS_MAX_BUFFER_SIZE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|size| *size > 0)
+        .unwrap_or(128)
+}
+
+// Emits `data` to each socket individually. socketioxide buffers at most
+// WS_MAX_BUFFER_SIZE packets per socket; a socket whose buffer is full isn't keeping up
+// and is disconnected instead of being allowed to hold back or drop events silently.
+pub fn emit_or_shed<T: Serialize + ?Sized>(sockets: Vec<SocketRef>, event: &str, data: &T) {
+    for socket in sockets {
+        match socket.emit(event, data) {
+            Ok(()) => {}
+            Err(SendError::Socket(SocketError::InternalChannelFull)) => {
+                warn!("Disconnecting slow socket {:?} on {}", socket.id, event);
+                METRICS.slow_consumer_disconnected();
+                let _ = socket.disconnect();
+            }
+            Err(e) => warn!("Failed to emit {} to {:?}: {}", event, socket.id, e),
+        }
+    }
+}
+
+// Emits every row published on the pulse feed to all sockets as `new-pair`
+pub async fn relay_pulse_feed(io: SocketIo, mut rx: Receiver<Arc<PulseDataResponse>>) {
+    loop {
+        match rx.recv().await {
+            Ok(pulse) => {
+                METRICS.ws_event("new-pair", 1);
+                emit_or_shed(io.sockets(), "new-pair", &*pulse);
+            }
+            Err(RecvError::Lagged(skipped)) => {
+                warn!(
+                    "Socket.io relay fell behind, skipped {} pulse rows",
+                    skipped
+                );
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+// How often idle sockets are looked for
+const IDLE_SWEEP_INTERVAL: Duration = Duration::from_secs(15);
+
+// Sockets silent for longer than this are disconnected. Only client events count, not
+// engine.io heartbeats, so a quiet listener looks idle; the reaper is off unless
+// WS_IDLE_TIMEOUT_SECS is set above 0.
+pub fn idle_timeout_from_env() -> Option<Duration> {
+    let secs = std::env::var("WS_IDLE_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+// Periodically disconnects sockets that haven't sent anything within `idle_timeout`
+pub async fn reap_idle_sockets(io: SocketIo, idle_timeout: Duration) {
+    let mut interval = tokio::time::interval(IDLE_SWEEP_INTERVAL);
+    loop {
+        interval.tick().await;
+        for sid in store::idle_sockets(idle_timeout) {
+            let socket = io
+                .get_socket(sid)
+                .or_else(|| io.of(SWAP_STREAM_NS).and_then(|ns| ns.get_socket(sid)));
+            match socket {
+                Some(socket) => {
+                    info!("Disconnecting idle socket {:?}", sid);
+                    let _ = socket.disconnect();
+                }
+                None => store::forget(sid),
+            }
+        }
+    }
+}
+
+// `auth` payload of a socket.io handshake. Browsers can't set handshake headers, so they
+// send the API key as `auth: { token }` instead.
+#[derive(Debug, Deserialize)]
+pub struct HandshakeAuth {
+    token: Option<String>,
+}
+
+// `require_api_key` for socket.io, as connect middleware on every namespace. The key is read
+// from the handshake headers like an HTTP request's, else from the `auth` payload. A refused
+// client gets a `connect_error` with the reason.
+pub async fn require_socket_api_key(
+    socket: SocketRef,
+    State(auth): State<Arc<ApiKeyAuth>>,
+    TryData(payload): TryData<HandshakeAuth>,
+) -> Result<(), &'static str> {
+    if !auth.is_enabled() {
+        return Ok(());
+    }
+    let token = payload.ok().and_then(|payload| payload.token);
+    let parts = socket.req_parts();
+    match header_key(&parts.headers).or(token.as_deref()) {
+        Some(key) if auth.is_valid(key) => Ok(()),
+        Some(_) => Err("invalid API key"),
+        None => Err("missing API key"),
+    }
+}
+
 impl Room {
     fn as_str(&self) -> String {
         match self {
@@ -28,10 +220,19 @@ impl Room {
 
 pub async fn on_connect(socket: SocketRef) {
     info!("Client connected: {:?}", socket.id);
+    METRICS.socket_connected();
+    store::touch(socket.id);
+    if let Err(e) = socket.emit("hello", &Hello::CURRENT) {
+        warn!("Failed to send hello to {:?}: {}", socket.id, e);
+    }
+    socket.on_disconnect(|socket: SocketRef| {
+        METRICS.socket_disconnected();
+        store::forget(socket.id);
+    });
 
-    socket.on(
-        "join",
-        |_socket: SocketRef, Data::<Room>(room)| match room {
+    socket.on("join", |_socket: SocketRef, Data::<Room>(room)| {
+        store::touch(_socket.id);
+        match room {
             Room::NewPair => {
                 info!("✅ Joining new-pair room");
                 _socket.join(room.as_str());
@@ -40,12 +241,264 @@ pub async fn on_connect(socket: SocketRef) {
                 info!("✅ Joining update-pulse room");
                 // _socket.join(room.as_str());
             }
+        }
+    });
+
+    socket.on(
+        "subscribe",
+        |socket: SocketRef, Data::<Subscription>(subscription)| {
+            store::touch(socket.id);
+            match subscription.room() {
+                Some(room) => {
+                    info!("✅ Joining {}", room);
+                    socket.join(room);
+                }
+                None => warn!(
+                    "Ignoring subscribe with invalid address: {:?}",
+                    subscription
+                ),
+            }
         },
     );
 
-    socket.on("message", |_socket: SocketRef, Data::<Value>(data)| {
-        info!("✅ Successfully parsed: {:?}", data);
-        // info!("Pool address: {}", data.pool_address);
-        // info!("Room: {}", data.room);
-    })
+    socket.on(
+        "unsubscribe",
+        |socket: SocketRef, Data::<Subscription>(subscription)| {
+            store::touch(socket.id);
+            if let Some(room) = subscription.room() {
+                socket.leave(room);
+            }
+        },
+    );
+
+    socket.on("message", on_message);
+}
+
+// Any message keeps the socket alive; `{"method": "ping"}` is answered with `pong`
+pub fn on_message(socket: SocketRef, Data(data): Data<Value>) {
+    store::touch(socket.id);
+    if data.get("method").and_then(Value::as_str) == Some("ping") {
+        let _ = socket.emit("pong", &());
+        return;
+    }
+    info!("✅ Successfully parsed: {:?}", data);
+    // info!("Pool address: {}", data.pool_address);
+    // info!("Room: {}", data.room);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::Request,
+    };
+    use serde_json::json;
+    use socketioxide::{SocketIo, handler::ConnectHandler};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    const POOL_A: &str = "So11111111111111111111111111111111111111112";
+    const POOL_B: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    // Speaks engine.io long-polling to the socket.io layer; `body` is POSTed when set
+    async fn poll(app: &Router, sid: Option<&str>, body: Option<String>) -> String {
+        let mut uri = "/socket.io/?EIO=4&transport=polling".to_string();
+        if let Some(sid) = sid {
+            uri.push_str(&format!("&sid={sid}"));
+        }
+        let request = match body {
+            Some(body) => Request::post(uri).body(Body::from(body)),
+            None => Request::get(uri).body(Body::empty()),
+        };
+        let response = app.clone().oneshot(request.unwrap()).await.unwrap();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    // Opens an engine.io session without joining a namespace
+    async fn open(app: &Router) -> String {
+        let handshake = poll(app, None, None).await;
+        let open: serde_json::Value = serde_json::from_str(&handshake[1..]).unwrap();
+        open["sid"].as_str().unwrap().to_string()
+    }
+
+    // Opens an engine.io session and connects it to the default namespace
+    async fn connect(app: &Router) -> String {
+        let sid = open(app).await;
+        poll(app, Some(&sid), Some("40".to_string())).await;
+        assert!(poll(app, Some(&sid), None).await.starts_with("40"));
+        sid
+    }
+
+    #[tokio::test]
+    async fn hello_announces_the_version_and_capabilities() {
+        let (layer, io) = SocketIo::new_layer();
+        io.ns("/", on_connect);
+        let app = Router::new().layer(layer);
+
+        let handshake = poll(&app, None, None).await;
+        let open: serde_json::Value = serde_json::from_str(&handshake[1..]).unwrap();
+        let sid = open["sid"].as_str().unwrap();
+        poll(&app, Some(sid), Some("40".to_string())).await;
+        let received = poll(&app, Some(sid), None).await;
+
+        let hello = received
+            .split('\u{1e}')
+            .find_map(|packet| packet.strip_prefix("42"))
+            .unwrap();
+        let hello: serde_json::Value = serde_json::from_str(hello).unwrap();
+        assert_eq!(
+            hello,
+            json!([
+                "hello",
+                {
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "capabilities": [
+                        "update_pulse_v2",
+                        "pool_swaps",
+                        "wallet_trades",
+                        "swap_stream",
+                    ],
+                }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn handshakes_need_an_api_key_when_keys_are_set() {
+        let (layer, io) = SocketIo::builder()
+            .with_state(Arc::new(ApiKeyAuth::with_keys("key-1")))
+            .build_layer();
+        io.ns("/", on_connect.with(require_socket_api_key));
+        let app = Router::new().layer(layer);
+
+        for (auth, accepted) in [
+            ("", false),
+            (r#"{"token":"nope"}"#, false),
+            (r#"{"token":"key-1"}"#, true),
+        ] {
+            let sid = open(&app).await;
+            poll(&app, Some(&sid), Some(format!("40{auth}"))).await;
+            let reply = poll(&app, Some(&sid), None).await;
+            assert_eq!(reply.starts_with("40"), accepted, "{auth}: {reply}");
+            if !accepted {
+                assert!(reply.starts_with("44"), "{auth}: {reply}");
+            }
+        }
+        assert_eq!(io.sockets().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn swaps_only_reach_sockets_subscribed_to_the_pool() {
+        let (layer, io) = SocketIo::new_layer();
+        io.ns("/", on_connect);
+        let app = Router::new().layer(layer);
+        let sid = &connect(&app).await;
+
+        let subscribe = json!(["subscribe", { "type": "pool_swaps", "pool_address": POOL_A }]);
+        poll(&app, Some(sid), Some(format!("42{subscribe}"))).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        for pool in [POOL_B, POOL_A] {
+            let room = pool_swaps_room(pool);
+            io.to(room.clone())
+                .emit(room, &json!({ "pool_address": pool }))
+                .await
+                .unwrap();
+        }
+
+        let received = poll(&app, Some(sid), None).await;
+        assert!(received.contains(&format!("s:{POOL_A}")), "{received}");
+        assert!(!received.contains(POOL_B), "{received}");
+    }
+
+    #[tokio::test]
+    async fn wallet_subscribers_only_get_that_wallets_swaps() {
+        const WALLET_A: &str = "Dcd19vVNc8tzNJLyLQUhdR7do7KaqMijgfxxJdcDM4ei";
+        const WALLET_B: &str = "9Ck8DLv2Us3M6R5CVXNt1yS4mYqKA5GnDRnhiWJgcSmu";
+        let (layer, io) = SocketIo::new_layer();
+        io.ns("/", on_connect);
+        let app = Router::new().layer(layer);
+        let sid = &connect(&app).await;
+
+        let subscribe = json!(["subscribe", { "type": "wallet_trades", "wallet": WALLET_A }]);
+        poll(&app, Some(sid), Some(format!("42{subscribe}"))).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        for wallet in [WALLET_B, WALLET_A] {
+            let room = wallet_trades_room(wallet);
+            io.to(room.clone())
+                .emit(room, &json!({ "creator": wallet }))
+                .await
+                .unwrap();
+        }
+
+        let received = poll(&app, Some(sid), None).await;
+        assert!(received.contains(&format!("w:{WALLET_A}")), "{received}");
+        assert!(!received.contains(WALLET_B), "{received}");
+    }
+
+    fn slow_consumers_total() -> u64 {
+        METRICS
+            .render()
+            .lines()
+            .find_map(|line| line.strip_prefix("socketio_slow_consumers_total "))
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn slow_consumers_are_disconnected() {
+        let (layer, io) = SocketIo::builder().max_buffer_size(2).build_layer();
+        io.ns("/", on_connect);
+        let app = Router::new().layer(layer);
+        connect(&app).await;
+
+        // The client stops polling, so nothing drains its buffer
+        let shed_before = slow_consumers_total();
+        for i in 0..5 {
+            emit_or_shed(io.sockets(), "new-pair", &json!({ "i": i }));
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert!(io.sockets().is_empty());
+        assert!(slow_consumers_total() > shed_before);
+    }
+
+    #[tokio::test]
+    async fn idle_sockets_are_disconnected() {
+        let (layer, io) = SocketIo::new_layer();
+        io.ns("/", on_connect);
+        io.ns(SWAP_STREAM_NS, swap_stream::on_swap_stream_connect);
+        let app = Router::new().layer(layer);
+        connect(&app).await;
+        let active = connect(&app).await;
+        assert_eq!(io.sockets().len(), 2);
+        let streaming = open(&app).await;
+        let join = format!("40{SWAP_STREAM_NS},");
+        poll(&app, Some(&streaming), Some(join.clone())).await;
+        assert!(poll(&app, Some(&streaming), None).await.starts_with(&join));
+        let stream_sockets = || io.of(SWAP_STREAM_NS).unwrap().sockets();
+        assert_eq!(stream_sockets().len(), 1);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let ping = json!(["message", { "method": "ping" }]);
+        poll(&app, Some(&active), Some(format!("42{ping}"))).await;
+        assert!(poll(&app, Some(&active), None).await.contains("pong"));
+
+        let reaper = tokio::spawn(reap_idle_sockets(io.clone(), Duration::from_millis(50)));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        reaper.abort();
+
+        let sockets = io.sockets();
+        assert_eq!(sockets.len(), 1);
+        assert_eq!(sockets[0].id.to_string(), active);
+        // Swap stream sockets are tracked and reaped the same way
+        assert!(stream_sockets().is_empty());
+    }
 }
@@ -0,0 +1,30 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use socketioxide::socket::Sid;
+
+// When each connected socket last sent us anything
+static LAST_SEEN: LazyLock<Mutex<HashMap<Sid, Instant>>> = LazyLock::new(Default::default);
+
+pub fn touch(sid: Sid) {
+    LAST_SEEN.lock().unwrap().insert(sid, Instant::now());
+}
+
+pub fn forget(sid: Sid) {
+    LAST_SEEN.lock().unwrap().remove(&sid);
+}
+
+// Sockets that have been silent for longer than `idle_timeout`
+pub fn idle_sockets(idle_timeout: Duration) -> Vec<Sid> {
+    let now = Instant::now();
+    LAST_SEEN
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, seen)| now.duration_since(**seen) > idle_timeout)
+        .map(|(sid, _)| *sid)
+        .collect()
+}
@@ -0,0 +1,265 @@
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use serde::Deserialize;
+use serde_json::json;
+use socketioxide::{
+    SocketIo,
+    extract::{Data, SocketRef, State},
+    socket::Sid,
+};
+use spl_token::solana_program::pubkey::Pubkey;
+use tracing::{info, warn};
+
+use crate::{models::swap::DBSwap, services::clickhouse::ClickhouseService};
+
+use super::{emit_or_shed, on_message, store};
+
+// Namespace for a pool's full swap tape, as opposed to the batched pulse on `/`
+pub const SWAP_STREAM_NS: &str = "/swaps/stream";
+
+#[derive(Debug, Deserialize)]
+struct StreamRequest {
+    pool_address: String,
+}
+
+// Live swaps held back from sockets whose snapshot of that pool is still loading. A socket
+// joins the pool's room before the snapshot query, so nothing published meanwhile is lost.
+#[derive(Clone, Default)]
+pub struct PendingSnapshots(Arc<Mutex<HashMap<(Sid, String), Vec<DBSwap>>>>);
+
+impl PendingSnapshots {
+    fn start(&self, sid: Sid, pool_address: &str) {
+        self.0
+            .lock()
+            .unwrap()
+            .insert((sid, pool_address.to_string()), Vec::new());
+    }
+
+    // Keeps `swap` for `sid` if its snapshot is loading; false means emit it now
+    fn hold(&self, sid: Sid, swap: &DBSwap) -> bool {
+        let mut pending = self.0.lock().unwrap();
+        match pending.get_mut(&(sid, swap.pool_address.clone())) {
+            Some(held) => {
+                held.push(swap.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Hands `emit` the swaps held for `sid`, minus those already in `snapshot`. It runs under
+    // the lock, so a swap relayed concurrently can't overtake the snapshot.
+    fn finish(
+        &self,
+        sid: Sid,
+        pool_address: &str,
+        snapshot: &[DBSwap],
+        emit: impl FnOnce(Vec<DBSwap>),
+    ) {
+        let mut pending = self.0.lock().unwrap();
+        let held = pending
+            .remove(&(sid, pool_address.to_string()))
+            .unwrap_or_default();
+        let seen: HashSet<&str> = snapshot.iter().map(|swap| swap.hash.as_str()).collect();
+        emit(
+            held.into_iter()
+                .filter(|swap| !seen.contains(swap.hash.as_str()))
+                .collect(),
+        );
+    }
+}
+
+// `subscribe` with `{"pool_address": "..."}` answers with a `snapshot` of the pool's last
+// 20 swaps, oldest first, then the socket gets a `swap` event for every new one
+pub async fn on_swap_stream_connect(socket: SocketRef) {
+    info!("Swap stream client connected: {:?}", socket.id);
+    store::touch(socket.id);
+    socket.on_disconnect(|socket: SocketRef| store::forget(socket.id));
+
+    socket.on("subscribe", on_subscribe);
+    socket.on("message", on_message);
+
+    socket.on(
+        "unsubscribe",
+        |socket: SocketRef, Data::<StreamRequest>(request)| {
+            store::touch(socket.id);
+            if let Ok(pool) = Pubkey::from_str(&request.pool_address) {
+                socket.leave(pool.to_string());
+            }
+        },
+    );
+}
+
+async fn on_subscribe(
+    socket: SocketRef,
+    State(db): State<ClickhouseService>,
+    State(pending): State<PendingSnapshots>,
+    Data(request): Data<StreamRequest>,
+) {
+    store::touch(socket.id);
+    let Ok(pool) = Pubkey::from_str(&request.pool_address) else {
+        warn!("Ignoring swap stream with invalid address: {:?}", request);
+        return;
+    };
+    let pool_address = pool.to_string();
+    // Live swaps are held back from here until the snapshot has been sent
+    pending.start(socket.id, &pool_address);
+    socket.join(pool_address.clone());
+    let snapshot = db.get_pool_swaps(pool_address.clone(), None, None).await;
+    let swaps = snapshot.as_deref().unwrap_or_default();
+    pending.finish(socket.id, &pool_address, swaps, |held| {
+        match &snapshot {
+            Ok(swaps) => {
+                let swaps: Vec<&DBSwap> = swaps.iter().rev().collect();
+                let snapshot = json!({ "pool_address": pool_address, "swaps": swaps });
+                let _ = socket.emit("snapshot", &snapshot);
+            }
+            Err(e) => {
+                warn!("Failed to load swap snapshot for {}: {}", pool_address, e);
+                let _ = socket.emit("error", &json!({ "error": e.to_string() }));
+            }
+        }
+        for swap in &held {
+            emit_or_shed(vec![socket.clone()], "swap", swap);
+        }
+    });
+}
+
+// Appends a `swap_created` event to the tape of the pool it happened in
+pub fn relay_swap(io: &SocketIo, pending: &PendingSnapshots, swap: &DBSwap) {
+    if let Some(ns) = io.of(SWAP_STREAM_NS) {
+        let sockets = ns
+            .to(swap.pool_address.clone())
+            .sockets()
+            .into_iter()
+            .filter(|socket| !pending.hold(socket.id, swap))
+            .collect();
+        emit_or_shed(sockets, "swap", swap);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::{
+        Router,
+        body::{Body, to_bytes},
+        http::Request,
+    };
+    use clickhouse::{
+        Client,
+        test::{Mock, handlers},
+    };
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    const POOL: &str = "So11111111111111111111111111111111111111112";
+
+    async fn poll(app: &Router, sid: Option<&str>, body: Option<String>) -> String {
+        let mut uri = "/socket.io/?EIO=4&transport=polling".to_string();
+        if let Some(sid) = sid {
+            uri.push_str(&format!("&sid={sid}"));
+        }
+        let request = match body {
+            Some(body) => Request::post(uri).body(Body::from(body)),
+            None => Request::get(uri).body(Body::empty()),
+        };
+        let response = app.clone().oneshot(request.unwrap()).await.unwrap();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    // Events received on the swap stream namespace, as `[name, data]`
+    fn events(received: &str) -> Vec<Value> {
+        received
+            .split('\u{1e}')
+            .filter_map(|packet| packet.strip_prefix(&format!("42{SWAP_STREAM_NS},")))
+            .map(|event| serde_json::from_str(event).unwrap())
+            .collect()
+    }
+
+    fn swap(hash: &str, slot: i64) -> DBSwap {
+        DBSwap {
+            pool_address: POOL.to_string(),
+            hash: hash.to_string(),
+            slot,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn subscribers_get_the_snapshot_then_live_swaps() {
+        let mock = Mock::new();
+        // Newest first, as get_pool_swaps returns them
+        mock.add(handlers::provide(vec![swap("b", 2), swap("a", 1)]));
+        let db = ClickhouseService::with_client(Client::default().with_url(mock.url()));
+        let pending = PendingSnapshots::default();
+        let (layer, io) = SocketIo::builder()
+            .with_state(db)
+            .with_state(pending.clone())
+            .build_layer();
+        io.ns(SWAP_STREAM_NS, on_swap_stream_connect);
+        let app = Router::new().layer(layer);
+
+        let handshake = poll(&app, None, None).await;
+        let open: Value = serde_json::from_str(&handshake[1..]).unwrap();
+        let sid = open["sid"].as_str().unwrap();
+        poll(&app, Some(sid), Some(format!("40{SWAP_STREAM_NS},"))).await;
+        assert!(poll(&app, Some(sid), None).await.starts_with("40"));
+
+        let subscribe = json!(["subscribe", { "pool_address": POOL }]);
+        poll(
+            &app,
+            Some(sid),
+            Some(format!("42{SWAP_STREAM_NS},{subscribe}")),
+        )
+        .await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        relay_swap(&io, &pending, &swap("c", 3));
+
+        let events = events(&poll(&app, Some(sid), None).await);
+        assert_eq!(events.len(), 2, "{events:?}");
+        assert_eq!(events[0][0], "snapshot");
+        let hashes: Vec<_> = events[0][1]["swaps"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|swap| swap["hash"].as_str().unwrap())
+            .collect();
+        assert_eq!(hashes, ["a", "b"]);
+        assert_eq!(events[1][0], "swap");
+        assert_eq!(events[1][1]["hash"], "c");
+    }
+
+    #[test]
+    fn swaps_during_a_snapshot_are_held_and_deduplicated() {
+        let pending = PendingSnapshots::default();
+        let sid = Sid::new();
+        assert!(!pending.hold(sid, &swap("a", 1)));
+
+        pending.start(sid, POOL);
+        // Landed in ClickHouse in time for the snapshot query, and one that didn't
+        assert!(pending.hold(sid, &swap("b", 2)));
+        assert!(pending.hold(sid, &swap("c", 3)));
+        let other_pool = DBSwap {
+            pool_address: "other".to_string(),
+            ..swap("x", 3)
+        };
+        assert!(!pending.hold(sid, &other_pool));
+
+        let mut held = Vec::new();
+        pending.finish(sid, POOL, &[swap("b", 2), swap("a", 1)], |swaps| {
+            held = swaps
+        });
+        let hashes: Vec<_> = held.iter().map(|swap| swap.hash.as_str()).collect();
+        assert_eq!(hashes, ["c"]);
+        assert!(!pending.hold(sid, &swap("d", 4)));
+    }
+}